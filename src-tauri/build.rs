@@ -1,3 +1,19 @@
 fn main() {
+    println!("cargo:rerun-if-changed=tauri.conf.json");
+    println!("cargo:rerun-if-env-changed=EMAIL_MANAGER_KEYRING_SERVICE");
+
+    // keyring服务名：优先使用构建时环境变量，否则取tauri.conf.json中的应用标识符，
+    // 不同标识符的构建（如稳定版和测试版）使用各自的keyring命名空间
+    let service = std::env::var("EMAIL_MANAGER_KEYRING_SERVICE")
+        .ok()
+        .filter(|service| !service.trim().is_empty())
+        .or_else(|| {
+            let config = std::fs::read_to_string("tauri.conf.json").ok()?;
+            let config: serde_json::Value = serde_json::from_str(&config).ok()?;
+            config["identifier"].as_str().map(str::to_string)
+        })
+        .unwrap_or_else(|| "email-manager-2925".to_string());
+    println!("cargo:rustc-env=KEYRING_SERVICE={}", service);
+
     tauri_build::build()
 }
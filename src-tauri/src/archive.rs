@@ -0,0 +1,64 @@
+//! 本地邮件归档模块
+//! 
+//! 将原始邮件以gzip压缩保存在应用数据目录的 `archive/` 下，
+//! 服务器上的邮件删除后仍可离线读取；归档索引由调用方保存在store.json中
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::i18n::tr;
+
+/// 归档目录名
+const ARCHIVE_DIR: &str = "archive";
+
+/// 归档索引读-改-写锁，避免同时归档多封邮件时丢失索引条目
+#[derive(Default)]
+pub struct ArchiveLock(pub async_std::sync::Mutex<()>);
+
+/// 归档目录路径，不存在时创建
+/// 
+/// # 参数
+/// * `app_data_dir` - 应用数据目录
+pub fn archive_dir(app_data_dir: &Path) -> Result<PathBuf, String> {
+    let dir = app_data_dir.join(ARCHIVE_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("{}: {}", tr("归档邮件失败"), e))?;
+    Ok(dir)
+}
+
+/// 压缩并写入一封原始邮件，返回文件路径
+/// 
+/// # 参数
+/// * `dir` - 归档目录
+/// * `id` - 归档ID，用作文件名
+/// * `raw` - RFC822原始邮件
+pub fn write_message(dir: &Path, id: &str, raw: &[u8]) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.eml.gz", id));
+    
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(raw)
+        .map_err(|e| format!("{}: {}", tr("归档邮件失败"), e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("{}: {}", tr("归档邮件失败"), e))?;
+    
+    std::fs::write(&path, compressed)
+        .map_err(|e| format!("{}: {}", tr("归档邮件失败"), e))?;
+    Ok(path)
+}
+
+/// 读取并解压一封归档邮件
+pub fn read_message(path: &Path) -> Result<Vec<u8>, String> {
+    let compressed = std::fs::read(path)
+        .map_err(|e| format!("{} {}: {}", tr("读取归档失败"), path.display(), e))?;
+    
+    let mut raw = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut raw)
+        .map_err(|e| format!("{} {}: {}", tr("读取归档失败"), path.display(), e))?;
+    Ok(raw)
+}
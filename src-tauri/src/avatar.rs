@@ -0,0 +1,89 @@
+//! 发件人头像模块
+//! 
+//! 根据发件人地址计算稳定的头像颜色和Gravatar哈希，
+//! 界面无需网络请求即可为同一发件人显示一致的头像
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::email::Email;
+
+/// 头像颜色的饱和度和亮度，只由色相区分发件人，保证白色文字在任何颜色上都清晰可读
+const SATURATION: f64 = 0.55;
+const LIGHTNESS: f64 = 0.45;
+
+/// 从 `显示名称 <地址>` 形式的发件人中取出小写地址，无法解析时使用整个值
+fn sender_address(from: &str) -> Option<String> {
+    let address = match mailparse::addrparse(from) {
+        Ok(list) => list.iter().find_map(|addr| match addr {
+            mailparse::MailAddr::Single(info) => Some(info.addr.clone()),
+            mailparse::MailAddr::Group(group) => group.addrs.first().map(|info| info.addr.clone()),
+        }),
+        Err(_) => None,
+    }
+    .unwrap_or_else(|| from.to_string());
+    
+    let address = address.trim().to_lowercase();
+    (!address.is_empty()).then_some(address)
+}
+
+/// 由地址的SHA-256计算色相，返回 `#rrggbb` 格式的颜色；同一地址在任何设备和版本上得到相同颜色
+pub fn sender_color(address: &str) -> String {
+    let digest = Sha256::digest(address.as_bytes());
+    let hue = u16::from_be_bytes([digest[0], digest[1]]) as f64 / 65536.0 * 360.0;
+    
+    // HSL转RGB
+    let chroma = (1.0 - (2.0 * LIGHTNESS - 1.0).abs()) * SATURATION;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = LIGHTNESS - chroma / 2.0;
+    let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+    
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+/// Gravatar哈希：小写地址的MD5（十六进制），用于 `https://gravatar.com/avatar/<hash>`
+pub fn gravatar_hash(address: &str) -> String {
+    Md5::digest(address.trim().to_lowercase().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 根据邮件的发件人设置 `senderColor` 和 `gravatarHash`
+pub fn annotate(email: &mut Email) {
+    let Some(address) = sender_address(&email.from) else {
+        return;
+    };
+    email.sender_color = Some(sender_color(&address));
+    email.gravatar_hash = Some(gravatar_hash(&address));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gravatar_hash_matches_rfc_1321_vectors() {
+        assert_eq!(gravatar_hash(""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(gravatar_hash("abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(gravatar_hash("message digest"), "f96b697d7cb7938d525a2f31aaf161d0");
+        assert_eq!(
+            gravatar_hash("12345678901234567890123456789012345678901234567890123456789012345678901234567890"),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+
+    #[test]
+    fn gravatar_hash_uses_lowercase_md5() {
+        // Gravatar文档中的示例地址
+        assert_eq!(gravatar_hash("MyEmailAddress@example.com "), "0bc83cb571cd1c50ba6f3e8a78ef1346");
+    }
+}
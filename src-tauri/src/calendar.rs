@@ -0,0 +1,222 @@
+//! 日历邀请（text/calendar）解析模块
+//! 
+//! 会议邀请以iCalendar（RFC 5545）格式的 `text/calendar` 部分发送，
+//! 这里只解析第一个VEVENT中界面显示邀请卡片所需的字段
+
+use mailparse::ParsedMail;
+use serde::{Deserialize, Serialize};
+
+/// 日历邀请中的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    /// iTIP方法，如 `REQUEST`、`CANCEL`、`REPLY`
+    pub method: Option<String>,
+    /// 会议被取消（METHOD为CANCEL或事件STATUS为CANCELLED）
+    pub cancelled: bool,
+    pub summary: Option<String>,
+    /// 开始时间：UTC时间为 `2024-01-02T09:30:00Z`，带时区或浮动时间不带 `Z`，全天事件为 `2024-01-02`
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// 开始时间的TZID参数
+    pub timezone: Option<String>,
+    #[serde(rename = "allDay")]
+    pub all_day: bool,
+    /// 组织者，格式为 `显示名称 <地址>`
+    pub organizer: Option<String>,
+    pub location: Option<String>,
+    pub uid: Option<String>,
+}
+
+/// 一行iCalendar属性
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+}
+
+/// 展开折行：以空格或制表符开头的行是上一行的续行
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// 解析 `NAME;PARAM=VALUE:值` 形式的属性行
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // 参数值可以用引号包围并包含冒号，值开始于引号外的第一个冒号
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(index),
+        _ => None,
+    })?;
+    
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value))
+        .collect();
+    
+    Some(Property { name, params, value })
+}
+
+/// 还原TEXT值中的转义字符
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// 将 `20240102T093000Z` / `20240102` 转换为ISO 8601格式，无法识别时原样返回
+fn format_date_time(value: &str) -> String {
+    let value = value.trim();
+    let is_digits = |range: std::ops::Range<usize>| {
+        value.get(range).is_some_and(|s| s.bytes().all(|b| b.is_ascii_digit()))
+    };
+    if !is_digits(0..8) {
+        return value.to_string();
+    }
+    
+    let date = format!("{}-{}-{}", &value[0..4], &value[4..6], &value[6..8]);
+    if value.len() == 8 {
+        return date;
+    }
+    if value.get(8..9) != Some("T") || !is_digits(9..15) {
+        return value.to_string();
+    }
+    
+    let utc = if value.ends_with('Z') { "Z" } else { "" };
+    format!("{}T{}:{}:{}{}", date, &value[9..11], &value[11..13], &value[13..15], utc)
+}
+
+/// 格式化组织者：`CN=名称` 参数和 `mailto:` 地址
+fn format_organizer(property: &Property) -> Option<String> {
+    let value = property.value.trim();
+    let address = value
+        .strip_prefix("mailto:")
+        .or_else(|| value.strip_prefix("MAILTO:"))
+        .unwrap_or(value);
+    match property.param("CN").map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) if !address.is_empty() => Some(format!("{} <{}>", name, address)),
+        Some(name) => Some(name.to_string()),
+        None if !address.is_empty() => Some(address.to_string()),
+        None => None,
+    }
+}
+
+/// 解析iCalendar文本中的第一个VEVENT
+pub fn parse_ics(text: &str) -> Option<CalendarEvent> {
+    let mut method = None;
+    let mut event: Option<CalendarEvent> = None;
+    let mut in_event = false;
+    let mut status_cancelled = false;
+    // 嵌套在VEVENT中的组件（如VALARM）的属性不属于事件本身
+    let mut nested_depth = 0;
+    
+    for line in unfold(text) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+        let value = property.value.trim();
+        
+        match property.name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") && event.is_none() => {
+                in_event = true;
+                event = Some(CalendarEvent {
+                    method: None,
+                    cancelled: false,
+                    summary: None,
+                    start: None,
+                    end: None,
+                    timezone: None,
+                    all_day: false,
+                    organizer: None,
+                    location: None,
+                    uid: None,
+                });
+            }
+            "BEGIN" if in_event => nested_depth += 1,
+            "END" if in_event && nested_depth > 0 => nested_depth -= 1,
+            "END" if in_event && value.eq_ignore_ascii_case("VEVENT") => in_event = false,
+            "METHOD" if !in_event => method = Some(value.to_ascii_uppercase()),
+            _ if in_event && nested_depth == 0 => {
+                let Some(event) = event.as_mut() else {
+                    continue;
+                };
+                match property.name.as_str() {
+                    "SUMMARY" => event.summary = Some(unescape(value)),
+                    "LOCATION" => event.location = Some(unescape(value)).filter(|location| !location.is_empty()),
+                    "UID" => event.uid = Some(value.to_string()),
+                    "ORGANIZER" => event.organizer = format_organizer(&property),
+                    "STATUS" => status_cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+                    "DTSTART" => {
+                        event.start = Some(format_date_time(value));
+                        event.timezone = property.param("TZID").map(str::to_string);
+                        event.all_day = property.param("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+                            || value.len() == 8;
+                    }
+                    "DTEND" => event.end = Some(format_date_time(value)),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    let mut event = event?;
+    event.cancelled = status_cancelled || method.as_deref() == Some("CANCEL");
+    event.method = method;
+    Some(event)
+}
+
+/// 按深度优先顺序查找第一个日历部分（`text/calendar` 或 `application/ics` 附件）
+fn find_calendar<'a>(part: &'a ParsedMail<'a>) -> Option<&'a ParsedMail<'a>> {
+    let mimetype = part.ctype.mimetype.to_ascii_lowercase();
+    if mimetype == "text/calendar" || mimetype == "application/ics" {
+        return Some(part);
+    }
+    part.subparts.iter().find_map(find_calendar)
+}
+
+/// 邮件中的日历邀请，没有日历部分或其中没有VEVENT时返回None
+/// 部分邮件客户端只在日历部分的Content-Type中声明method，VCALENDAR中缺少METHOD时以此为准
+pub fn calendar_event(parsed: &ParsedMail) -> Option<CalendarEvent> {
+    let part = find_calendar(parsed)?;
+    let mut event = parse_ics(&crate::decode_part_text(part)?)?;
+    
+    if event.method.is_none() {
+        event.method = part.ctype.params.get("method").map(|method| method.to_ascii_uppercase());
+        event.cancelled |= event.method.as_deref() == Some("CANCEL");
+    }
+    Some(event)
+}
@@ -0,0 +1,86 @@
+//! 账户连接状态模块
+//! 
+//! 记录每个账户当前的IMAP连接状态，状态变化时发送 `connection-status` 事件，
+//! 界面据此实时显示连接指示，而不必从命令失败中推断
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+/// 连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionState {
+    /// 首次建立连接
+    Connecting,
+    /// 已连接
+    Connected,
+    /// 连接断开后重新连接
+    Reconnecting,
+    /// 无法连接服务器
+    Offline,
+}
+
+/// 单个账户的连接状态，也是 `connection-status` 事件的负载
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStatus {
+    pub account: String,
+    pub state: ConnectionState,
+    /// 状态说明，如离线时的错误信息
+    pub detail: Option<String>,
+}
+
+/// 各账户连接状态，作为应用状态管理
+#[derive(Default)]
+pub struct ConnectionStatusTracker(Mutex<HashMap<String, ConnectionStatus>>);
+
+impl ConnectionStatusTracker {
+    /// 当前所有账户的连接状态
+    pub fn snapshot(&self) -> Vec<ConnectionStatus> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+
+    fn state_of(&self, account: &str) -> Option<ConnectionState> {
+        self.0.lock().unwrap().get(account).map(|status| status.state)
+    }
+}
+
+/// 更新账户的连接状态，状态变化时发送 `connection-status` 事件
+/// 
+/// # 参数
+/// * `app` - 应用句柄
+/// * `account` - 账户邮箱
+/// * `state` - 新状态
+/// * `detail` - 状态说明
+pub fn update(app: &tauri::AppHandle, account: &str, state: ConnectionState, detail: Option<String>) {
+    let tracker = app.state::<ConnectionStatusTracker>();
+    let status = ConnectionStatus {
+        account: account.to_string(),
+        state,
+        detail,
+    };
+    
+    let changed = {
+        let mut statuses = tracker.0.lock().unwrap();
+        let changed = statuses.get(account).map(|previous| previous.state) != Some(state);
+        statuses.insert(account.to_string(), status.clone());
+        changed
+    };
+    
+    if changed {
+        let _ = app.emit("connection-status", status);
+    }
+}
+
+/// 即将获取连接时调用：已连接时不变，之前离线则为重新连接，否则为首次连接
+pub fn begin_connect(app: &tauri::AppHandle, account: &str) {
+    let tracker = app.state::<ConnectionStatusTracker>();
+    match tracker.state_of(account) {
+        Some(ConnectionState::Connected) => {}
+        Some(ConnectionState::Offline) | Some(ConnectionState::Reconnecting) => {
+            update(app, account, ConnectionState::Reconnecting, None)
+        }
+        _ => update(app, account, ConnectionState::Connecting, None),
+    }
+}
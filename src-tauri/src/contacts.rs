@@ -0,0 +1,198 @@
+//! 联系人建议模块
+//! 
+//! 从获取到的邮件的From、To和Cc中收集地址，按账户保存在存储的 `contacts` 中，用于写邮件时自动补全。
+//! 每个文件夹记录已统计过的最大UID，重复获取同一封邮件不会重复计数；
+//! 每个账户最多保留 `MAX_CONTACTS` 个地址，超出时淘汰得分最低的
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+use crate::email::Email;
+use crate::persist;
+
+/// 每个账户保留的联系人上限
+const MAX_CONTACTS: usize = 1000;
+/// 得分的半衰期（天）：多久没有往来时，往来次数的权重减半
+const HALF_LIFE_DAYS: f64 = 30.0;
+/// 每次最多返回的建议数
+pub const MAX_SUGGESTIONS: usize = 50;
+
+/// 联系人
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    /// 邮箱地址（小写）
+    pub address: String,
+    /// 最近一次出现的显示名称，已按RFC 2047解码
+    pub name: Option<String>,
+    /// 出现在多少封邮件中
+    pub count: u32,
+    /// 最近一次出现的邮件时间（毫秒）
+    #[serde(rename = "lastSeen")]
+    pub last_seen: i64,
+}
+
+impl Contact {
+    /// 按往来次数和最近往来时间计算的排序得分
+    fn score(&self, now: i64) -> f64 {
+        let age_days = (now - self.last_seen).max(0) as f64 / 86_400_000.0;
+        self.count as f64 * 0.5_f64.powf(age_days / HALF_LIFE_DAYS)
+    }
+
+    fn matches(&self, prefix: &str) -> bool {
+        if self.address.starts_with(prefix) {
+            return true;
+        }
+        // 显示名称中任意一个词以前缀开头也算匹配，如 "Zhang San" 匹配 "san"
+        self.name.as_deref().is_some_and(|name| {
+            let name = name.to_lowercase();
+            name.starts_with(prefix) || name.split_whitespace().any(|word| word.starts_with(prefix))
+        })
+    }
+}
+
+/// 一个账户的联系人索引
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ContactIndex {
+    contacts: Vec<Contact>,
+    /// 文件夹名称到已统计的最大UID的映射
+    indexed: HashMap<String, u32>,
+}
+
+/// 联系人索引读-改-写锁，避免并发获取时互相覆盖
+#[derive(Default)]
+pub struct ContactsLock(async_std::sync::Mutex<()>);
+
+fn load(app: &tauri::AppHandle) -> HashMap<String, ContactIndex> {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("contacts"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, indexes: &HashMap<String, ContactIndex>) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("contacts", serde_json::to_value(indexes).unwrap());
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save contacts: {}", e))
+}
+
+/// 邮件的发件人、收件人和抄送中的地址和显示名称
+fn addresses(email: &Email) -> Vec<(String, Option<String>)> {
+    [Some(&email.from), Some(&email.to), email.cc.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|value| mailparse::addrparse(value).ok())
+        .flat_map(|list| {
+            list.iter()
+                .flat_map(|addr| match addr {
+                    mailparse::MailAddr::Single(info) => vec![info.clone()],
+                    mailparse::MailAddr::Group(group) => group.addrs.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|info| crate::smtp::is_valid_address(&info.addr))
+        .map(|info| {
+            let name = info.display_name.map(|name| name.trim().trim_matches('"').trim().to_string());
+            (info.addr.to_lowercase(), name.filter(|name| !name.is_empty()))
+        })
+        .collect()
+}
+
+/// 将获取到的邮件计入联系人索引
+/// 
+/// 只统计UID大于该文件夹已统计的最大UID的邮件；账户自己的地址不计入
+/// 
+/// # 参数
+/// * `account` - 账户邮箱
+/// * `folder` - 邮件所在的文件夹
+/// * `emails` - 获取到的邮件
+pub async fn record(
+    app: &tauri::AppHandle,
+    account: &str,
+    folder: &str,
+    emails: &[Email],
+) -> Result<(), String> {
+    let lock = app.state::<ContactsLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut indexes = load(app);
+    let index = indexes.entry(account.to_string()).or_default();
+    let watermark = index.indexed.get(folder).copied().unwrap_or(0);
+    
+    let mut by_address: HashMap<String, Contact> = index
+        .contacts
+        .drain(..)
+        .map(|contact| (contact.address.clone(), contact))
+        .collect();
+    let mut highest = watermark;
+    for email in emails {
+        let Some(uid) = email.uid() else {
+            continue;
+        };
+        if uid <= watermark {
+            continue;
+        }
+        highest = highest.max(uid);
+        let timestamp = email.timestamp;
+        
+        // 同一封邮件中重复出现的地址只计一次
+        let mut seen = std::collections::HashSet::new();
+        for (address, name) in addresses(email) {
+            if address.eq_ignore_ascii_case(account) || !seen.insert(address.clone()) {
+                continue;
+            }
+            let contact = by_address.entry(address.clone()).or_insert_with(|| Contact {
+                address,
+                name: None,
+                count: 0,
+                last_seen: 0,
+            });
+            contact.count = contact.count.saturating_add(1);
+            if timestamp >= contact.last_seen {
+                contact.last_seen = timestamp;
+                if name.is_some() {
+                    contact.name = name;
+                }
+            } else if contact.name.is_none() {
+                contact.name = name;
+            }
+        }
+    }
+    if highest == watermark {
+        return Ok(());
+    }
+    
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut contacts: Vec<Contact> = by_address.into_values().collect();
+    contacts.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+    contacts.truncate(MAX_CONTACTS);
+    index.contacts = contacts;
+    index.indexed.insert(folder.to_string(), highest);
+    save(app, &indexes)
+}
+
+/// 地址或显示名称以 `prefix`（不区分大小写）开头的联系人，按得分从高到低排列
+pub fn suggestions(app: &tauri::AppHandle, account: &str, prefix: &str, limit: usize) -> Vec<Contact> {
+    let prefix = prefix.trim().to_lowercase();
+    let now = chrono::Utc::now().timestamp_millis();
+    
+    let mut matches: Vec<Contact> = load(app)
+        .remove(account)
+        .unwrap_or_default()
+        .contacts
+        .into_iter()
+        .filter(|contact| contact.matches(&prefix))
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score(now)
+            .total_cmp(&a.score(now))
+            .then_with(|| b.last_seen.cmp(&a.last_seen))
+    });
+    matches.truncate(limit);
+    matches
+}
@@ -1,8 +1,8 @@
-/// 三层加密模块
-/// 
-/// 第一层：AES-256-GCM加密（使用机器特征派生的密钥）
-/// 第二层：基于用户邮箱的PBKDF2密钥派生
-/// 第三层：操作系统级别的keyring存储
+//! 三层加密模块
+//! 
+//! 第一层：AES-256-GCM加密（使用机器特征派生的密钥）
+//! 第二层：基于用户邮箱的PBKDF2密钥派生
+//! 第三层：操作系统级别的keyring存储
 
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
@@ -13,6 +13,12 @@ use sha2::Sha256;
 use rand::RngCore;
 use base64::{Engine as _, engine::general_purpose};
 use keyring::Entry;
+use serde::Serialize;
+
+/// keyring服务名，构建时由应用标识符（或 `EMAIL_MANAGER_KEYRING_SERVICE` 环境变量）生成
+pub const KEYRING_SERVICE: &str = env!("KEYRING_SERVICE");
+/// 旧版本固定使用的keyring服务名，读取时迁移一次
+const LEGACY_KEYRING_SERVICE: &str = "email-manager-2925";
 
 const PBKDF2_ITERATIONS: u32 = 100_000;
 const SALT_LENGTH: usize = 32;
@@ -217,14 +223,23 @@ fn layer2_decrypt(encrypted_data: &[u8], email: &str) -> Result<Vec<u8>, CryptoE
     Ok(plaintext)
 }
 
+/// keyring用户名，由邮箱和服务器组成，避免不同服务器上的同名账户互相覆盖
+/// 
+/// # 参数
+/// * `email` - 用户邮箱
+/// * `server` - IMAP服务器地址
+pub fn keyring_account(email: &str, server: &str) -> String {
+    format!("{}@{}", email, server)
+}
+
 /// 第三层：使用操作系统keyring存储
 /// 
 /// # 参数
-/// * `email` - 用户邮箱（作为keyring的用户名）
+/// * `account` - keyring用户名
 /// * `encrypted_data` - 第二层加密后的数据
-pub fn layer3_save(email: &str, encrypted_data: &[u8]) -> Result<(), CryptoError> {
+pub fn layer3_save(account: &str, encrypted_data: &[u8]) -> Result<(), CryptoError> {
     // 创建keyring条目
-    let entry = Entry::new("email-manager-2925", email)
+    let entry = Entry::new(KEYRING_SERVICE, account)
         .map_err(|e| CryptoError::KeyringError(format!("创建keyring条目失败: {}", e)))?;
     
     // 将二进制数据编码为base64
@@ -238,13 +253,32 @@ pub fn layer3_save(email: &str, encrypted_data: &[u8]) -> Result<(), CryptoError
     Ok(())
 }
 
-/// 第三层：从操作系统keyring读取
+/// 可能保存了该账户密码的keyring位置（服务名, 用户名），按读取顺序排列：
+/// 当前服务名和旧服务名下带服务器的用户名，然后是旧版本仅以邮箱作为用户名的条目。
+/// 第一项是保存密码时使用的位置
 /// 
 /// # 参数
 /// * `email` - 用户邮箱
-pub fn layer3_load(email: &str) -> Result<Vec<u8>, CryptoError> {
+/// * `server` - IMAP服务器地址
+fn keyring_locations(email: &str, server: &str) -> Vec<(&'static str, String)> {
+    let mut locations = Vec::new();
+    for user in [keyring_account(email, server), email.to_string()] {
+        locations.push((KEYRING_SERVICE, user.clone()));
+        if KEYRING_SERVICE != LEGACY_KEYRING_SERVICE {
+            locations.push((LEGACY_KEYRING_SERVICE, user));
+        }
+    }
+    locations
+}
+
+/// 第三层：从操作系统keyring读取
+/// 
+/// # 参数
+/// * `service` - keyring服务名
+/// * `account` - keyring用户名
+fn layer3_load_from(service: &str, account: &str) -> Result<Vec<u8>, CryptoError> {
     // 创建keyring条目
-    let entry = Entry::new("email-manager-2925", email)
+    let entry = Entry::new(service, account)
         .map_err(|e| CryptoError::KeyringError(format!("创建keyring条目失败: {}", e)))?;
     
     // 从keyring读取
@@ -263,10 +297,9 @@ pub fn layer3_load(email: &str) -> Result<Vec<u8>, CryptoError> {
 /// 第三层：从操作系统keyring删除
 /// 
 /// # 参数
-/// * `email` - 用户邮箱
-#[allow(dead_code)]
-pub fn layer3_delete(email: &str) -> Result<(), CryptoError> {
-    let entry = Entry::new("email-manager-2925", email)
+/// * `account` - keyring用户名
+pub fn layer3_delete(account: &str) -> Result<(), CryptoError> {
+    let entry = Entry::new(KEYRING_SERVICE, account)
         .map_err(|e| CryptoError::KeyringError(format!("创建keyring条目失败: {}", e)))?;
     
     entry
@@ -281,7 +314,8 @@ pub fn layer3_delete(email: &str) -> Result<(), CryptoError> {
 /// # 参数
 /// * `password` - 明文密码
 /// * `email` - 用户邮箱
-pub fn encrypt_and_save_password(password: &str, email: &str) -> Result<(), CryptoError> {
+/// * `server` - IMAP服务器地址
+pub fn encrypt_and_save_password(password: &str, email: &str, server: &str) -> Result<(), CryptoError> {
     // 第一层：使用机器ID加密
     let layer1_encrypted = layer1_encrypt(password.as_bytes(), email)?;
     
@@ -289,18 +323,39 @@ pub fn encrypt_and_save_password(password: &str, email: &str) -> Result<(), Cryp
     let layer2_encrypted = layer2_encrypt(&layer1_encrypted, email)?;
     
     // 第三层：保存到keyring
-    layer3_save(email, &layer2_encrypted)?;
+    layer3_save(&keyring_account(email, server), &layer2_encrypted)?;
     
     Ok(())
 }
 
 /// 三层解密读取密码
 /// 
+/// 按 `keyring_locations` 的顺序读取，条目在旧位置（旧用户名或旧服务名）时
+/// 迁移到当前位置
+/// 
 /// # 参数
 /// * `email` - 用户邮箱
-pub fn load_and_decrypt_password(email: &str) -> Result<String, CryptoError> {
+/// * `server` - IMAP服务器地址
+pub fn load_and_decrypt_password(email: &str, server: &str) -> Result<String, CryptoError> {
     // 第三层：从keyring读取
-    let layer2_encrypted = layer3_load(email)?;
+    let mut locations = keyring_locations(email, server).into_iter();
+    let (current_service, account) = locations.next().expect("至少有一个keyring位置");
+    let layer2_encrypted = match layer3_load_from(current_service, &account) {
+        Ok(data) => data,
+        Err(e) => {
+            let Some((service, user, legacy)) = locations.find_map(|(service, user)| {
+                layer3_load_from(service, &user).ok().map(|data| (service, user, data))
+            }) else {
+                return Err(e);
+            };
+            layer3_save(&account, &legacy)?;
+            // 迁移成功后旧条目已无用，删除失败不影响读取
+            if let Ok(entry) = Entry::new(service, &user) {
+                let _ = entry.delete_password();
+            }
+            legacy
+        }
+    };
     
     // 第二层：使用邮箱解密
     let layer1_encrypted = layer2_decrypt(&layer2_encrypted, email)?;
@@ -315,11 +370,205 @@ pub fn load_and_decrypt_password(email: &str) -> Result<String, CryptoError> {
     Ok(password)
 }
 
+/// 凭据诊断结果代码，标明读取保存的密码时失败的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CredentialStatus {
+    /// 密码可以正常读取和解密
+    Ok,
+    /// keyring中没有该账户的条目（未保存密码或已被删除）
+    KeyringMissing,
+    /// 无法访问keyring（被锁定、拒绝访问或服务不可用）
+    KeyringAccessDenied,
+    /// keyring中的数据不是有效的base64
+    KeyringCorrupted,
+    /// 第二层（邮箱派生密钥）解密失败
+    Layer2Failed,
+    /// 第一层解密失败，通常是重装系统或更换机器后机器ID已变化
+    MachineIdMismatch,
+    /// 无法获取当前机器ID
+    MachineIdUnavailable,
+    /// 解密结果不是有效的UTF-8文本
+    InvalidUtf8,
+}
+
+/// 凭据诊断结果，不包含密码本身
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialDiagnosis {
+    pub status: CredentialStatus,
+    /// 失败阶段的错误详情，成功时为None
+    pub detail: Option<String>,
+    /// 条目是否仍保存在旧的keyring位置（仅邮箱的用户名或旧服务名）下
+    #[serde(rename = "legacyEntry")]
+    pub legacy_entry: bool,
+}
+
+impl CredentialDiagnosis {
+    fn failed(status: CredentialStatus, detail: impl std::fmt::Display, legacy_entry: bool) -> Self {
+        CredentialDiagnosis {
+            status,
+            detail: Some(detail.to_string()),
+            legacy_entry,
+        }
+    }
+}
+
+/// 读取keyring条目，区分条目不存在和无法访问keyring
+fn keyring_read(service: &str, account: &str) -> Result<String, (CredentialStatus, String)> {
+    let entry = Entry::new(service, account)
+        .map_err(|e| (CredentialStatus::KeyringAccessDenied, e.to_string()))?;
+    entry.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => (CredentialStatus::KeyringMissing, e.to_string()),
+        _ => (CredentialStatus::KeyringAccessDenied, e.to_string()),
+    })
+}
+
+/// 诊断保存的密码能否读取，逐层检查并报告失败的阶段
+/// 与 `load_and_decrypt_password` 的读取顺序相同，但不迁移旧条目，也不返回密码
+/// 
+/// # 参数
+/// * `email` - 用户邮箱
+/// * `server` - IMAP服务器地址
+pub fn diagnose_password(email: &str, server: &str) -> CredentialDiagnosis {
+    // 第三层：keyring读取，当前位置没有条目时依次检查旧位置
+    let mut found = None;
+    let mut missing_detail = String::new();
+    for (index, (service, user)) in keyring_locations(email, server).into_iter().enumerate() {
+        match keyring_read(service, &user) {
+            Ok(encoded) => {
+                found = Some((encoded, index > 0));
+                break;
+            }
+            Err((CredentialStatus::KeyringMissing, detail)) => {
+                if index == 0 {
+                    missing_detail = detail;
+                }
+            }
+            Err((status, detail)) => return CredentialDiagnosis::failed(status, detail, index > 0),
+        }
+    }
+    let Some((encoded, legacy_entry)) = found else {
+        return CredentialDiagnosis::failed(CredentialStatus::KeyringMissing, missing_detail, false);
+    };
+    
+    let layer2_encrypted = match general_purpose::STANDARD.decode(encoded) {
+        Ok(data) => data,
+        Err(e) => return CredentialDiagnosis::failed(CredentialStatus::KeyringCorrupted, e, legacy_entry),
+    };
+    
+    // 第二层：邮箱派生密钥解密
+    let layer1_encrypted = match layer2_decrypt(&layer2_encrypted, email) {
+        Ok(data) => data,
+        Err(e) => return CredentialDiagnosis::failed(CredentialStatus::Layer2Failed, e, legacy_entry),
+    };
+    
+    // 第一层：机器ID派生密钥解密
+    if let Err(e) = get_machine_id() {
+        return CredentialDiagnosis::failed(CredentialStatus::MachineIdUnavailable, e, legacy_entry);
+    }
+    let password_bytes = match layer1_decrypt(&layer1_encrypted, email) {
+        Ok(data) => data,
+        Err(e) => return CredentialDiagnosis::failed(CredentialStatus::MachineIdMismatch, e, legacy_entry),
+    };
+    
+    if let Err(e) = String::from_utf8(password_bytes) {
+        return CredentialDiagnosis::failed(CredentialStatus::InvalidUtf8, e.utf8_error(), legacy_entry);
+    }
+    
+    CredentialDiagnosis {
+        status: CredentialStatus::Ok,
+        detail: None,
+        legacy_entry,
+    }
+}
+
+/// 读取所有可能保存了该账户密码的keyring位置，返回能解密的位置（`服务名/用户名`）及密码
+/// 查找顺序与 `load_and_decrypt_password` 相同，第一项即为登录时使用的密码；不迁移旧条目
+/// 
+/// # 参数
+/// * `email` - 用户邮箱
+/// * `server` - IMAP服务器地址
+pub fn stored_passwords(email: &str, server: &str) -> Vec<(String, String)> {
+    keyring_locations(email, server)
+        .into_iter()
+        .filter_map(|(service, user)| {
+            let layer2_encrypted = layer3_load_from(service, &user).ok()?;
+            let layer1_encrypted = layer2_decrypt(&layer2_encrypted, email).ok()?;
+            let password = String::from_utf8(layer1_decrypt(&layer1_encrypted, email).ok()?).ok()?;
+            Some((format!("{}/{}", service, user), password))
+        })
+        .collect()
+}
+
+/// keyring后端信息
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyringInfo {
+    /// 能否访问keyring
+    pub available: bool,
+    /// 编译时选择的后端：`secret-service`、`macos-keychain`、`windows-credential`，
+    /// 其他平台为 `mock`（仅保存在内存中，重启后丢失）
+    pub backend: String,
+    /// 无法访问时的错误详情
+    pub detail: Option<String>,
+}
+
+/// 当前平台使用的keyring后端名称
+fn keyring_backend() -> &'static str {
+    if cfg!(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")) {
+        "secret-service"
+    } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+        "macos-keychain"
+    } else if cfg!(target_os = "windows") {
+        "windows-credential"
+    } else {
+        "mock"
+    }
+}
+
+/// 探测keyring是否可用
+/// 读取一个不存在的探测条目：返回“条目不存在”说明后端可以访问，不会创建或修改任何条目
+pub fn keyring_info() -> KeyringInfo {
+    let backend = keyring_backend().to_string();
+    let probe = Entry::new(KEYRING_SERVICE, "__keyring_probe__")
+        .and_then(|entry| entry.get_password());
+    
+    let detail = match probe {
+        Ok(_) | Err(keyring::Error::NoEntry) => None,
+        Err(e) => Some(e.to_string()),
+    };
+    
+    KeyringInfo {
+        available: detail.is_none() && backend != "mock",
+        backend,
+        detail,
+    }
+}
+
 /// 删除保存的密码
 /// 
 /// # 参数
 /// * `email` - 用户邮箱
+/// * `server` - IMAP服务器地址
 #[allow(dead_code)]
-pub fn delete_saved_password(email: &str) -> Result<(), CryptoError> {
-    layer3_delete(email)
+pub fn delete_saved_password(email: &str, server: &str) -> Result<(), CryptoError> {
+    layer3_delete(&keyring_account(email, server))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyring_locations_prefer_server_account_then_service() {
+        let locations = keyring_locations("user@2925.com", "imap.2925.com");
+        let mut expected = vec![(KEYRING_SERVICE, String::from("user@2925.com@imap.2925.com"))];
+        if KEYRING_SERVICE != LEGACY_KEYRING_SERVICE {
+            expected.push((LEGACY_KEYRING_SERVICE, String::from("user@2925.com@imap.2925.com")));
+        }
+        expected.push((KEYRING_SERVICE, String::from("user@2925.com")));
+        if KEYRING_SERVICE != LEGACY_KEYRING_SERVICE {
+            expected.push((LEGACY_KEYRING_SERVICE, String::from("user@2925.com")));
+        }
+        assert_eq!(locations, expected);
+    }
 }
@@ -0,0 +1,219 @@
+//! 投递状态通知（DSN）解析模块
+//! 
+//! 退信以 `multipart/report; report-type=delivery-status`（RFC 3462）发送，其中的
+//! `message/delivery-status` 部分（RFC 3464）由一组每封邮件的字段和每个收件人一组的字段组成，
+//! 各组之间以空行分隔。这里解析界面显示退信卡片所需的字段，并从附带的原邮件（或其头部）中取出
+//! Message-ID，便于界面关联到原邮件
+
+use mailparse::{MailHeaderMap, ParsedMail};
+use serde::{Deserialize, Serialize};
+
+/// 单个收件人的投递结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientStatus {
+    /// Final-Recipient中的地址，已去掉地址类型（如 `rfc822;`）
+    pub recipient: String,
+    /// Action字段（小写）：`failed`、`delayed`、`delivered`、`relayed` 或 `expanded`
+    pub action: String,
+    /// 状态码，如 `5.1.1`
+    pub status: Option<String>,
+    /// Diagnostic-Code中远程服务器给出的原因，已去掉诊断类型（如 `smtp;`）
+    pub diagnostic: Option<String>,
+}
+
+/// 退信中的投递状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryStatus {
+    /// 生成退信的MTA
+    #[serde(rename = "reportingMta")]
+    pub reporting_mta: Option<String>,
+    pub recipients: Vec<RecipientStatus>,
+    /// 原邮件的Message-ID（含尖括号），退信没有附带原邮件或其头部时为None
+    #[serde(rename = "originalMessageId")]
+    pub original_message_id: Option<String>,
+}
+
+/// 去掉 `类型; 值` 形式字段的类型部分
+fn strip_type(value: &str) -> String {
+    match value.split_once(';') {
+        Some((_, rest)) => rest.trim().to_string(),
+        None => value.trim().to_string(),
+    }
+}
+
+/// 按深度优先顺序查找第一个符合条件的部分
+fn find_part<'a>(part: &'a ParsedMail<'a>, matches: fn(&str) -> bool) -> Option<&'a ParsedMail<'a>> {
+    if matches(&part.ctype.mimetype.to_ascii_lowercase()) {
+        return Some(part);
+    }
+    part.subparts.iter().find_map(|subpart| find_part(subpart, matches))
+}
+
+/// 将 `message/delivery-status` 的正文按空行分为字段组
+fn field_groups(body: &[u8]) -> Vec<Vec<mailparse::MailHeader<'_>>> {
+    let mut groups = Vec::new();
+    let mut rest = body;
+    loop {
+        // 跳过组之间多余的空行
+        while let Some(stripped) = rest.strip_prefix(b"\r\n").or_else(|| rest.strip_prefix(b"\n")) {
+            rest = stripped;
+        }
+        if rest.is_empty() {
+            break;
+        }
+        let Ok((headers, end)) = mailparse::parse_headers(rest) else {
+            break;
+        };
+        if end == 0 {
+            break;
+        }
+        if !headers.is_empty() {
+            groups.push(headers);
+        }
+        rest = &rest[end..];
+    }
+    groups
+}
+
+/// 邮件中的投递状态，不是退信或退信中没有收件人的投递结果时返回None
+/// 也接受国际化退信的 `message/global-delivery-status`（RFC 6533），其字段可以包含UTF-8
+pub fn delivery_status(parsed: &ParsedMail) -> Option<DeliveryStatus> {
+    let part = find_part(parsed, |mimetype| {
+        mimetype == "message/delivery-status" || mimetype == "message/global-delivery-status"
+    })?;
+    let body = part.get_body_raw().ok()?;
+    let groups = field_groups(&body);
+    
+    // 第一组为每封邮件的字段，之后每组对应一个收件人
+    let reporting_mta = groups
+        .first()
+        .and_then(|fields| fields.get_first_value("Reporting-MTA"))
+        .map(|value| strip_type(&value));
+    let recipients: Vec<RecipientStatus> = groups
+        .iter()
+        .filter_map(|fields| {
+            let recipient = fields.get_first_value("Final-Recipient")?;
+            Some(RecipientStatus {
+                recipient: strip_type(&recipient),
+                action: fields.get_first_value("Action")?.trim().to_ascii_lowercase(),
+                status: fields.get_first_value("Status").map(|status| status.trim().to_string()),
+                diagnostic: fields
+                    .get_first_value("Diagnostic-Code")
+                    .map(|code| strip_type(&code))
+                    .filter(|code| !code.is_empty()),
+            })
+        })
+        .collect();
+    if recipients.is_empty() {
+        return None;
+    }
+    
+    // 原邮件以message/rfc822附带，或只附带头部（text/rfc822-headers）；mailparse不展开这两种部分
+    let original_message_id = find_part(parsed, |mimetype| {
+        matches!(mimetype, "message/rfc822" | "message/global" | "text/rfc822-headers" | "message/global-headers")
+    })
+    .and_then(|original| original.get_body_raw().ok())
+    .and_then(|raw| {
+        let (headers, _) = mailparse::parse_headers(&raw).ok()?;
+        headers.get_first_value("Message-ID")
+    })
+    .map(|id| id.trim().to_string())
+    .filter(|id| !id.is_empty());
+    
+    Some(DeliveryStatus {
+        reporting_mta,
+        recipients,
+        original_message_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Postfix生成的退信
+    const POSTFIX_BOUNCE: &str = "Return-Path: <>\r\n\
+From: MAILER-DAEMON@mx.2925.com (Mail Delivery System)\r\n\
+Subject: Undelivered Mail Returned to Sender\r\n\
+To: user@2925.com\r\n\
+Auto-Submitted: auto-replied\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/report; report-type=delivery-status;\r\n\
+\tboundary=\"6A1B2C3D4E.1760400000/mx.2925.com\"\r\n\
+Message-Id: <20261014000000.6A1B2C3D4E@mx.2925.com>\r\n\
+\r\n\
+This is a MIME-encapsulated message.\r\n\
+\r\n\
+--6A1B2C3D4E.1760400000/mx.2925.com\r\n\
+Content-Description: Notification\r\n\
+Content-Type: text/plain; charset=us-ascii\r\n\
+\r\n\
+This is the mail system at host mx.2925.com.\r\n\
+\r\n\
+I'm sorry to have to inform you that your message could not\r\n\
+be delivered to one or more recipients.\r\n\
+\r\n\
+<nobody@example.com>: host mx.example.com[203.0.113.5] said: 550 5.1.1\r\n\
+\t<nobody@example.com>: Recipient address rejected: User unknown\r\n\
+\r\n\
+--6A1B2C3D4E.1760400000/mx.2925.com\r\n\
+Content-Description: Delivery report\r\n\
+Content-Type: message/delivery-status\r\n\
+\r\n\
+Reporting-MTA: dns; mx.2925.com\r\n\
+X-Postfix-Queue-ID: 6A1B2C3D4E\r\n\
+X-Postfix-Sender: rfc822; user@2925.com\r\n\
+Arrival-Date: Wed, 14 Oct 2026 08:00:00 +0800 (CST)\r\n\
+\r\n\
+Final-Recipient: rfc822; nobody@example.com\r\n\
+Original-Recipient: rfc822;nobody@example.com\r\n\
+Action: failed\r\n\
+Status: 5.1.1\r\n\
+Remote-MTA: dns; mx.example.com\r\n\
+Diagnostic-Code: smtp; 550 5.1.1 <nobody@example.com>: Recipient address\r\n\
+\trejected: User unknown\r\n\
+\r\n\
+Final-Recipient: rfc822; later@example.com\r\n\
+Action: delayed\r\n\
+Status: 4.4.1\r\n\
+\r\n\
+--6A1B2C3D4E.1760400000/mx.2925.com\r\n\
+Content-Description: Undelivered Message Headers\r\n\
+Content-Type: text/rfc822-headers\r\n\
+\r\n\
+Return-Path: <user@2925.com>\r\n\
+From: user@2925.com\r\n\
+To: nobody@example.com\r\n\
+Subject: =?UTF-8?B?5rWL6K+V?=\r\n\
+Message-ID: <original-1@2925.com>\r\n\
+Date: Wed, 14 Oct 2026 08:00:00 +0800\r\n\
+\r\n\
+--6A1B2C3D4E.1760400000/mx.2925.com--\r\n";
+
+    #[test]
+    fn parses_postfix_bounce() {
+        let parsed = mailparse::parse_mail(POSTFIX_BOUNCE.as_bytes()).unwrap();
+        let status = delivery_status(&parsed).unwrap();
+        
+        assert_eq!(status.reporting_mta.as_deref(), Some("mx.2925.com"));
+        assert_eq!(status.original_message_id.as_deref(), Some("<original-1@2925.com>"));
+        assert_eq!(status.recipients.len(), 2);
+        
+        let failed = &status.recipients[0];
+        assert_eq!(failed.recipient, "nobody@example.com");
+        assert_eq!(failed.action, "failed");
+        assert_eq!(failed.status.as_deref(), Some("5.1.1"));
+        let diagnostic = failed.diagnostic.as_deref().unwrap();
+        assert!(diagnostic.starts_with("550 5.1.1 <nobody@example.com>: Recipient address"), "{}", diagnostic);
+        assert!(diagnostic.ends_with("rejected: User unknown"), "{}", diagnostic);
+        
+        assert_eq!(status.recipients[1].action, "delayed");
+        assert!(status.recipients[1].diagnostic.is_none());
+    }
+
+    #[test]
+    fn ordinary_message_has_no_status() {
+        let parsed = mailparse::parse_mail(b"From: a@example.com\r\nSubject: hi\r\n\r\nhello\r\n").unwrap();
+        assert!(delivery_status(&parsed).is_none());
+    }
+}
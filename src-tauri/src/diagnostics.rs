@@ -0,0 +1,243 @@
+//! 诊断包模块
+//! 
+//! 把最近的日志、keyring状态、服务器配置和连接统计打包为一个zip文件，用户反馈问题时附上即可。
+//! 写入包中的每个文件都先经过 `Redactor`：已知的密码（包括JSON转义后的形式）替换为占位文字，
+//! 可选地把邮箱地址的用户名部分替换为哈希。zip中的文件使用deflate压缩
+
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::i18n::tr;
+
+/// 诊断包中最多包含的日志文件数（按文件名中的日期取最近的）
+pub const MAX_LOG_FILES: usize = 14;
+/// 每个日志文件最多保留的末尾字节数
+const MAX_LOG_BYTES: usize = 1024 * 1024;
+/// 密码的占位文字
+const REDACTED: &str = "[REDACTED]";
+
+/// 写入诊断包前的脱敏处理
+pub struct Redactor {
+    /// 需要替换的原文，较长的在前，避免较短的密码先替换掉较长密码的一部分
+    secrets: Vec<String>,
+    hash_emails: bool,
+}
+
+impl Redactor {
+    /// # 参数
+    /// * `passwords` - 本机保存的所有密码，空字符串被忽略
+    /// * `hash_emails` - 是否把邮箱地址的用户名部分替换为哈希
+    pub fn new(passwords: impl IntoIterator<Item = String>, hash_emails: bool) -> Self {
+        let mut secrets: Vec<String> = passwords
+            .into_iter()
+            .filter(|password| !password.is_empty())
+            .flat_map(|password| {
+                // 密码出现在JSON中时引号、反斜杠等字符会被转义，两种形式都要替换
+                let escaped = serde_json::to_string(&password).unwrap();
+                let escaped = escaped[1..escaped.len() - 1].to_string();
+                [password, escaped]
+            })
+            .collect();
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+        Self { secrets, hash_emails }
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            text = text.replace(secret.as_str(), REDACTED);
+        }
+        if self.hash_emails {
+            text = hash_addresses(&text);
+        }
+        text
+    }
+}
+
+fn is_local_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "._%+-".contains(c)
+}
+
+fn is_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ".-".contains(c)
+}
+
+/// 把文本中 `local@domain` 形式的地址替换为 `[哈希]@domain`，保留域名便于判断服务商
+/// 已作为域名的部分不再作为下一个地址的用户名，如 `a@b.com@c.com` 只替换 `a`
+fn hash_addresses(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    // 上一个地址的域名结束的位置
+    let mut domain_end = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '@' {
+            output.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        
+        // 向前找用户名（已写入output），向后找域名
+        let local_len = chars[domain_end..i].iter().rev().take_while(|c| is_local_char(**c)).count();
+        let domain_len = chars[i + 1..].iter().take_while(|c| is_domain_char(**c)).count();
+        let domain: String = chars[i + 1..i + 1 + domain_len].iter().collect();
+        if local_len == 0 || !domain.contains('.') {
+            output.push('@');
+            i += 1;
+            continue;
+        }
+        
+        let local: String = chars[i - local_len..i].iter().collect();
+        output.truncate(output.len() - local.len());
+        let digest = Sha256::digest(local.to_lowercase().as_bytes());
+        let hash: String = digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect();
+        output.push_str(&format!("[{}]@{}", hash, domain));
+        i += 1 + domain_len;
+        domain_end = i;
+    }
+    output
+}
+
+/// 日志文件末尾 `MAX_LOG_BYTES` 字节，截断时从下一行开始
+pub fn read_log_tail(path: &Path) -> std::io::Result<String> {
+    let content = std::fs::read(path)?;
+    if content.len() <= MAX_LOG_BYTES {
+        return Ok(String::from_utf8_lossy(&content).into_owned());
+    }
+    let tail = &content[content.len() - MAX_LOG_BYTES..];
+    let start = tail.iter().position(|byte| *byte == b'\n').map_or(0, |pos| pos + 1);
+    Ok(String::from_utf8_lossy(&tail[start..]).into_owned())
+}
+
+/// 诊断包，内容在内存中组装，`write` 时一次写入磁盘
+pub struct Bundle {
+    redactor: Redactor,
+    zip: ZipWriter<Cursor<Vec<u8>>>,
+    options: SimpleFileOptions,
+}
+
+impl Bundle {
+    pub fn new(redactor: Redactor) -> Self {
+        use chrono::{Datelike, Timelike};
+        
+        let now = chrono::Local::now();
+        // zip的时间从1980年开始，系统时间无效时使用默认时间
+        let modified = zip::DateTime::from_date_and_time(
+            now.year().clamp(1980, 2107) as u16,
+            now.month() as u8,
+            now.day() as u8,
+            now.hour() as u8,
+            now.minute() as u8,
+            now.second() as u8,
+        )
+        .unwrap_or_default();
+        Self {
+            redactor,
+            zip: ZipWriter::new(Cursor::new(Vec::new())),
+            options: SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .last_modified_time(modified),
+        }
+    }
+
+    /// 脱敏后加入一个文本文件
+    pub fn add_text(&mut self, name: &str, text: &str) -> Result<(), String> {
+        let content = self.redactor.redact(text);
+        self.zip
+            .start_file(name, self.options)
+            .map_err(|e| format!("{}: {}", tr("导出诊断信息失败"), e))?;
+        self.zip
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("{}: {}", tr("导出诊断信息失败"), e))
+    }
+
+    /// 以格式化的JSON加入一个文件，同样经过脱敏
+    pub fn add_json(&mut self, name: &str, value: &serde_json::Value) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("{}: {}", tr("导出诊断信息失败"), e))?;
+        self.add_text(name, &text)
+    }
+
+    /// 写入中央目录，保存到 `path`
+    pub fn write(self, path: &Path) -> Result<(), String> {
+        let data = self.zip
+            .finish()
+            .map_err(|e| format!("{}: {}", tr("导出诊断信息失败"), e))?
+            .into_inner();
+        std::fs::write(path, data)
+            .map_err(|e| format!("{}: {}", tr("导出诊断信息失败"), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn hash(local: &str) -> String {
+        Sha256::digest(local.as_bytes()).iter().take(6).map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn redacts_plain_and_json_escaped_passwords() {
+        let password = String::from("p\"a\\ss");
+        let redactor = Redactor::new([password.clone(), String::new()], false);
+        let json = serde_json::json!({ "password": password }).to_string();
+        
+        assert_eq!(redactor.redact(&json), "{\"password\":\"[REDACTED]\"}");
+        assert_eq!(redactor.redact("登录失败: p\"a\\ss"), "登录失败: [REDACTED]");
+        assert_eq!(redactor.redact("user@2925.com"), "user@2925.com");
+    }
+
+    #[test]
+    fn longer_password_is_redacted_first() {
+        let redactor = Redactor::new([String::from("abc"), String::from("abcdef")], false);
+        assert_eq!(redactor.redact("abcdef abc"), "[REDACTED] [REDACTED]");
+    }
+
+    #[test]
+    fn hashes_local_part_only() {
+        assert_eq!(
+            hash_addresses("From: User@2925.com, 2@b"),
+            format!("From: [{}]@2925.com, 2@b", hash("user"))
+        );
+        assert_eq!(hash_addresses("@example.com"), "@example.com");
+    }
+
+    #[test]
+    fn domain_is_not_reused_as_local_part() {
+        assert_eq!(hash_addresses("a@b.com@c.com"), format!("[{}]@b.com@c.com", hash("a")));
+    }
+
+    #[test]
+    fn written_bundle_is_valid_zip() {
+        let mut bundle = Bundle::new(Redactor::new([String::from("secret")], false));
+        bundle.add_text("logs/日志.log", "password=secret\n").unwrap();
+        bundle.add_json("info.json", &serde_json::json!({ "os": "linux" })).unwrap();
+        
+        let path = std::env::temp_dir().join(format!("diagnostics-test-{}.zip", std::process::id()));
+        bundle.write(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+        assert_eq!(archive.len(), 2);
+        
+        let mut log = archive.by_name("logs/日志.log").unwrap();
+        assert_eq!(log.compression(), CompressionMethod::Deflated);
+        let mut content = String::new();
+        log.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "password=[REDACTED]\n");
+        drop(log);
+        
+        let mut info = String::new();
+        archive.by_name("info.json").unwrap().read_to_string(&mut info).unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&info).unwrap(), serde_json::json!({ "os": "linux" }));
+    }
+}
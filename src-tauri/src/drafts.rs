@@ -0,0 +1,131 @@
+//! 草稿同步模块
+//! 
+//! 将界面中的草稿以带 `\Draft` 标记的邮件APPEND到服务器的草稿箱，使其他客户端也能看到。
+//! 每份草稿上次同步到服务器的UID保存在存储的 `server_drafts` 中，重新同步时新的副本追加成功后
+//! 再删除旧副本，避免草稿箱中出现重复的草稿
+
+use async_imap::imap_proto::{Response, ResponseCode, Status, UidSetMember};
+use async_std::io::WriteExt;
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+
+use crate::i18n::tr;
+use crate::imap_pool::ImapTransport;
+use crate::persist;
+
+/// 待同步的草稿
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailDraft {
+    /// 草稿ID，由界面生成，同一份草稿的多次同步使用相同的ID
+    pub id: String,
+    /// 收件人，多个地址以逗号分隔，可以为空
+    #[serde(default)]
+    pub to: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub body: String,
+    /// 附件的本地路径
+    #[serde(rename = "attachmentPaths", default)]
+    pub attachment_paths: Vec<String>,
+}
+
+/// 草稿在服务器上的副本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerDraft {
+    /// 副本所在的文件夹
+    pub folder: String,
+    pub uid: u32,
+    /// 追加时文件夹的UIDVALIDITY，变化后 `uid` 不再指向该副本
+    #[serde(rename = "uidValidity")]
+    pub uid_validity: u32,
+}
+
+/// 服务器草稿记录读-改-写锁，同时保证同一时间只同步一份草稿，避免重复追加
+#[derive(Default)]
+pub struct DraftsLock(pub async_std::sync::Mutex<()>);
+
+fn draft_key(account: &str, draft_id: &str) -> String {
+    format!("{}/{}", account, draft_id)
+}
+
+fn load(app: &tauri::AppHandle) -> serde_json::Map<String, serde_json::Value> {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("server_drafts"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// 草稿上次同步到服务器的副本，没有同步过时返回None
+pub fn get(app: &tauri::AppHandle, account: &str, draft_id: &str) -> Option<ServerDraft> {
+    load(app)
+        .remove(&draft_key(account, draft_id))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// 记录草稿在服务器上的副本，覆盖之前的记录
+/// 
+/// 调用方需持有 `DraftsLock`
+pub fn set(app: &tauri::AppHandle, account: &str, draft_id: &str, draft: &ServerDraft) -> Result<(), String> {
+    let mut drafts = load(app);
+    drafts.insert(draft_key(account, draft_id), serde_json::to_value(draft).unwrap());
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("server_drafts", serde_json::Value::Object(drafts));
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save server drafts: {}", e))
+}
+
+/// 将邮件以 `\Draft` 标记追加到文件夹，返回服务器在APPENDUID响应码（RFC 4315）中给出的
+/// UIDVALIDITY和UID；服务器不支持UIDPLUS时返回None，由调用方另外查找
+/// 
+/// async-imap的 `append` 不能设置标记，也会丢弃APPENDUID，这里直接发送命令和字面量
+/// 
+/// # 参数
+/// * `imap_session` - 已登录的IMAP会话
+/// * `mailbox` - 目标文件夹（已按修改版UTF-7编码）
+/// * `content` - 完整的RFC 5322邮件内容
+pub async fn append<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    mailbox: &str,
+    content: &[u8],
+) -> Result<Option<(u32, u32)>, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", tr("保存草稿到服务器失败"), e);
+    
+    let mailbox = mailbox.replace('\\', "\\\\").replace('"', "\\\"");
+    let tag = imap_session
+        .run_command(format!("APPEND \"{}\" (\\Draft) {{{}}}", mailbox, content.len()))
+        .await
+        .map_err(|e| error(&e))?;
+    
+    let mut sent = false;
+    while let Some(response) = imap_session.read_response().await {
+        let response = response.map_err(|e| error(&e))?;
+        match response.parsed() {
+            Response::Continue { .. } if !sent => {
+                let stream = imap_session.as_mut();
+                stream.write_all(content).await.map_err(|e| error(&e))?;
+                stream.write_all(b"\r\n").await.map_err(|e| error(&e))?;
+                stream.flush().await.map_err(|e| error(&e))?;
+                sent = true;
+            }
+            Response::Done { tag: done, status, code, information } if *done == tag => {
+                if *status != Status::Ok {
+                    return Err(error(&information.as_deref().unwrap_or_default()));
+                }
+                return Ok(match code {
+                    Some(ResponseCode::AppendUid(uid_validity, uids)) => match uids.as_slice() {
+                        [UidSetMember::Uid(uid)] => Some((*uid_validity, *uid)),
+                        _ => None,
+                    },
+                    _ => None,
+                });
+            }
+            _ => {}
+        }
+    }
+    
+    Err(error(&tr("连接意外中断")))
+}
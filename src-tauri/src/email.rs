@@ -0,0 +1,96 @@
+//! 邮件列表项模块
+//! 
+//! 返回邮件的命令（列表、信封、详情、搜索、离线缓存、归档）都使用同一个 `Email` 结构，
+//! 字段与前端的 `Email` 类型一致。只在部分命令中出现的字段为None时不序列化
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::CalendarEvent;
+use crate::delivery_status::DeliveryStatus;
+use crate::unsubscribe::Unsubscribe;
+
+/// 邮件
+/// 
+/// 反序列化时缺少的字段取默认值，旧版本写入的离线缓存仍可读取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Email {
+    /// 邮件UID；归档邮件为归档ID
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    /// 抄送地址，没有抄送时为None
+    pub cc: Option<String>,
+    pub subject: String,
+    /// Message-ID头部，没有该头部时为None；界面用于从退信关联到原邮件
+    #[serde(rename = "messageId", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// 纯文本正文；只获取信封时为空
+    pub body: String,
+    /// HTML正文，没有HTML部分时为None；只有单封邮件详情中内嵌的 `cid:` 图片替换为data URI
+    #[serde(rename = "bodyHtml")]
+    pub body_html: Option<String>,
+    /// 会议邀请（text/calendar部分）中的事件，没有邀请时为None
+    #[serde(rename = "calendarEvent")]
+    pub calendar_event: Option<CalendarEvent>,
+    /// 退信（multipart/report）中的投递状态，不是退信时为None
+    #[serde(rename = "deliveryStatus")]
+    pub delivery_status: Option<DeliveryStatus>,
+    /// 列表预览：去除HTML和引用内容后的前140个字符
+    pub snippet: String,
+    /// 邮件时间（毫秒），优先取Date头部
+    pub timestamp: i64,
+    /// 服务器接收时间（毫秒），不受伪造或错误的Date头部影响，便于按接收时间排序
+    #[serde(rename = "receivedAt")]
+    pub received_at: Option<i64>,
+    #[serde(rename = "isRead")]
+    pub is_read: bool,
+    /// 已标记 `\Deleted` 但尚未压缩
+    #[serde(rename = "isDeleted")]
+    pub is_deleted: bool,
+    #[serde(rename = "isFlagged")]
+    pub is_flagged: bool,
+    /// IMAP关键字（不以 `\` 开头的自定义标记）
+    pub keywords: Vec<String>,
+    #[serde(rename = "isSubEmailForwarded")]
+    pub is_sub_email_forwarded: bool,
+    /// 投递到的子邮箱地址（小写）
+    #[serde(rename = "originalSubEmail")]
+    pub original_sub_email: Option<String>,
+    /// 投递到的子邮箱后缀（`local+suffix@2925.com` 中的suffix）
+    #[serde(rename = "deliveredToSuffix")]
+    pub delivered_to_suffix: Option<String>,
+    /// 邮件列表的退订方式（List-Unsubscribe），没有可用链接时为None
+    pub unsubscribe: Option<Unsubscribe>,
+    /// 邮件无法解析，正文为占位文字
+    #[serde(rename = "parseError")]
+    pub parse_error: bool,
+    /// 只有信封信息，正文需要在详情中另外获取
+    #[serde(rename = "envelopeOnly")]
+    pub envelope_only: bool,
+    /// 发件人头像颜色
+    #[serde(rename = "senderColor")]
+    pub sender_color: Option<String>,
+    /// 发件人地址的Gravatar哈希（小写地址的MD5）
+    #[serde(rename = "gravatarHash")]
+    pub gravatar_hash: Option<String>,
+    /// 休眠的唤醒时间（毫秒），只有休眠未到期的邮件有此字段
+    #[serde(rename = "snoozedUntil", skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<i64>,
+    /// 跨文件夹的命令中 `id` 所属的文件夹
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// 按Message-ID去重时，同一封邮件出现的所有文件夹
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folders: Option<Vec<String>>,
+    /// 邮件大小（字节），只有 `largest_messages` 返回的邮件有此字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+}
+
+impl Email {
+    /// 邮件ID解析为UID，ID不是UID（如归档邮件）时返回None
+    pub fn uid(&self) -> Option<u32> {
+        self.id.parse().ok()
+    }
+}
@@ -0,0 +1,233 @@
+//! 离线邮件缓存模块
+//! 
+//! 获取到的邮件按账户和文件夹缓存在单独的 `cache.json` 中，离线时可直接读取；
+//! 与 `store.json` 分开存放，缓存再大也不会拖慢会话和偏好设置的加载。
+//! 默认每个文件夹的邮件列表以gzip压缩后按base64字符串保存，读取时自动识别：
+//! 值为数组时是未压缩的格式（关闭压缩后写入的，或旧版本写入的），切换偏好设置后仍可读取已有的缓存
+
+use base64::{engine::general_purpose, Engine as _};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::Read;
+use tauri_plugin_store::StoreExt;
+
+use crate::email::Email;
+use crate::persist;
+
+/// 缓存文件名
+pub const CACHE_FILE: &str = "cache.json";
+
+/// 每个文件夹默认缓存的邮件数
+pub const DEFAULT_CACHE_LIMIT: usize = 200;
+
+fn cache_key(account: &str, folder: &str) -> String {
+    format!("{}/{}", account, folder)
+}
+
+/// 将邮件列表转换为缓存中保存的值，`compress` 为true时为gzip压缩后的base64字符串
+fn encode(emails: &[Email], compress: bool) -> Result<serde_json::Value, String> {
+    if !compress {
+        return Ok(serde_json::to_value(emails).unwrap());
+    }
+    
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, emails)
+        .map_err(|e| format!("Failed to compress cache: {}", e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress cache: {}", e))?;
+    Ok(serde_json::Value::String(general_purpose::STANDARD.encode(compressed)))
+}
+
+/// 解析缓存中保存的值，同时接受压缩和未压缩的格式；数据损坏时返回None
+fn decode(value: serde_json::Value) -> Option<Vec<Email>> {
+    let serde_json::Value::String(encoded) = value else {
+        return serde_json::from_value(value).ok();
+    };
+    
+    let compressed = general_purpose::STANDARD.decode(encoded).ok()?;
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice()).read_to_end(&mut json).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// 与已缓存的邮件按ID合并（新数据覆盖旧数据），按时间从新到旧保留最多 `limit` 封
+fn merge(cached: Option<serde_json::Value>, emails: &[Email], limit: usize) -> Vec<Email> {
+    let merged: HashMap<String, Email> = cached
+        .and_then(decode)
+        .unwrap_or_default()
+        .into_iter()
+        .chain(emails.iter().cloned())
+        .filter(|email| !email.id.is_empty())
+        .map(|email| (email.id.clone(), email))
+        .collect::<HashMap<_, _>>();
+    
+    let mut merged: Vec<Email> = merged.into_values().collect();
+    merged.sort_by_key(|email| std::cmp::Reverse(email.timestamp));
+    merged.truncate(limit);
+    merged
+}
+
+/// 将邮件写入文件夹缓存
+/// 与已缓存的邮件按ID合并（新数据覆盖旧数据），按时间从新到旧保留最多 `limit` 封，超出的最旧邮件被淘汰；
+/// `limit` 为0时不缓存并清除该文件夹已有的缓存
+/// 
+/// # 参数
+/// * `account` - 账户邮箱
+/// * `folder` - 文件夹名称
+/// * `emails` - 本次获取的邮件
+/// * `limit` - 每个文件夹的缓存上限
+/// * `compress` - 是否压缩保存
+pub fn write_folder(
+    app: &tauri::AppHandle,
+    account: &str,
+    folder: &str,
+    emails: &[Email],
+    limit: usize,
+    compress: bool,
+) -> Result<(), String> {
+    let store = app.store(CACHE_FILE)
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    let key = cache_key(account, folder);
+    
+    if limit == 0 {
+        store.delete(&key);
+        return persist::save(app, &store, CACHE_FILE).map_err(|e| format!("Failed to save cache: {}", e));
+    }
+    
+    let cached = merge(store.get(&key), emails, limit);
+    store.set(key, encode(&cached, compress)?);
+    persist::save(app, &store, CACHE_FILE).map_err(|e| format!("Failed to save cache: {}", e))
+}
+
+/// 读取文件夹缓存，按时间从新到旧排列；没有缓存时返回空列表
+pub fn read_folder(app: &tauri::AppHandle, account: &str, folder: &str) -> Result<Vec<Email>, String> {
+    let store = app.store(CACHE_FILE)
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    Ok(store
+        .get(cache_key(account, folder))
+        .and_then(decode)
+        .unwrap_or_default())
+}
+
+/// 清除缓存
+/// 
+/// # 参数
+/// * `account` - 账户邮箱
+/// * `folder` - 只清除该文件夹的缓存；为None时清除该账户的全部缓存
+pub fn clear(app: &tauri::AppHandle, account: &str, folder: Option<&str>) -> Result<(), String> {
+    let store = app.store(CACHE_FILE)
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    match folder {
+        Some(folder) => {
+            store.delete(cache_key(account, folder));
+        }
+        None => {
+            let prefix = cache_key(account, "");
+            for key in store.keys().into_iter().filter(|key| key.starts_with(&prefix)) {
+                store.delete(key);
+            }
+        }
+    }
+    
+    persist::save(app, &store, CACHE_FILE).map_err(|e| format!("Failed to save cache: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(id: u32) -> Email {
+        Email {
+            id: id.to_string(),
+            from: format!("sender{}@example.com", id),
+            to: String::from("user@2925.com"),
+            subject: format!("Message {}", id),
+            body: "正文 ".repeat(200),
+            snippet: format!("正文 {}", id),
+            timestamp: 1_760_000_000_000 + i64::from(id),
+            ..Email::default()
+        }
+    }
+
+    fn ids(emails: &[Email]) -> Vec<&str> {
+        emails.iter().map(|email| email.id.as_str()).collect()
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let emails: Vec<Email> = (1..=3).map(email).collect();
+        let value = encode(&emails, true).unwrap();
+        assert!(value.is_string());
+        let decoded = decode(value).unwrap();
+        assert_eq!(ids(&decoded), ["1", "2", "3"]);
+        assert_eq!(decoded[0].body, emails[0].body);
+    }
+
+    #[test]
+    fn switching_compression_keeps_cache() {
+        let emails: Vec<Email> = (1..=2).map(email).collect();
+        
+        // 压缩写入后关闭压缩，下次写入读取到已有的压缩缓存并以数组保存
+        let compressed = encode(&emails, true).unwrap();
+        let uncompressed = encode(&merge(Some(compressed), &[email(3)], 10), false).unwrap();
+        assert!(uncompressed.is_array());
+        
+        // 再打开压缩
+        let compressed = encode(&merge(Some(uncompressed), &[], 10), true).unwrap();
+        assert_eq!(ids(&decode(compressed).unwrap()), ["3", "2", "1"]);
+    }
+
+    #[test]
+    fn legacy_array_is_read_with_compression_on() {
+        // 旧版本写入的数组，缺少后来增加的字段
+        let legacy = serde_json::json!([
+            { "id": "5", "from": "a@example.com", "to": "user@2925.com", "subject": "旧邮件", "body": "", "timestamp": 5 },
+        ]);
+        let merged = merge(Some(legacy), &[email(6)], 10);
+        assert_eq!(ids(&merged), ["6", "5"]);
+        assert_eq!(merged[1].subject, "旧邮件");
+        
+        let compressed = encode(&merged, true).unwrap();
+        assert_eq!(ids(&decode(compressed).unwrap()), ["6", "5"]);
+    }
+
+    #[test]
+    fn corrupted_value_is_ignored() {
+        assert!(decode(serde_json::Value::String(String::from("不是base64"))).is_none());
+        assert!(decode(serde_json::Value::String(general_purpose::STANDARD.encode("not gzip"))).is_none());
+    }
+
+    /// 读取几百封缓存邮件时解压带来的额外耗时：`cargo test --release -- --ignored decode_latency --nocapture`
+    #[test]
+    #[ignore]
+    fn decode_latency() {
+        const MESSAGES: u32 = 300;
+        const ROUNDS: u32 = 20;
+        
+        let emails: Vec<Email> = (1..=MESSAGES).map(email).collect();
+        let compressed = encode(&emails, true).unwrap();
+        let uncompressed = encode(&emails, false).unwrap();
+        let time = |value: &serde_json::Value| {
+            let started = std::time::Instant::now();
+            for _ in 0..ROUNDS {
+                assert_eq!(decode(value.clone()).unwrap().len(), MESSAGES as usize);
+            }
+            started.elapsed() / ROUNDS
+        };
+        
+        let compressed_time = time(&compressed);
+        let uncompressed_time = time(&uncompressed);
+        println!(
+            "{} 封邮件：压缩 {} 字节，解码 {:?}；未压缩 {} 字节，解码 {:?}",
+            MESSAGES,
+            compressed.as_str().unwrap().len(),
+            compressed_time,
+            uncompressed.to_string().len(),
+            uncompressed_time,
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! 邮件信封（ENVELOPE）解析模块
+//! 
+//! IMAP服务器返回已解析好的发件人、收件人、主题、日期和Message-ID，
+//! 邮件列表只需这些字段时无需下载和解析完整邮件
+
+use async_imap::imap_proto::{Address, Envelope};
+
+/// 解码信封中的文本字段（RFC 2047编码的主题、显示名称等）
+pub fn decode_text(value: &[u8]) -> String {
+    let text = String::from_utf8_lossy(value);
+    let line = format!("X: {}", text);
+    match mailparse::parse_header(line.as_bytes()) {
+        Ok((header, _)) => header.get_value(),
+        Err(_) => text.into_owned(),
+    }
+}
+
+/// 地址的 `mailbox@host` 部分；组地址的起止标记（没有host）返回None
+pub fn bare_address(address: &Address) -> Option<String> {
+    let mailbox = address.mailbox.as_ref()?;
+    let host = address.host.as_ref()?;
+    Some(format!(
+        "{}@{}",
+        String::from_utf8_lossy(mailbox),
+        String::from_utf8_lossy(host)
+    ))
+}
+
+/// 格式化为 `显示名称 <mailbox@host>`，没有显示名称时只返回地址
+fn format_address(address: &Address) -> Option<String> {
+    let bare = bare_address(address)?;
+    match address.name.as_ref().map(|name| decode_text(name)) {
+        Some(name) if !name.trim().is_empty() => Some(format!("{} <{}>", name.trim(), bare)),
+        _ => Some(bare),
+    }
+}
+
+/// 将地址列表格式化为逗号分隔的字符串，列表为空时返回None
+pub fn format_addresses(addresses: Option<&Vec<Address>>) -> Option<String> {
+    let formatted: Vec<String> = addresses?.iter().filter_map(format_address).collect();
+    if formatted.is_empty() {
+        None
+    } else {
+        Some(formatted.join(", "))
+    }
+}
+
+/// 信封中的所有收件人地址（To和Cc），不含显示名称
+pub fn recipients(envelope: &Envelope) -> Vec<String> {
+    [envelope.to.as_ref(), envelope.cc.as_ref()]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(bare_address)
+        .collect()
+}
+
+/// 解析信封中的Date字段为毫秒时间戳
+pub fn timestamp(envelope: &Envelope) -> Option<i64> {
+    let date = String::from_utf8_lossy(envelope.date.as_ref()?);
+    chrono::DateTime::parse_from_rfc2822(date.trim())
+        .ok()
+        .map(|date| date.timestamp_millis())
+}
@@ -0,0 +1,81 @@
+//! 应用错误类型模块
+//! 
+//! 区分服务器真实拒绝登录与网络/协议层面的故障，
+//! 避免网络波动被误报为"邮箱或密码错误"
+
+use async_imap::error::Error as ImapError;
+
+use crate::i18n::tr;
+
+/// 应用错误类型
+#[derive(Debug)]
+pub enum AppError {
+    /// 无法建立到服务器的TCP连接
+    Connection(String),
+    /// TLS握手失败
+    Tls(String),
+    /// 服务器以NO/BAD响应拒绝了登录凭据
+    AuthRejected(String),
+    /// 会话过程中发生IO错误或连接中断
+    Network(String),
+    /// 服务器响应无法解析或不符合协议
+    Protocol(String),
+    /// SMTP服务器拒绝了命令
+    Smtp(String),
+    /// 收件人地址无效
+    InvalidRecipient(String),
+    /// 头部字段的值包含换行等非法字符
+    InvalidHeader(String),
+    /// IMAP或SMTP操作在限定时间内没有完成（秒）
+    OperationTimeout(u64),
+    /// 邮件超过SMTP服务器允许的大小（字节）
+    MessageTooLarge { size: u64, limit: u64 },
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Connection(msg) => write!(f, "{}: {}", tr("无法连接到邮件服务器"), msg),
+            AppError::Tls(msg) => write!(f, "{}: {}", tr("TLS连接失败"), msg),
+            AppError::AuthRejected(msg) => write!(f, "{}: {}", tr("邮箱或密码错误"), msg),
+            AppError::Network(msg) => write!(f, "{}: {}", tr("网络连接异常"), msg),
+            AppError::Protocol(msg) => write!(f, "{}: {}", tr("邮件服务器协议错误"), msg),
+            AppError::Smtp(msg) => write!(f, "{}: {}", tr("邮件发送失败"), msg),
+            AppError::InvalidRecipient(msg) => write!(f, "{}: {}", tr("收件人地址无效"), msg),
+            AppError::InvalidHeader(field) => write!(f, "{}: {}", tr("头部字段不能包含换行符"), field),
+            AppError::OperationTimeout(secs) => write!(f, "{} ({}s)", tr("邮件服务器响应超时"), secs),
+            AppError::MessageTooLarge { size, limit } => write!(
+                f,
+                "{}: {:.1} MB > {:.1} MB",
+                tr("邮件超过服务器允许的大小"),
+                *size as f64 / (1024.0 * 1024.0),
+                *limit as f64 / (1024.0 * 1024.0)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Tauri命令统一以字符串形式向前端返回错误
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+impl AppError {
+    /// 将async-imap登录阶段的错误归类
+    /// 
+    /// 只有服务器明确的NO/BAD响应（或凭据中含有IMAP不允许的字符）才视为认证失败，
+    /// IO错误和连接中断归为网络问题，其余归为协议错误
+    pub fn from_login_error(error: ImapError) -> Self {
+        match error {
+            ImapError::No(msg) | ImapError::Bad(msg) => AppError::AuthRejected(msg),
+            ImapError::Validate(e) => AppError::AuthRejected(format!("{} {}", tr("凭据包含非法字符"), e.0)),
+            ImapError::Io(e) => AppError::Network(e.to_string()),
+            ImapError::ConnectionLost => AppError::Network(tr("连接意外中断").to_string()),
+            other => AppError::Protocol(other.to_string()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! FETCH响应合并模块
+//! 
+//! RFC 3501 7.4.2允许服务器把同一封邮件的数据项分在多条FETCH响应中返回，例如UID与头部分开，
+//! 或在请求的数据之后再推送一条只含FLAGS的未请求响应。头部很大（很长的References、大量Received）
+//! 的邮件在部分服务器上就会这样返回。单个字面量由async-imap完整缓冲后才交给调用方，不会被截断；
+//! 这里按序号把各条响应中的数据项合并为一封邮件，后到的空数据项不会覆盖已收到的数据
+
+use std::collections::HashMap;
+
+use async_imap::types::{Fetch, Flag};
+
+/// 一封邮件在各条FETCH响应中收到的数据项
+#[derive(Debug, Clone, Default)]
+pub struct MessageParts {
+    pub uid: Option<u32>,
+    /// `BODY[HEADER]`、`BODY[HEADER.FIELDS (...)]` 或 `RFC822.HEADER`
+    pub header: Option<Vec<u8>>,
+    /// `BODY[TEXT]`（可以是部分获取）或 `RFC822.TEXT`
+    pub text: Option<Vec<u8>>,
+    /// `BODY[]` 或 `RFC822`
+    pub body: Option<Vec<u8>>,
+    /// 各条响应中FLAGS的并集
+    pub flags: Vec<Flag<'static>>,
+}
+
+impl MessageParts {
+    fn merge(&mut self, message: &Fetch) {
+        if message.uid.is_some() {
+            self.uid = message.uid;
+        }
+        if let Some(header) = message.header() {
+            self.header = Some(header.to_vec());
+        }
+        if let Some(text) = message.text() {
+            self.text = Some(text.to_vec());
+        }
+        if let Some(body) = message.body() {
+            self.body = Some(body.to_vec());
+        }
+        merge_flags(&mut self.flags, message);
+    }
+}
+
+/// 将响应中的标记加入 `flags`，已有的标记不重复加入
+pub fn merge_flags(flags: &mut Vec<Flag<'static>>, message: &Fetch) {
+    for flag in message.flags() {
+        let flag = match flag {
+            Flag::Custom(name) => Flag::Custom(name.into_owned().into()),
+            Flag::Seen => Flag::Seen,
+            Flag::Answered => Flag::Answered,
+            Flag::Flagged => Flag::Flagged,
+            Flag::Deleted => Flag::Deleted,
+            Flag::Draft => Flag::Draft,
+            Flag::Recent => Flag::Recent,
+            Flag::MayCreate => Flag::MayCreate,
+        };
+        if !flags.contains(&flag) {
+            flags.push(flag);
+        }
+    }
+}
+
+/// 按序号合并的FETCH响应
+#[derive(Debug, Default)]
+pub struct FetchParts {
+    messages: HashMap<u32, MessageParts>,
+}
+
+impl FetchParts {
+    /// 合并一条FETCH响应
+    pub fn add(&mut self, message: &Fetch) {
+        self.messages.entry(message.message).or_default().merge(message);
+    }
+
+    /// UID为 `uid` 的邮件
+    pub fn get(&self, uid: u32) -> Option<&MessageParts> {
+        self.messages.values().find(|parts| parts.uid == Some(uid))
+    }
+
+    /// 所有收到UID的邮件
+    pub fn into_messages(self) -> impl Iterator<Item = (u32, MessageParts)> {
+        self.messages
+            .into_values()
+            .filter_map(|parts| parts.uid.map(|uid| (uid, parts)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_std::stream::StreamExt;
+
+    use super::*;
+    use crate::imap_pool::login_over;
+    use crate::mock_imap::{literal, ScriptedStream};
+
+    #[async_std::test]
+    async fn large_header_split_across_reads_is_merged() {
+        // 很长的References和大量Received使头部超过64 KiB，服务器每次只送达少量字节
+        let references: Vec<String> = (0..2000).map(|n| format!("<thread-{:05}@mail.example.com>", n)).collect();
+        let received: String = (0..300)
+            .map(|n| format!("Received: from relay{n}.example.com (relay{n}.example.com [192.0.2.{}])\r\n\tby mx.2925.com with ESMTPS id {n:08x}\r\n", n % 250))
+            .collect();
+        let header = format!(
+            "{}From: sender@example.com\r\nSubject: Long thread\r\nReferences: {}\r\n\r\n",
+            received,
+            references.join("\r\n ")
+        );
+        assert!(header.len() > 64 * 1024);
+        
+        let stream = ScriptedStream::new(format!(
+            "A0001 OK LOGIN completed\r\n\
+             * 1 FETCH (BODY[HEADER] {})\r\n\
+             * 1 FETCH (UID 7)\r\n\
+             * 1 FETCH (FLAGS (\\Seen $Work))\r\n\
+             * 1 FETCH (FLAGS (\\Seen \\Flagged))\r\n\
+             A0002 OK FETCH completed\r\n",
+            literal(&header)
+        )).with_read_size(1000);
+        let mut session = login_over(stream, "user@2925.com", "secret").await.unwrap();
+        
+        let mut parts = FetchParts::default();
+        let mut messages = session.uid_fetch("7", "(UID FLAGS BODY.PEEK[HEADER])").await.unwrap();
+        while let Some(message) = messages.next().await {
+            parts.add(&message.unwrap());
+        }
+        
+        let message = parts.get(7).unwrap();
+        assert_eq!(message.header.as_deref(), Some(header.as_bytes()));
+        assert_eq!(message.flags, [Flag::Seen, Flag::Custom("$Work".into()), Flag::Flagged]);
+        assert!(message.body.is_none());
+    }
+}
@@ -0,0 +1,65 @@
+//! 邮件过滤规则模块
+//! 
+//! 规则按顺序匹配发件人和主题（不区分大小写的包含匹配），命中后对邮件执行标记已读、加星标或移动操作
+
+use serde::{Deserialize, Serialize};
+
+use crate::email::Email;
+
+/// 规则命中后执行的操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FilterAction {
+    /// 标记为已读
+    MarkRead,
+    /// 加星标
+    Flag,
+    /// 移动到指定文件夹
+    Move { folder: String },
+}
+
+/// 过滤规则
+/// 同时设置发件人和主题条件时两者都要满足；两个条件都为空的规则不匹配任何邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub name: String,
+    #[serde(rename = "fromContains", default)]
+    pub from_contains: Option<String>,
+    #[serde(rename = "subjectContains", default)]
+    pub subject_contains: Option<String>,
+    pub action: FilterAction,
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+impl FilterRule {
+    /// 判断邮件是否满足规则条件
+    pub fn matches(&self, email: &Email) -> bool {
+        let conditions = [
+            (self.from_contains.as_deref(), &email.from),
+            (self.subject_contains.as_deref(), &email.subject),
+        ];
+        let mut has_condition = false;
+        for (needle, field) in conditions {
+            let Some(needle) = needle.map(str::trim).filter(|needle| !needle.is_empty()) else {
+                continue;
+            };
+            has_condition = true;
+            if !contains_ignore_case(field, needle) {
+                return false;
+            }
+        }
+        has_condition
+    }
+
+    /// 邮件是否已处于操作后的状态，此时无需再次执行
+    pub fn already_applied(&self, email: &Email) -> bool {
+        match self.action {
+            FilterAction::MarkRead => email.is_read,
+            FilterAction::Flag => email.is_flagged,
+            FilterAction::Move { .. } => false,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! 后端错误信息本地化模块
+//! 
+//! 以中文原文作为消息键，按当前语言查表返回译文，未收录的消息回退为中文原文。
+//! 当前语言为进程级状态，辅助函数和错误类型无需持有AppHandle即可读取。
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    ZhCn,
+    En,
+}
+
+impl Locale {
+    /// 解析语言标签，如 `zh-CN`、`zh`、`en`、`en-US`
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.trim().to_ascii_lowercase();
+        if tag == "zh" || tag.starts_with("zh-") || tag.starts_with("zh_") {
+            Some(Locale::ZhCn)
+        } else if tag == "en" || tag.starts_with("en-") || tag.starts_with("en_") {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+
+    /// 标准语言标签
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::En => "en",
+        }
+    }
+}
+
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// 设置当前语言
+pub fn set_locale(locale: Locale) {
+    let value = match locale {
+        Locale::ZhCn => 0,
+        Locale::En => 1,
+    };
+    CURRENT_LOCALE.store(value, Ordering::Relaxed);
+}
+
+/// 当前语言，默认为简体中文
+pub fn current_locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::En,
+        _ => Locale::ZhCn,
+    }
+}
+
+/// 翻译面向用户的消息
+/// 
+/// # 参数
+/// * `message` - 中文原文（消息键）
+pub fn tr(message: &'static str) -> &'static str {
+    match current_locale() {
+        Locale::ZhCn => message,
+        Locale::En => english(message).unwrap_or(message),
+    }
+}
+
+/// 英文译文表
+fn english(message: &str) -> Option<&'static str> {
+    let translated = match message {
+        // 登录校验
+        "邮箱地址必须是2925.com域名" => "Email address must be a 2925.com address",
+        "密码不能为空" => "Password must not be empty",
+        "密码不能只包含空白字符" => "Password must not consist only of whitespace",
+        "密码长度至少为6位" => "Password must be at least 6 characters",
+        "登录失败" => "Login failed",
+        "登出失败" => "Logout failed",
+        // 连接与认证
+        "无法连接到邮件服务器" => "Unable to connect to mail server",
+        "TLS连接失败" => "TLS connection failed",
+        "要求的最低版本为" => "minimum required version is",
+        "邮箱或密码错误" => "Incorrect email or password",
+        "网络连接异常" => "Network error",
+        "邮件服务器协议错误" => "Mail server protocol error",
+        "凭据包含非法字符" => "Credentials contain an invalid character",
+        "连接意外中断" => "Connection lost unexpectedly",
+        "邮件服务器响应超时" => "Mail server timed out",
+        "邮件超过服务器允许的大小" => "Message exceeds the size allowed by the server",
+        // 会话与凭据
+        "无法访问存储" => "Unable to access storage",
+        "未登录，请先登录" => "Not logged in, please log in first",
+        "会话数据无效" => "Invalid session data",
+        "会话已过期，请重新登录" => "Session expired, please log in again",
+        "未找到登录凭据" => "No saved credentials found",
+        "未找到会话信息" => "No session found",
+        "密码数据无效" => "Invalid password data",
+        "保存密码失败" => "Failed to save password",
+        // 邮件操作
+        "无效的邮件ID" => "Invalid message id",
+        "无法打开文件夹" => "Unable to open folder",
+        "查找邮件失败" => "Failed to look up message",
+        "邮件不存在" => "Message not found",
+        "修改邮件标记失败" => "Failed to update message flags",
+        "获取邮件失败" => "Failed to fetch messages",
+        "压缩文件夹失败" => "Failed to expunge folder",
+        "获取文件夹列表失败" => "Failed to list folders",
+        "获取文件夹状态失败" => "Failed to get folder status",
+        "移动邮件失败" => "Failed to move message",
+        "未找到垃圾邮件文件夹" => "No junk folder found",
+        // 地址校验
+        "邮箱地址格式无效" => "Invalid email address format",
+        "2925邮箱用户名只能包含字母、数字、下划线、点和短横线" => "2925 usernames may only contain letters, digits, underscores, dots and hyphens",
+        "域名解析失败" => "DNS lookup failed",
+        "该域名没有邮件服务器" => "The domain has no mail server",
+        // 邮件结构与附件
+        "获取邮件结构失败" => "Failed to fetch message structure",
+        "无效的部分编号" => "Invalid part id",
+        "邮件中没有该部分" => "Message has no such part",
+        "附件解码失败" => "Failed to decode attachment",
+        "保存附件失败" => "Failed to save attachment",
+        // 本地归档
+        "归档邮件失败" => "Failed to archive message",
+        "读取归档失败" => "Failed to read archived message",
+        "归档邮件不存在" => "Archived message not found",
+        // 邮件发送
+        "邮件发送失败" => "Failed to send mail",
+        "无效的SMTP响应" => "Invalid SMTP response",
+        "收件人地址无效" => "Invalid recipient address",
+        "收件人不能为空" => "At least one recipient is required",
+        "头部字段不能包含换行符" => "Header field must not contain line breaks",
+        "无法读取附件" => "Unable to read attachment",
+        "附件总大小超过限制" => "Attachments exceed the size limit",
+        "邮件正在发送中" => "This message is already being sent",
+        "上次发送的结果未知，邮件可能已经发出" => "The previous attempt to send this message had an unknown result; it may already have been sent",
+        "发件箱中没有该邮件" => "Message not found in the outbox",
+        "SMTP服务器地址无效" => "Invalid SMTP server address",
+        "SMTP端口无效" => "Invalid SMTP port",
+        "关键字无效" => "Invalid keyword",
+        "搜索词无效" => "Invalid search term",
+        "休眠时间必须晚于当前时间" => "Snooze time must be in the future",
+        "无法打开收件箱，请检查IMAP配置中的收件箱名称" => "Cannot open the inbox, check the inbox name in the IMAP settings",
+        "服务器不支持自定义关键字" => "The server does not allow custom keywords",
+        "过滤规则无效" => "Invalid filter rule",
+        "获取服务器能力失败" => "Failed to get server capabilities",
+        "IDLE失败" => "IDLE failed",
+        "参数包含不支持的字符" => "Argument contains unsupported characters",
+        "获取自动回复状态失败" => "Failed to get auto-reply status",
+        "邮件包含无法重新发送的8位内容" => "The message contains 8-bit content that cannot be resent",
+        "解析邮件失败" => "Failed to parse the message",
+        "草稿ID不能为空" => "Draft ID cannot be empty",
+        "找不到草稿箱" => "Drafts folder not found",
+        "保存草稿到服务器失败" => "Failed to save the draft to the server",
+        "连接已重置" => "Connection reset",
+        "该邮件没有退订链接" => "This message has no unsubscribe link",
+        "该邮件只能通过网页退订" => "This message can only be unsubscribed from in a browser",
+        "退订链接无效" => "Invalid unsubscribe link",
+        "退订失败" => "Failed to unsubscribe",
+        "退订请求超时" => "Unsubscribe request timed out",
+        // 备份
+        "此备份包含明文密码，请妥善保管" => "This backup contains a plaintext password, keep it safe",
+        "无法在本机解密密码，备份中不包含密码" => "The password could not be decrypted on this computer and was left out of the backup",
+        "导出备份失败" => "Failed to export backup",
+        "导入备份失败" => "Failed to import backup",
+        "导出诊断信息失败" => "Failed to export diagnostics",
+        "备份文件无效" => "Invalid backup file",
+        "备份文件版本过新" => "Backup file was created by a newer version",
+        // 托盘
+        "2925邮箱管理系统" => "2925 Mail Manager",
+        "封未读" => "unread",
+        // 偏好设置
+        "未知的偏好设置项" => "Unknown preference",
+        "偏好设置项的值无效" => "Invalid value for preference",
+        "不支持的语言" => "Unsupported locale",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tray_tooltip_is_translated() {
+        assert_eq!(english("2925邮箱管理系统"), Some("2925 Mail Manager"));
+        assert_eq!(english("封未读"), Some("unread"));
+        assert_eq!(english("未收录的消息"), None);
+    }
+}
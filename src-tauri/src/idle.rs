@@ -0,0 +1,174 @@
+//! IMAP IDLE推送模块
+//! 
+//! IDLE使用单独的连接，在连接期间占用连接池的一个并发名额，总连接数不超过服务器的限制。
+//! 服务器通知收件箱变化时立即刷新，不必等待定时刷新。
+//! 连接断开后按带随机抖动的指数退避重连，避免网络切换时大量客户端同时重连冲击服务器
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_imap::extensions::idle::IdleResponse;
+use rand::Rng;
+use stop_token::StopSource;
+
+use crate::i18n::tr;
+use crate::imap_pool::ImapSession;
+
+/// 首次重连前的等待时间，之后每次失败翻倍
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+/// 重连等待时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// 单次IDLE的最长时间，到期后结束并重新发出IDLE
+/// RFC 2177建议每29分钟内重新发出；更短的间隔也能更早发现无声断开的连接
+pub const RENEW_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 重连退避状态
+#[derive(Debug, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    /// 记录一次失败，返回下次重连前的等待时间
+    /// 等待时间在退避值的一半到全部之间随机取值
+    pub fn next_delay(&mut self) -> Duration {
+        let exponent = self.failures.min(16);
+        self.failures = self.failures.saturating_add(1);
+        
+        let delay = BASE_BACKOFF.saturating_mul(1 << exponent).min(MAX_BACKOFF);
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+
+    /// 连接成功后重置
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    /// 连续失败的次数
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+/// 正在进行的IDLE的停止句柄
+/// 退出登录或切换账户时调用 `stop`，IDLE立即结束，不必等到 `RENEW_INTERVAL` 到期
+#[derive(Default)]
+pub struct IdleStop(Mutex<StopState>);
+
+#[derive(Default)]
+struct StopState {
+    source: Option<StopSource>,
+    /// 没有正在进行的IDLE时收到的停止请求，由下一次IDLE处理
+    requested: bool,
+}
+
+impl IdleStop {
+    /// 结束正在进行的IDLE；此时没有IDLE时，下一次IDLE开始后立即结束
+    pub fn stop(&self) {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // 丢弃StopSource即中断对应的IDLE
+        if state.source.take().is_none() {
+            state.requested = true;
+        }
+    }
+
+    /// 保存本次IDLE的停止句柄；之前已请求停止时直接丢弃，IDLE随即结束
+    fn begin(&self, source: StopSource) {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if std::mem::take(&mut state.requested) {
+            drop(source);
+        } else {
+            state.source = Some(source);
+        }
+    }
+
+    /// 本次IDLE已结束
+    fn end(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).source = None;
+    }
+}
+
+/// 服务器是否声明支持IDLE（RFC 2177）
+pub async fn supports_idle(imap_session: &mut ImapSession) -> Result<bool, String> {
+    let capabilities = imap_session
+        .capabilities()
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取服务器能力失败"), e))?;
+    Ok(capabilities.has_str("IDLE"))
+}
+
+/// 在已选择的文件夹上IDLE一次，直到服务器报告变化、到达 `timeout` 或通过 `stop` 停止
+/// 返回会话和文件夹是否可能有变化；连接出错时返回错误，会话随之关闭
+pub async fn wait(imap_session: ImapSession, timeout: Duration, stop: &IdleStop) -> Result<(ImapSession, bool), String> {
+    let mut handle = imap_session.idle();
+    handle.init()
+        .await
+        .map_err(|e| format!("{}: {}", tr("IDLE失败"), e))?;
+    
+    let (idle, source) = handle.wait_with_timeout(timeout);
+    stop.begin(source);
+    let response = idle.await;
+    stop.end();
+    let response = response.map_err(|e| format!("{}: {}", tr("IDLE失败"), e))?;
+    
+    let imap_session = handle.done()
+        .await
+        .map_err(|e| format!("{}: {}", tr("IDLE失败"), e))?;
+    // IDLE期间收到的其他未经请求的响应不再需要，清空以免通道填满
+    while imap_session.unsolicited_responses.try_recv().is_ok() {}
+    
+    Ok((imap_session, matches!(response, IdleResponse::NewData(_))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `token` 是否在短时间内结束（对应的StopSource已丢弃）
+    async fn stopped(token: stop_token::StopToken) -> bool {
+        async_std::future::timeout(Duration::from_millis(50), token).await.is_ok()
+    }
+
+    #[async_std::test]
+    async fn stop_interrupts_running_idle() {
+        let stop = IdleStop::default();
+        let source = StopSource::new();
+        let token = source.token();
+        stop.begin(source);
+        
+        stop.stop();
+        assert!(stopped(token).await);
+    }
+
+    #[async_std::test]
+    async fn stop_before_idle_interrupts_next_idle_once() {
+        let stop = IdleStop::default();
+        stop.stop();
+        
+        let first = StopSource::new();
+        let first_token = first.token();
+        stop.begin(first);
+        assert!(stopped(first_token).await);
+        
+        let second = StopSource::new();
+        let second_token = second.token();
+        stop.begin(second);
+        assert!(!stopped(second_token).await);
+    }
+
+    #[async_std::test]
+    async fn finished_idle_is_not_kept() {
+        let stop = IdleStop::default();
+        stop.begin(StopSource::new());
+        stop.end();
+        
+        // 上一次IDLE已结束，停止请求留给下一次IDLE
+        stop.stop();
+        let next = StopSource::new();
+        let token = next.token();
+        stop.begin(next);
+        assert!(stopped(token).await);
+    }
+}
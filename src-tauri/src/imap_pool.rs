@@ -1,100 +1,808 @@
-/// IMAP连接池模块
-/// 复用IMAP连接以提高性能
-use async_native_tls::TlsConnector;
+//! IMAP连接池模块
+//! 复用IMAP连接以提高性能
+use async_lock::{Semaphore, SemaphoreGuard};
+use async_native_tls::{Protocol, TlsConnector, TlsStream};
+use async_std::io::prelude::*;
+use async_std::io::BufReader;
 use async_std::net::TcpStream;
 use async_std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::ImapConfig;
+
+/// 默认空闲超时（秒）
+pub const DEFAULT_IDLE_SECS: u64 = 300;
+/// 重置连接时登出被移除连接的总等待时间
+const PURGE_LOGOUT_TIMEOUT: Duration = Duration::from_secs(2);
+/// 默认同时借出的最大连接数，2925限制了每个账户的并发连接数
+pub const DEFAULT_MAX_CONNECTIONS: usize = 3;
+
+/// 已认证的IMAP会话类型
+pub type ImapSession = async_imap::Session<async_native_tls::TlsStream<TcpStream>>;
+
+/// IMAP会话可使用的底层传输
+/// 正常运行时为TLS连接；获取与解析逻辑只依赖该约束，可以换成按脚本返回服务器响应的流
+pub trait ImapTransport:
+    async_std::io::Read + async_std::io::Write + Unpin + std::fmt::Debug + Send
+{
+}
+
+impl<T> ImapTransport for T where
+    T: async_std::io::Read + async_std::io::Write + Unpin + std::fmt::Debug + Send
+{
+}
+
 /// IMAP连接包装器
 pub struct ImapConnection {
-    pub session: async_imap::Session<async_native_tls::TlsStream<TcpStream>>,
+    pub session: ImapSession,
     pub last_used: Instant,
 }
 
+/// 连接池中连接的键
+/// 
+/// 除账户和服务器外还包含登录时所用密码的哈希：密码修改后旧连接的键不再匹配，
+/// 不会把用旧密码登录的会话借给新的凭据。键只保存哈希，不保存密码本身
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    email: String,
+    server: String,
+    port: u16,
+    credential: String,
+}
+
+impl PoolKey {
+    fn new(email: &str, password: &str, config: &ImapConfig) -> Self {
+        // 哈希中加入邮箱，不同账户使用相同密码时哈希也不同
+        let digest = Sha256::new()
+            .chain_update(email.as_bytes())
+            .chain_update([0])
+            .chain_update(password.as_bytes())
+            .finalize();
+        Self {
+            email: email.to_string(),
+            server: config.server.clone(),
+            port: config.port,
+            credential: digest.iter().take(16).map(|byte| format!("{:02x}", byte)).collect(),
+        }
+    }
+}
+
+/// 从连接池借出的连接
+/// 
+/// 持有一个并发名额，被丢弃时释放名额。操作出错、命令被取消或发生panic时
+/// 连接没有归还就被丢弃，名额同样会释放，连接池不会因此逐渐耗尽。
+/// 会话类型默认为TLS会话，测试中可以是脚本会话
+pub struct PooledSession<'a, S = ImapSession> {
+    session: S,
+    /// 借出时的键，归还时放回同一个键下
+    key: PoolKey,
+    _permit: SemaphoreGuard<'a>,
+}
+
+impl<S> std::ops::Deref for PooledSession<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.session
+    }
+}
+
+impl<S> std::ops::DerefMut for PooledSession<'_, S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.session
+    }
+}
+
 /// IMAP连接池
+/// 
+/// 每个借出的连接占用一个名额，名额用尽时 `get_connection` 会等待，
+/// 直到有连接通过 `return_connection` 归还或被丢弃。同一个键下可以有多个空闲连接，
+/// 数量不超过并发上限，按归还顺序排列
 pub struct ImapPool {
-    connections: Arc<Mutex<HashMap<String, ImapConnection>>>,
+    connections: Arc<Mutex<HashMap<PoolKey, Vec<ImapConnection>>>>,
+    /// 每个账户（小写）最近一次成功借出连接时的凭据哈希，归还时据此识别用旧密码登录的连接
+    current_credentials: Mutex<HashMap<String, String>>,
     max_idle_time: Duration,
+    permits: Semaphore,
+    max_connections: usize,
 }
 
 impl ImapPool {
     /// 创建新的连接池
+    #[allow(dead_code)]
     pub fn new() -> Self {
+        Self::with_limits(Duration::from_secs(DEFAULT_IDLE_SECS), DEFAULT_MAX_CONNECTIONS) // 5分钟空闲超时
+    }
+
+    /// 使用指定的空闲超时和并发上限创建连接池
+    /// 
+    /// # 参数
+    /// * `max_idle_time` - 连接在池中可空闲的最长时间，超过后不再复用
+    /// * `max_connections` - 同时借出的最大连接数，至少为1
+    pub fn with_limits(max_idle_time: Duration, max_connections: usize) -> Self {
+        let max_connections = max_connections.max(1);
         Self {
             connections: Arc::new(Mutex::new(HashMap::new())),
-            max_idle_time: Duration::from_secs(300), // 5分钟空闲超时
+            current_credentials: Mutex::new(HashMap::new()),
+            max_idle_time,
+            permits: Semaphore::new(max_connections),
+            max_connections,
         }
     }
 
     /// 获取或创建IMAP连接
+    /// 并发借出的连接数达到上限时等待其他连接归还
     pub async fn get_connection(
         &self,
         email: &str,
         password: &str,
-        server: &str,
-        port: u16,
-    ) -> Result<async_imap::Session<async_native_tls::TlsStream<TcpStream>>, String> {
-        let key = format!("{}@{}:{}", email, server, port);
+        config: &ImapConfig,
+    ) -> Result<PooledSession<'_>, AppError> {
+        // 名额随连接一起借出，在归还或丢弃连接时释放；等待期间或连接失败时随guard一起释放
+        let permit = self.permits.acquire().await;
         
-        // 尝试从池中获取现有连接
-        {
-            let mut pool = self.connections.lock().await;
-            
-            if let Some(conn) = pool.remove(&key) {
-                // 检查连接是否过期
-                if conn.last_used.elapsed() < self.max_idle_time {
-                    // 连接仍然有效，返回
-                    return Ok(conn.session);
+        let key = PoolKey::new(email, password, config);
+        
+        // 尝试从池中获取现有连接，优先使用最近归还的
+        while let Some(mut conn) = self.take_idle(&key).await {
+            // 检查连接是否过期
+            if conn.last_used.elapsed() < self.max_idle_time {
+                // 服务器可能已单方面断开空闲连接，用NOOP确认仍然可用
+                if conn.session.noop().await.is_ok() {
+                    crate::perf::record_connection(true);
+                    self.set_current_credential(&key).await;
+                    return Ok(PooledSession {
+                        session: conn.session,
+                        key,
+                        _permit: permit,
+                    });
                 }
-                // 连接已过期，继续创建新连接
             }
+            // 连接已过期或失效，尝试下一个
         }
         
-        // 创建新连接
-        let tcp_stream = TcpStream::connect((server, port))
+        // 创建新连接，失败时没有连接借出，名额随permit释放
+        let session = connect(email, password, config).await?;
+        crate::perf::record_connection(false);
+        self.set_current_credential(&key).await;
+        Ok(PooledSession {
+            session,
+            key,
+            _permit: permit,
+        })
+    }
+
+    /// 从池中取出键下最近归还的空闲连接
+    async fn take_idle(&self, key: &PoolKey) -> Option<ImapConnection> {
+        let mut pool = self.connections.lock().await;
+        let idle = pool.get_mut(key)?;
+        let conn = idle.pop();
+        if idle.is_empty() {
+            pool.remove(key);
+        }
+        conn
+    }
+
+    async fn set_current_credential(&self, key: &PoolKey) {
+        self.current_credentials
+            .lock()
             .await
-            .map_err(|e| format!("无法连接到邮件服务器: {}", e))?;
+            .insert(key.email.to_lowercase(), key.credential.clone());
+    }
+
+    /// 归还连接到池中，连接放入池后释放名额
+    /// 
+    /// 借出期间账户密码发生变化或账户重新登录时，连接的凭据已过期，登出后丢弃，不再放回池中
+    pub async fn return_connection(&self, session: PooledSession<'_>) {
+        let PooledSession { session, key, _permit } = session;
+        let stale = self.current_credentials.lock().await.get(&key.email.to_lowercase()) != Some(&key.credential);
+        if stale {
+            log::debug!("归还的连接凭据已过期，登出连接");
+            logout_all(vec![ImapConnection { session, last_used: Instant::now() }], PURGE_LOGOUT_TIMEOUT).await;
+            return;
+        }
         
-        let tls = TlsConnector::new();
-        let tls_stream = tls
-            .connect(server, tcp_stream)
-            .await
-            .map_err(|e| format!("TLS连接失败: {}", e))?;
+        // 同一个键下的空闲连接超过并发上限时登出最早归还的
+        let displaced = {
+            let mut pool = self.connections.lock().await;
+            let idle = pool.entry(key).or_default();
+            idle.push(ImapConnection {
+                session,
+                last_used: Instant::now(),
+            });
+            let excess = idle.len().saturating_sub(self.max_connections);
+            idle.drain(..excess).collect::<Vec<_>>()
+        };
+        logout_all(displaced, PURGE_LOGOUT_TIMEOUT).await;
+    }
+
+    /// 丢弃借出的连接并释放名额
+    /// 操作失败后连接状态未知，不再放回池中复用
+    pub fn discard_connection<S>(&self, session: PooledSession<'_, S>) {
+        drop(session);
+    }
+
+    /// 占用一个名额借出已建立的会话，测试中以脚本会话代替网络连接
+    #[cfg(test)]
+    async fn lend<S>(&self, session: S) -> PooledSession<'_, S> {
+        PooledSession {
+            session,
+            key: PoolKey::new("user@2925.com", "secret", &ImapConfig::default()),
+            _permit: self.permits.acquire().await,
+        }
+    }
+
+    /// 移除账户在池中的所有空闲连接并依次登出，返回移除的数量
+    /// 
+    /// 在账户的密码或登录状态变化后调用，登出总耗时不超过 `PURGE_LOGOUT_TIMEOUT`。
+    /// 正在借出的连接在之后借出新连接前归还时同样登出丢弃
+    pub async fn invalidate_account(&self, email: &str) -> usize {
+        self.current_credentials.lock().await.remove(&email.to_lowercase());
+        let sessions: Vec<ImapConnection> = {
+            let mut pool = self.connections.lock().await;
+            let keys: Vec<PoolKey> = pool
+                .keys()
+                .filter(|key| key.email.eq_ignore_ascii_case(email))
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| pool.remove(key)).flatten().collect()
+        };
         
-        let client = async_imap::Client::new(tls_stream);
+        let removed = sessions.len();
+        logout_all(sessions, PURGE_LOGOUT_TIMEOUT).await;
+        removed
+    }
+
+    /// 移除账户在指定服务器上的所有空闲连接并依次登出，返回成功登出的数量
+    /// 
+    /// 用于用户手动重置连接；登出总耗时不超过 `PURGE_LOGOUT_TIMEOUT`，超时未登出的连接直接关闭。
+    /// 池中没有该账户的连接时不做任何操作。正在借出的连接不受影响
+    pub async fn purge(&self, email: &str, server: &str, port: u16) -> usize {
+        let sessions: Vec<ImapConnection> = {
+            let mut pool = self.connections.lock().await;
+            let keys: Vec<PoolKey> = pool
+                .keys()
+                .filter(|key| key.email.eq_ignore_ascii_case(email) && key.server == server && key.port == port)
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| pool.remove(key)).flatten().collect()
+        };
         
-        let session = client
-            .login(email, password)
-            .await
-            .map_err(|e| format!("登录失败: {:?}", e.0))?;
+        logout_all(sessions, PURGE_LOGOUT_TIMEOUT).await
+    }
+
+    /// 移除空闲超时的连接并依次登出，返回移除的数量
+    /// 由后台任务定期调用，避免过期连接一直占用服务器的连接数
+    pub async fn cleanup_expired(&self) -> usize {
+        let sessions: Vec<ImapConnection> = {
+            let mut pool = self.connections.lock().await;
+            let mut expired = Vec::new();
+            for idle in pool.values_mut() {
+                let (stale, fresh): (Vec<_>, Vec<_>) = std::mem::take(idle)
+                    .into_iter()
+                    .partition(|conn| conn.last_used.elapsed() >= self.max_idle_time);
+                *idle = fresh;
+                expired.extend(stale);
+            }
+            pool.retain(|_, idle| !idle.is_empty());
+            expired
+        };
+        
+        let removed = sessions.len();
+        logout_all(sessions, PURGE_LOGOUT_TIMEOUT).await;
+        removed
+    }
+
+    /// 关闭池中所有空闲连接：依次发送LOGOUT，总耗时不超过 `timeout`
+    /// 在应用退出时调用；正在借出的连接不受影响，随进程结束关闭
+    pub async fn shutdown(&self, timeout: Duration) -> usize {
+        let sessions: Vec<ImapConnection> = {
+            let mut pool = self.connections.lock().await;
+            pool.drain().flat_map(|(_, idle)| idle).collect()
+        };
         
-        Ok(session)
+        logout_all(sessions, timeout).await
     }
 
-    /// 归还连接到池中
-    pub async fn return_connection(
+    /// 当前池中空闲连接的数量
+    pub async fn size(&self) -> usize {
+        self.connections.lock().await.values().map(Vec::len).sum()
+    }
+
+    /// 当前使用的空闲超时
+    pub fn idle_time(&self) -> Duration {
+        self.max_idle_time
+    }
+
+    /// 同时借出的最大连接数
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    /// 为不经过连接池的长期连接（IDLE）占用一个并发名额，名额用尽时等待
+    /// 返回的guard被丢弃时释放名额，连接关闭前应一直持有
+    pub async fn reserve(&self) -> SemaphoreGuard<'_> {
+        self.permits.acquire().await
+    }
+
+    /// 执行 `UID THREAD REFERENCES UTF-8 ALL`（RFC 5256），返回 `* THREAD` 响应行
+    /// 
+    /// async-imap无法解析THREAD响应，解析失败会使会话无法继续使用，因此单独建立连接，
+    /// 以原始命令登录并只读打开文件夹，读取响应行后登出。单独的连接同样占用一个并发名额，
+    /// 名额用尽时等待其他连接归还
+    /// 
+    /// # 参数
+    /// * `config` - IMAP服务器配置
+    /// * `email` - 用户邮箱地址
+    /// * `password` - 用户密码
+    /// * `folder` - 已编码为修改版UTF-7的文件夹名称
+    pub async fn uid_thread(
         &self,
+        config: &ImapConfig,
         email: &str,
-        server: &str,
-        port: u16,
-        session: async_imap::Session<async_native_tls::TlsStream<TcpStream>>,
-    ) {
-        let key = format!("{}@{}:{}", email, server, port);
-        let mut pool = self.connections.lock().await;
+        password: &str,
+        folder: &str,
+    ) -> Result<Vec<String>, AppError> {
+        let _permit = self.permits.acquire().await;
         
-        pool.insert(
-            key,
-            ImapConnection {
-                session,
-                last_used: Instant::now(),
-            },
-        );
+        let tcp_stream = TcpStream::connect((config.server.as_str(), config.port))
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        let mut reader = BufReader::new(open_tls(tcp_stream, config).await?);
+        read_probe_line(&mut reader).await?;
+        
+        raw_command_with_args(&mut reader, "t1", "LOGIN", &[email, password])
+            .await
+            .map_err(|e| match e {
+                AppError::Protocol(msg) => AppError::AuthRejected(msg),
+                other => other,
+            })?;
+        raw_command_with_args(&mut reader, "t2", "EXAMINE", &[folder]).await?;
+        let lines = raw_command(&mut reader, "t3", "t3 UID THREAD REFERENCES UTF-8 ALL").await?;
+        
+        let _ = write_probe_line(&mut reader, "t4 LOGOUT").await;
+        
+        Ok(lines
+            .into_iter()
+            .filter(|line| line.get(..8).is_some_and(|prefix| prefix.eq_ignore_ascii_case("* THREAD")))
+            .collect())
     }
+}
 
-    /// 清理过期连接
-    pub async fn cleanup_expired(&self) {
-        let mut pool = self.connections.lock().await;
-        pool.retain(|_, conn| conn.last_used.elapsed() < self.max_idle_time);
+/// 依次登出连接，总耗时不超过 `timeout`，返回成功登出的数量
+async fn logout_all(sessions: Vec<ImapConnection>, timeout: Duration) -> usize {
+    let mut logged_out = 0;
+    let _ = async_std::future::timeout(timeout, async {
+        for mut conn in sessions {
+            if conn.session.logout().await.is_ok() {
+                logged_out += 1;
+            }
+        }
+    }).await;
+    
+    logged_out
+}
+
+/// 允许的最低TLS版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MinTlsVersion {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+}
+
+impl MinTlsVersion {
+    fn protocol(self) -> Protocol {
+        match self {
+            MinTlsVersion::Tls10 => Protocol::Tlsv10,
+            MinTlsVersion::Tls11 => Protocol::Tlsv11,
+            MinTlsVersion::Tls12 => Protocol::Tlsv12,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            MinTlsVersion::Tls10 => "TLS 1.0",
+            MinTlsVersion::Tls11 => "TLS 1.1",
+            MinTlsVersion::Tls12 => "TLS 1.2",
+        }
+    }
+}
+
+/// 按允许的最低版本创建TLS连接器
+/// 
+/// native-tls不提供密码套件配置，套件由系统TLS库决定；
+/// 要求TLS 1.2及以上即可排除仅在旧协议版本中使用的弱套件
+pub fn tls_connector(min_tls_version: MinTlsVersion) -> TlsConnector {
+    TlsConnector::new().min_protocol_version(Some(min_tls_version.protocol()))
+}
+
+/// 在TCP连接上建立TLS，IMAP和SMTP连接共用；服务器仅支持低于最低版本的协议时握手失败
+/// 
+/// # 参数
+/// * `tcp_stream` - 已建立的TCP连接
+/// * `server` - 服务器域名，用于验证证书
+/// * `min_tls_version` - 允许的最低TLS版本
+pub async fn tls_handshake(
+    tcp_stream: TcpStream,
+    server: &str,
+    min_tls_version: MinTlsVersion,
+) -> Result<TlsStream<TcpStream>, AppError> {
+    tls_connector(min_tls_version)
+        .connect(server, tcp_stream)
+        .await
+        .map_err(|e| AppError::Tls(format!(
+            "{} ({} {})",
+            e,
+            tr("要求的最低版本为"),
+            min_tls_version.label()
+        )))
+}
+
+/// 建立新的IMAP连接并登录
+/// 
+/// # 参数
+/// * `email` - 用户邮箱地址
+/// * `password` - 用户密码
+/// * `config` - IMAP服务器配置
+pub async fn connect(
+    email: &str,
+    password: &str,
+    config: &ImapConfig,
+) -> Result<ImapSession, AppError> {
+    // 连接到IMAP服务器
+    let tcp_stream = TcpStream::connect((config.server.as_str(), config.port))
+        .await
+        .map_err(|e| AppError::Connection(e.to_string()))?;
+    
+    let tls_stream = open_tls(tcp_stream, config).await?;
+    login_over(tls_stream, email, password).await
+}
+
+/// 在TCP连接上按IMAP配置建立TLS
+async fn open_tls(tcp_stream: TcpStream, config: &ImapConfig) -> Result<TlsStream<TcpStream>, AppError> {
+    tls_handshake(tcp_stream, &config.server, config.min_tls_version).await
+}
+
+/// 连接诊断使用的加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImapSecurity {
+    /// 连接后立即TLS握手（通常为993端口）
+    #[default]
+    Tls,
+    /// 明文连接（通常为143端口），诊断时不升级，用于查看服务器是否声明STARTTLS
+    StartTls,
+}
+
+/// 登录前从服务器读取到的原始信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerProbe {
+    /// 服务器问候行，如 `* OK [CAPABILITY IMAP4rev1 ...] ready`
+    pub greeting: String,
+    /// CAPABILITY命令的响应行，服务器拒绝命令时包含带标签的NO/BAD行
+    pub capabilities: String,
+}
+
+/// 连接服务器，读取问候行和CAPABILITY响应后登出，不进行认证
+/// 
+/// # 参数
+/// * `config` - IMAP服务器配置
+/// * `security` - 加密方式
+pub async fn probe(config: &ImapConfig, security: ImapSecurity) -> Result<ServerProbe, AppError> {
+    let tcp_stream = TcpStream::connect((config.server.as_str(), config.port))
+        .await
+        .map_err(|e| AppError::Connection(e.to_string()))?;
+    
+    match security {
+        ImapSecurity::Tls => read_probe(open_tls(tcp_stream, config).await?).await,
+        ImapSecurity::StartTls => read_probe(tcp_stream).await,
+    }
+}
+
+async fn read_probe<T: ImapTransport>(stream: T) -> Result<ServerProbe, AppError> {
+    let mut reader = BufReader::new(stream);
+    let greeting = read_probe_line(&mut reader).await?;
+    
+    write_probe_line(&mut reader, "p1 CAPABILITY").await?;
+    let mut lines = Vec::new();
+    loop {
+        let line = read_probe_line(&mut reader).await?;
+        if let Some(status) = line.strip_prefix("p1 ") {
+            if !status.to_ascii_uppercase().starts_with("OK") {
+                lines.push(line);
+            }
+            break;
+        }
+        lines.push(line);
+    }
+    
+    // 登出失败不影响诊断结果
+    let _ = write_probe_line(&mut reader, "p2 LOGOUT").await;
+    
+    Ok(ServerProbe {
+        greeting,
+        capabilities: lines.join("\n"),
+    })
+}
+
+async fn read_probe_line<T: ImapTransport>(reader: &mut BufReader<T>) -> Result<String, AppError> {
+    let mut line = String::new();
+    let read = reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    if read == 0 {
+        return Err(AppError::Network(tr("连接意外中断").to_string()));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+async fn write_probe_line<T: ImapTransport>(reader: &mut BufReader<T>, line: &str) -> Result<(), AppError> {
+    let stream = reader.get_mut();
+    stream
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    stream.flush().await.map_err(|e| AppError::Network(e.to_string()))
+}
+
+/// 发送一条原始命令并读取到带标签的结束行，返回之前的未经请求的响应行
+/// 结束行不是OK时返回协议错误
+async fn raw_command<T: ImapTransport>(
+    reader: &mut BufReader<T>,
+    tag: &str,
+    command: &str,
+) -> Result<Vec<String>, AppError> {
+    write_probe_line(reader, command).await?;
+    let mut lines = Vec::new();
+    loop {
+        let line = read_probe_line(reader).await?;
+        if let Some(status) = line.strip_prefix(tag).and_then(|rest| rest.strip_prefix(' ')) {
+            if status.get(..2).is_some_and(|ok| ok.eq_ignore_ascii_case("OK")) {
+                return Ok(lines);
+            }
+            return Err(AppError::Protocol(status.to_string()));
+        }
+        lines.push(line);
+    }
+}
+
+/// 发送带参数的原始命令并读取到带标签的结束行
+/// 
+/// 只含可打印ASCII字符的参数写为引用字符串，其他参数（如非ASCII密码）以字面量 `{n}` 发送：
+/// 发送长度后等待服务器的继续请求，再发送参数内容和命令的其余部分
+async fn raw_command_with_args<T: ImapTransport>(
+    reader: &mut BufReader<T>,
+    tag: &str,
+    command: &str,
+    args: &[&str],
+) -> Result<Vec<String>, AppError> {
+    let mut line = format!("{} {}", tag, command);
+    for arg in args {
+        if arg.contains(['\r', '\n']) {
+            return Err(AppError::Protocol(tr("参数包含不支持的字符").to_string()));
+        }
+        if let Some(quoted) = quote_raw(arg) {
+            line.push(' ');
+            line.push_str(&quoted);
+            continue;
+        }
+        
+        line.push_str(&format!(" {{{}}}", arg.len()));
+        write_probe_line(reader, &line).await?;
+        let response = read_probe_line(reader).await?;
+        if !response.starts_with('+') {
+            // 服务器拒绝字面量时直接返回带标签的结束行
+            let status = response.strip_prefix(tag).map_or(response.as_str(), str::trim_start);
+            return Err(AppError::Protocol(status.to_string()));
+        }
+        line = arg.to_string();
+    }
+    raw_command(reader, tag, &line).await
+}
+
+/// 将参数写为IMAP引用字符串，只接受可打印ASCII字符，其他字符需要以字面量发送
+fn quote_raw(value: &str) -> Option<String> {
+    if !value.chars().all(|c| (' '..='~').contains(&c)) {
+        return None;
+    }
+    Some(format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+/// 在已建立的传输上创建IMAP客户端并登录
+/// 
+/// # 参数
+/// * `stream` - 底层传输
+/// * `email` - 用户邮箱地址
+/// * `password` - 用户密码
+pub async fn login_over<T: ImapTransport>(
+    stream: T,
+    email: &str,
+    password: &str,
+) -> Result<async_imap::Session<T>, AppError> {
+    let client = async_imap::Client::new(stream);
+    client
+        .login(email, password)
+        .await
+        .map_err(|(e, _client)| AppError::from_login_error(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_imap::ScriptedStream;
+
+    /// 接受连接但从不响应的本地服务器，连接停在TLS握手；返回配置和已接受的连接数
+    async fn silent_server() -> (ImapConfig, Arc<Mutex<Vec<TcpStream>>>) {
+        let listener = async_std::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ImapConfig {
+            server: String::from("127.0.0.1"),
+            port: listener.local_addr().unwrap().port(),
+            ..ImapConfig::default()
+        };
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let streams = accepted.clone();
+        async_std::task::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.lock().await.push(stream);
+            }
+        });
+        (config, accepted)
+    }
+
+    /// 等待已接受的连接数达到 `count`，最多等待2秒
+    async fn wait_for_connections(accepted: &Mutex<Vec<TcpStream>>, count: usize) -> usize {
+        for _ in 0..40 {
+            if accepted.lock().await.len() >= count {
+                break;
+            }
+            async_std::task::sleep(Duration::from_millis(50)).await;
+        }
+        accepted.lock().await.len()
+    }
+
+    /// 接受连接后立即关闭的本地服务器，连接在TLS握手时失败
+    async fn closing_server() -> ImapConfig {
+        let listener = async_std::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ImapConfig {
+            server: String::from("127.0.0.1"),
+            port: listener.local_addr().unwrap().port(),
+            ..ImapConfig::default()
+        };
+        async_std::task::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+        config
+    }
+
+    /// 脚本会话，只用于占用名额
+    async fn scripted_session() -> async_imap::Session<ScriptedStream> {
+        login_over(ScriptedStream::new("A0001 OK LOGIN completed\r\n"), "user@2925.com", "secret").await.unwrap()
+    }
+
+    #[async_std::test]
+    async fn failed_connection_releases_permit() {
+        let config = closing_server().await;
+        let pool = ImapPool::with_limits(Duration::from_secs(60), 1);
+        
+        // 名额没有释放时第二次获取会一直等待
+        for _ in 0..3 {
+            let result = async_std::future::timeout(
+                Duration::from_secs(5),
+                pool.get_connection("user@2925.com", "secret", &config),
+            ).await;
+            assert!(matches!(result, Ok(Err(_))), "连接失败后名额没有释放");
+        }
+    }
+
+    #[async_std::test]
+    async fn discarded_session_releases_permit() {
+        let pool = ImapPool::with_limits(Duration::from_secs(60), 1);
+        let borrowed = pool.lend(scripted_session().await).await;
+        let waiting = async_std::future::timeout(Duration::from_millis(100), pool.lend(scripted_session().await)).await;
+        assert!(waiting.is_err(), "名额用尽时应等待");
+        
+        // 操作失败后丢弃连接
+        pool.discard_connection(borrowed);
+        let next = async_std::future::timeout(Duration::from_secs(1), pool.lend(scripted_session().await)).await;
+        assert!(next.is_ok(), "丢弃连接后名额没有释放");
+    }
+
+    #[async_std::test]
+    async fn reserved_permit_counts_toward_limit() {
+        let pool = ImapPool::with_limits(Duration::from_secs(60), 2);
+        let idle = pool.reserve().await;
+        let _borrowed = pool.lend(scripted_session().await).await;
+        let waiting = async_std::future::timeout(Duration::from_millis(100), pool.lend(scripted_session().await)).await;
+        assert!(waiting.is_err(), "IDLE占用的名额应计入并发上限");
+        
+        drop(idle);
+        let next = async_std::future::timeout(Duration::from_secs(1), pool.lend(scripted_session().await)).await;
+        assert!(next.is_ok(), "IDLE连接关闭后名额没有释放");
+    }
+
+    #[test]
+    fn panic_during_operation_releases_permit() {
+        let pool = ImapPool::with_limits(Duration::from_secs(60), 1);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            async_std::task::block_on(async {
+                let _session = pool.lend(scripted_session().await).await;
+                panic!("操作中发生panic");
+            })
+        }));
+        assert!(panicked.is_err());
+        
+        let next = async_std::task::block_on(async {
+            async_std::future::timeout(Duration::from_secs(1), pool.lend(scripted_session().await)).await
+        });
+        assert!(next.is_ok(), "panic后名额没有释放");
+    }
+
+    #[async_std::test]
+    async fn request_beyond_limit_waits_for_permit() {
+        let (config, accepted) = silent_server().await;
+        let pool = Arc::new(ImapPool::with_limits(Duration::from_secs(60), 2));
+        let borrow = || {
+            let pool = pool.clone();
+            let config = config.clone();
+            async_std::task::spawn(async move {
+                let _ = pool.get_connection("user@2925.com", "secret", &config).await;
+            })
+        };
+        
+        let first = borrow();
+        let _second = borrow();
+        assert_eq!(wait_for_connections(&accepted, 2).await, 2);
+        let _third = borrow();
+        
+        // 名额用尽，第三个请求不会建立连接
+        async_std::task::sleep(Duration::from_millis(300)).await;
+        assert_eq!(accepted.lock().await.len(), 2);
+        
+        // 第一个请求放弃后释放名额，第三个请求继续
+        first.cancel().await;
+        assert_eq!(wait_for_connections(&accepted, 3).await, 3);
+    }
+
+    #[async_std::test]
+    async fn non_ascii_password_is_sent_as_literal() {
+        let stream = ScriptedStream::new("+ Ready for literal\r\nt1 OK LOGIN completed\r\n");
+        let written = stream.written();
+        let mut reader = BufReader::new(stream);
+        
+        raw_command_with_args(&mut reader, "t1", "LOGIN", &["user@2925.com", "密码\"1"]).await.unwrap();
+        let written = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+        assert_eq!(written, "t1 LOGIN \"user@2925.com\" {8}\r\n密码\"1\r\n");
+    }
+
+    #[async_std::test]
+    async fn ascii_arguments_are_quoted() {
+        let stream = ScriptedStream::new("t2 OK [READ-ONLY] EXAMINE completed\r\n");
+        let written = stream.written();
+        let mut reader = BufReader::new(stream);
+        
+        raw_command_with_args(&mut reader, "t2", "EXAMINE", &["a\\b \"c\""]).await.unwrap();
+        assert_eq!(written.lock().unwrap().as_slice(), b"t2 EXAMINE \"a\\\\b \\\"c\\\"\"\r\n");
+    }
+
+    #[async_std::test]
+    async fn rejected_literal_is_protocol_error() {
+        let stream = ScriptedStream::new("t1 NO Literal too long\r\n");
+        let mut reader = BufReader::new(stream);
+        
+        let error = raw_command_with_args(&mut reader, "t1", "LOGIN", &["user@2925.com", "pässword"]).await.unwrap_err();
+        assert!(matches!(&error, AppError::Protocol(status) if status == "NO Literal too long"), "{:?}", error);
     }
 }
@@ -0,0 +1,146 @@
+//! 内嵌图片模块
+//! 
+//! HTML邮件通过 `cid:` 引用同一封邮件中带Content-ID的图片部分，WebView无法直接加载，
+//! 这里将这些图片转换为base64 data URI并替换HTML中的引用。data URI会让正文变大数倍，
+//! 只在获取单封邮件详情时替换，列表、缓存、归档和预取中的HTML正文保留原始引用
+
+use base64::{engine::general_purpose, Engine as _};
+use mailparse::{MailHeaderMap, ParsedMail};
+
+/// 单封邮件内嵌图片的总大小上限（字节），超出的图片保留原始 `cid:` 引用
+const MAX_INLINE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 按深度优先顺序查找第一个 `text/html` 部分
+fn find_html<'a>(part: &'a ParsedMail<'a>) -> Option<&'a ParsedMail<'a>> {
+    if part.ctype.mimetype.eq_ignore_ascii_case("text/html") {
+        return Some(part);
+    }
+    part.subparts.iter().find_map(find_html)
+}
+
+/// 收集所有带Content-ID的图片部分
+fn collect_images<'a>(part: &'a ParsedMail<'a>, images: &mut Vec<&'a ParsedMail<'a>>) {
+    if part.ctype.mimetype.to_ascii_lowercase().starts_with("image/")
+        && part.headers.get_first_value("Content-ID").is_some()
+    {
+        images.push(part);
+    }
+    for subpart in &part.subparts {
+        collect_images(subpart, images);
+    }
+}
+
+/// HTML正文，`cid:` 引用保持不变；邮件没有HTML部分时返回None
+pub fn html_body(parsed: &ParsedMail) -> Option<String> {
+    crate::decode_part_text(find_html(parsed)?)
+}
+
+/// HTML中是否有 `cid:` 引用
+pub fn has_cid_references(html: &str) -> bool {
+    html.to_ascii_lowercase().contains("cid:")
+}
+
+/// 将HTML中对 `content_id` 的 `cid:` 引用替换为 `data_uri`，没有引用时返回None
+/// 
+/// 引用之后必须是引号、`)` 或空白（或正文结束），`cid:img1` 不会替换 `cid:img10` 的前缀
+fn replace_cid(html: &str, content_id: &str, data_uri: &str) -> Option<String> {
+    // 只转换ASCII字母，字节位置与原文一致
+    let lower = html.to_ascii_lowercase();
+    let mut output = String::with_capacity(html.len());
+    let mut copied = 0;
+    let mut search = 0;
+    while let Some(pos) = lower[search..].find("cid:") {
+        let start = search + pos;
+        let id_start = start + "cid:".len();
+        let id_end = id_start + content_id.len();
+        let is_reference = html.get(id_start..id_end) == Some(content_id)
+            && html[id_end..]
+                .chars()
+                .next()
+                .is_none_or(|c| matches!(c, '"' | '\'' | ')') || c.is_whitespace());
+        if is_reference {
+            output.push_str(&html[copied..start]);
+            output.push_str(data_uri);
+            copied = id_end;
+            search = id_end;
+        } else {
+            search = id_start;
+        }
+    }
+    if copied == 0 {
+        return None;
+    }
+    output.push_str(&html[copied..]);
+    Some(output)
+}
+
+/// 将内嵌图片替换为data URI后的HTML正文
+/// 邮件没有HTML部分时返回None
+/// 
+/// `http(s)` 远程图片保持不变，由界面决定是否加载
+pub fn html_with_inline_images(parsed: &ParsedMail) -> Option<String> {
+    let mut html = html_body(parsed)?;
+    
+    let mut images = Vec::new();
+    collect_images(parsed, &mut images);
+    
+    let mut inlined = 0;
+    for image in images {
+        let Some(content_id) = image.headers.get_first_value("Content-ID") else {
+            continue;
+        };
+        let content_id = content_id.trim().trim_start_matches('<').trim_end_matches('>');
+        if content_id.is_empty() {
+            continue;
+        }
+        
+        let Ok(data) = image.get_body_raw() else {
+            continue;
+        };
+        if inlined + data.len() > MAX_INLINE_BYTES {
+            continue;
+        }
+        
+        let data_uri = format!(
+            "data:{};base64,{}",
+            image.ctype.mimetype.to_ascii_lowercase(),
+            general_purpose::STANDARD.encode(&data)
+        );
+        if let Some(replaced) = replace_cid(&html, content_id, &data_uri) {
+            html = replaced;
+            inlined += data.len();
+        }
+    }
+    
+    Some(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_only_whole_content_id() {
+        let html = r#"<img src="cid:img1"><img src='cid:img10'><div style="background:url(CID:img1)">"#;
+        let replaced = replace_cid(html, "img1", "data:image/png;base64,AA").unwrap();
+        assert_eq!(
+            replaced,
+            r#"<img src="data:image/png;base64,AA"><img src='cid:img10'><div style="background:url(data:image/png;base64,AA)">"#
+        );
+        assert_eq!(replace_cid(html, "img2", "data:"), None);
+    }
+
+    #[test]
+    fn inlines_related_images() {
+        let raw = "Content-Type: multipart/related; boundary=b\r\n\r\n\
+            --b\r\nContent-Type: text/html\r\n\r\n<img src=\"cid:logo@x\"><img src=\"cid:logo@x2\">\r\n\
+            --b\r\nContent-Type: image/png\r\nContent-ID: <logo@x>\r\nContent-Transfer-Encoding: base64\r\n\r\niVBO\r\n\
+            --b--\r\n";
+        let parsed = mailparse::parse_mail(raw.as_bytes()).unwrap();
+        
+        assert_eq!(html_body(&parsed).unwrap().trim(), r#"<img src="cid:logo@x"><img src="cid:logo@x2">"#);
+        let html = html_with_inline_images(&parsed).unwrap();
+        assert!(html.starts_with(r#"<img src="data:image/png;base64,iVBO">"#), "{}", html);
+        assert!(html.contains("cid:logo@x2"));
+    }
+}
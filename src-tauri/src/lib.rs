@@ -1,19 +1,67 @@
 use serde::{Deserialize, Serialize};
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_store::StoreExt;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
 use std::fs::OpenOptions;
 use std::io::Write;
-use async_native_tls::TlsConnector;
-use async_std::net::TcpStream;
 use mailparse::MailHeaderMap;
+use async_std::io::WriteExt;
 use async_std::stream::StreamExt;
+use async_imap::types::{Fetch, Flag, NameAttribute};
+use async_imap::imap_proto::{MailboxDatum, Response, Status};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 
+mod archive;
+mod avatar;
+mod calendar;
+mod connection_status;
+mod contacts;
 mod crypto;
+mod delivery_status;
+mod diagnostics;
+mod drafts;
+mod email;
+mod email_cache;
+mod envelope;
+mod error;
+mod fetch_parts;
+mod filters;
+mod i18n;
+mod idle;
+mod imap_pool;
+mod inline_images;
+mod logging;
+mod mime_structure;
+#[cfg(test)]
+mod mock_imap;
+mod outbox;
+mod perf;
+mod persist;
+mod prefetch;
+mod read_position;
+mod refresh;
+mod smtp;
+mod snippet;
+mod snooze;
+mod threading;
+mod unread;
+mod unsubscribe;
+mod utf7;
+mod vacation;
+
+use email::Email;
+use error::AppError;
+use i18n::tr;
+use imap_pool::{ImapPool, ImapSession, ImapTransport};
+use connection_status::{ConnectionState, ConnectionStatus, ConnectionStatusTracker};
 
 /// 认证会话结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 缺少的字段使用默认值，多出的字段被忽略；缺少 `expiresAt` 的会话视为已过期
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 struct AuthSession {
     email: String,
     token: String,
@@ -32,7 +80,9 @@ struct SubEmail {
 }
 
 /// 用户偏好设置结构
+/// 缺少的字段（旧版本保存的数据）使用默认值，多出的字段（新版本保存的数据）被忽略
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 struct UserPreferences {
     #[serde(rename = "autoRefreshInterval")]
     auto_refresh_interval: i64,
@@ -41,6 +91,72 @@ struct UserPreferences {
     window_size: WindowSize,
     #[serde(rename = "autoLogin")]
     auto_login: bool,
+    /// 发件人显示名称
+    #[serde(rename = "displayName", default)]
+    display_name: Option<String>,
+    /// 附加在发送邮件正文末尾的签名（纯文本），为None或空时不附加
+    #[serde(default)]
+    signature: Option<String>,
+    /// 附件总大小上限（MB）
+    #[serde(rename = "attachmentLimitMb", default = "default_attachment_limit_mb")]
+    attachment_limit_mb: u64,
+    /// 每个文件夹离线缓存的邮件数上限，为0时不缓存
+    #[serde(rename = "cacheLimit", default = "default_cache_limit")]
+    cache_limit: usize,
+    /// 离线缓存是否以gzip压缩保存，默认压缩
+    #[serde(rename = "compressCache", default = "default_compress_cache")]
+    compress_cache: bool,
+    /// 已发送文件夹中邮件的保留天数，超过的邮件由后台任务删除；为None（默认）时不删除
+    #[serde(rename = "sentRetentionDays", default)]
+    sent_retention_days: Option<u32>,
+    /// 打开邮件时是否标记为已读
+    #[serde(rename = "markReadOnOpen", default = "default_mark_read_on_open")]
+    mark_read_on_open: bool,
+    /// 后端日志级别
+    #[serde(rename = "logLevel", default)]
+    log_level: logging::LogLevel,
+    /// 点击窗口关闭按钮时的行为
+    #[serde(rename = "closeAction", default)]
+    close_action: CloseAction,
+}
+
+fn default_attachment_limit_mb() -> u64 {
+    smtp::DEFAULT_ATTACHMENT_LIMIT_MB
+}
+
+fn default_cache_limit() -> usize {
+    email_cache::DEFAULT_CACHE_LIMIT
+}
+
+fn default_compress_cache() -> bool {
+    true
+}
+
+fn default_mark_read_on_open() -> bool {
+    true
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            auto_refresh_interval: 5000,
+            theme: "glass".to_string(),
+            window_size: WindowSize {
+                width: 1200,
+                height: 800,
+            },
+            auto_login: true,
+            display_name: None,
+            signature: None,
+            attachment_limit_mb: default_attachment_limit_mb(),
+            cache_limit: default_cache_limit(),
+            compress_cache: default_compress_cache(),
+            sent_retention_days: None,
+            mark_read_on_open: default_mark_read_on_open(),
+            log_level: logging::LogLevel::default(),
+            close_action: CloseAction::default(),
+        }
+    }
 }
 
 /// 窗口尺寸结构
@@ -50,6 +166,136 @@ struct WindowSize {
     height: i32,
 }
 
+/// 窗口关闭按钮的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CloseAction {
+    /// 退出应用
+    #[default]
+    Quit,
+    /// 隐藏窗口，应用继续在托盘中运行
+    MinimizeToTray,
+    /// 发送 `close-requested` 事件，由界面询问后调用 `quit_app` 或 `hide_to_tray`
+    Ask,
+}
+
+/// 偏好设置写入锁
+/// 多个子系统（窗口尺寸、后台任务等）会并发写入偏好设置，读-改-写期间持有此锁避免更新丢失
+#[derive(Default)]
+struct PreferencesLock(async_std::sync::Mutex<()>);
+
+/// IMAP服务器配置结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ImapConfig {
+    server: String,
+    port: u16,
+    /// 连接池中空闲连接的最长保留时间（秒），2925会较早断开空闲连接
+    #[serde(rename = "idleTimeoutSecs", default = "default_idle_timeout_secs")]
+    idle_timeout_secs: u64,
+    /// 允许的最低TLS版本，默认TLS 1.2
+    #[serde(rename = "minTlsVersion", default)]
+    min_tls_version: imap_pool::MinTlsVersion,
+    /// 同时使用的最大连接数，修改后重启生效
+    #[serde(rename = "maxConnections", default = "default_max_connections")]
+    max_connections: usize,
+    /// 单次IMAP操作（从获取连接、登录到选择文件夹并获取邮件）的超时时间（秒）
+    #[serde(rename = "operationTimeoutSecs", default = "default_operation_timeout_secs")]
+    operation_timeout_secs: u64,
+    /// 未指定文件夹时使用的收件箱名称，默认 `INBOX`；为默认值时优先使用服务器声明的收件箱
+    #[serde(rename = "inboxName", default = "default_inbox_name")]
+    inbox_name: String,
+}
+
+/// 标准的收件箱名称（RFC 3501）
+const DEFAULT_INBOX: &str = "INBOX";
+
+fn default_inbox_name() -> String {
+    DEFAULT_INBOX.to_string()
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    imap_pool::DEFAULT_IDLE_SECS
+}
+
+fn default_max_connections() -> usize {
+    imap_pool::DEFAULT_MAX_CONNECTIONS
+}
+
+fn default_operation_timeout_secs() -> u64 {
+    60
+}
+
+impl Default for ImapConfig {
+    fn default() -> Self {
+        Self {
+            server: "imap.2925.com".to_string(),
+            port: 993,
+            idle_timeout_secs: default_idle_timeout_secs(),
+            min_tls_version: imap_pool::MinTlsVersion::default(),
+            max_connections: default_max_connections(),
+            operation_timeout_secs: default_operation_timeout_secs(),
+            inbox_name: default_inbox_name(),
+        }
+    }
+}
+
+/// 从存储中读取IMAP配置，未配置或数据无效时使用默认值
+fn load_imap_config(app: &tauri::AppHandle) -> ImapConfig {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("imap_config"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// SMTP服务器配置结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SmtpConfig {
+    server: String,
+    port: u16,
+    #[serde(default)]
+    security: smtp::SmtpSecurity,
+    /// 允许的最低TLS版本，默认TLS 1.2
+    #[serde(rename = "minTlsVersion", default)]
+    min_tls_version: imap_pool::MinTlsVersion,
+    /// 服务器未在EHLO中声明SIZE时使用的邮件大小上限（MB），为0时不限制
+    #[serde(rename = "sizeLimitMb", default = "default_message_size_limit_mb")]
+    size_limit_mb: u64,
+    /// 单次发送（从连接、认证到服务器确认接收）的超时时间（秒）
+    #[serde(rename = "timeoutSecs", default = "default_smtp_timeout_secs")]
+    timeout_secs: u64,
+}
+
+fn default_message_size_limit_mb() -> u64 {
+    smtp::DEFAULT_MESSAGE_SIZE_LIMIT_MB
+}
+
+fn default_smtp_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            server: smtp::DEFAULT_SMTP_SERVER.to_string(),
+            port: smtp::DEFAULT_SMTP_PORT,
+            security: smtp::SmtpSecurity::default(),
+            min_tls_version: imap_pool::MinTlsVersion::default(),
+            size_limit_mb: default_message_size_limit_mb(),
+            timeout_secs: default_smtp_timeout_secs(),
+        }
+    }
+}
+
+/// 从存储中读取SMTP配置，未配置或数据无效时使用默认值
+fn load_smtp_config(app: &tauri::AppHandle) -> SmtpConfig {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("smtp_config"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
 /// 错误日志条目结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ErrorLogEntry {
@@ -61,45 +307,104 @@ struct ErrorLogEntry {
     error_type: String,
 }
 
+/// 登录结果
+#[derive(Debug, Clone, Serialize)]
+struct LoginResult {
+    #[serde(flatten)]
+    session: AuthSession,
+    /// 会话和密码是否已保存；为false时（如系统密钥环不可用）本次运行期间仍可正常使用，
+    /// 下次启动需要重新登录
+    persisted: bool,
+}
+
+/// 保存失败时只在内存中保留的密码（账户、密码），应用退出后丢失
+#[derive(Default)]
+struct MemoryCredentials(std::sync::Mutex<Option<(String, String)>>);
+
 /// 登录命令
-/// 验证邮箱和密码，返回会话信息
+/// 验证邮箱和密码，成功后保存会话和密码并返回会话信息。
+/// 认证成功但保存失败时登录仍然成功，通过 `persisted: false` 提示界面
 #[tauri::command]
-async fn login(email: String, password: String) -> Result<AuthSession, String> {
+async fn login(
+    app: tauri::AppHandle,
+    email: String,
+    password: String,
+) -> Result<LoginResult, String> {
     // 验证邮箱格式
     if !email.ends_with("@2925.com") {
-        return Err("邮箱地址必须是2925.com域名".to_string());
+        return Err(tr("邮箱地址必须是2925.com域名").to_string());
     }
     
     // 验证密码不为空
     if password.is_empty() {
-        return Err("密码不能为空".to_string());
+        return Err(tr("密码不能为空").to_string());
+    }
+    
+    // 密码按原样使用，不去除首尾空白：首尾空格也可能是密码的一部分，修改后会导致正确的密码无法登录。
+    // 只拒绝完全由空白组成的密码，这种输入几乎总是误操作，服务器也只会返回难以理解的认证错误
+    if password.trim().is_empty() {
+        return Err(tr("密码不能只包含空白字符").to_string());
     }
     
     // 验证密码长度
     if password.len() < 6 {
-        return Err("密码长度至少为6位".to_string());
+        return Err(tr("密码长度至少为6位").to_string());
     }
     
     // 尝试连接到IMAP服务器进行真实验证
-    // 标准IMAP服务器配置
-    let imap_server = "imap.2925.com";
-    let imap_port = 993; // IMAPS端口
+    let config = load_imap_config(&app);
     
     // 尝试连接并验证
-    match connect_and_verify_imap(&email, &password, imap_server, imap_port).await {
-        Ok(_) => {
-            // 验证成功，返回会话
-            Ok(AuthSession {
-                email: email.clone(),
-                token: format!("token_{}", uuid::Uuid::new_v4()),
-                expires_at: chrono::Utc::now().timestamp() + 3600,
-            })
+    if let Err(e) = connect_and_verify_imap(&email, &password, &config).await {
+        return Err(format!("{}: {}", tr("登录失败"), e));
+    }
+    
+    // 切换账户或重新登录后，旧会话的IDLE连接立即结束，按新的会话重新建立
+    app.state::<idle::IdleStop>().stop();
+    
+    // 密码可能已修改，池中用旧密码登录的空闲连接不再复用
+    let invalidated = app.state::<ImapPool>().invalidate_account(&email).await;
+    if invalidated > 0 {
+        log::debug!("登录后丢弃账户的空闲连接: {} 个", invalidated);
+    }
+    
+    // 验证成功，保存失败不影响登录
+    let session = AuthSession {
+        email: email.clone(),
+        token: format!("token_{}", uuid::Uuid::new_v4()),
+        expires_at: chrono::Utc::now().timestamp() + 3600,
+    };
+    let persisted = match persist_login(&app, &session, &password, &config.server) {
+        Ok(()) => {
+            *app.state::<MemoryCredentials>().0.lock().unwrap() = None;
+            true
         }
         Err(e) => {
-            // 验证失败
-            Err(format!("登录失败: {}", e))
+            log::warn!("保存登录凭据失败，本次运行期间使用内存中的凭据: {}", e);
+            *app.state::<MemoryCredentials>().0.lock().unwrap() = Some((email, password));
+            false
         }
-    }
+    };
+    
+    // 登录后立即识别收件箱，之后未指定文件夹的命令使用识别结果
+    let inbox = discover_inbox(&app).await;
+    log::debug!("当前账户的收件箱: {}", inbox);
+    
+    Ok(LoginResult { session, persisted })
+}
+
+/// 保存登录后的会话和密码
+fn persist_login(app: &tauri::AppHandle, session: &AuthSession, password: &str, server: &str) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    store.set("session", serde_json::to_value(session).unwrap());
+    // 会话保存失败时所有操作都无法读取当前账户，这里总是写入内存中的存储
+    let saved = persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save session: {}", e));
+    
+    crypto::encrypt_and_save_password(password, &session.email, server)
+        .map_err(|e| format!("{}: {}", tr("保存密码失败"), e))?;
+    saved
 }
 
 /// 连接并验证IMAP服务器
@@ -107,431 +412,5513 @@ async fn login(email: String, password: String) -> Result<AuthSession, String> {
 /// # 参数
 /// * `email` - 用户邮箱地址
 /// * `password` - 用户密码
-/// * `server` - IMAP服务器地址
-/// * `port` - IMAP服务器端口
+/// * `config` - IMAP服务器配置
 async fn connect_and_verify_imap(
     email: &str,
     password: &str,
-    server: &str,
-    port: u16,
-) -> Result<(), String> {
-    // 连接到IMAP服务器
-    let tcp_stream = TcpStream::connect((server, port))
-        .await
-        .map_err(|e| format!("无法连接到邮件服务器: {}", e))?;
-    
-    // 创建TLS连接
-    let tls = TlsConnector::new();
-    let tls_stream = tls
-        .connect(server, tcp_stream)
-        .await
-        .map_err(|e| format!("TLS连接失败: {}", e))?;
-    
-    // 创建IMAP客户端
-    let client = async_imap::Client::new(tls_stream);
-    
-    // 尝试登录
-    let mut imap_session = client
-        .login(email, password)
-        .await
-        .map_err(|e| format!("邮箱或密码错误: {:?}", e.0))?;
+    config: &ImapConfig,
+) -> Result<(), AppError> {
+    // 连接并尝试登录，认证失败与网络错误会得到不同的错误类型
+    let mut imap_session = imap_pool::connect(email, password, config).await?;
     
     // 登录成功，登出并关闭连接
     imap_session
         .logout()
         .await
-        .map_err(|e| format!("登出失败: {}", e))?;
+        .map_err(|e| AppError::Network(format!("{}: {}", tr("登出失败"), e)))?;
     
     Ok(())
 }
 
 /// 登出命令
+/// 清除保存的会话和内存中的凭据，并立即结束当前账户的IDLE连接，不再接收该账户的推送。
+/// 加密保存的密码不删除
 #[tauri::command]
-async fn logout() -> Result<(), String> {
-    // TODO: 实现实际的登出逻辑
-    Ok(())
+async fn logout(app: tauri::AppHandle) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    store.delete("session");
+    *app.state::<MemoryCredentials>().0.lock().unwrap() = None;
+    // 会话清除后IDLE循环不会再为该账户重新连接
+    app.state::<idle::IdleStop>().stop();
+    
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("{}: {}", tr("登出失败"), e))
 }
 
-/// 获取邮件列表命令
-/// 从IMAP服务器获取邮件
-#[tauri::command]
-async fn fetch_emails(app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, String> {
+/// 从存储中加载当前会话
+/// 会话不存在或已过期时返回错误
+fn load_session_info(app: &tauri::AppHandle) -> Result<AuthSession, String> {
     // 从存储中加载会话信息
     let store = app.store("store.json")
-        .map_err(|e| format!("无法访问存储: {}", e))?;
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
     
     let session_value = store.get("session")
-        .ok_or("未登录，请先登录")?;
+        .ok_or(tr("未登录，请先登录"))?;
     
     let session: AuthSession = serde_json::from_value(session_value.clone())
-        .map_err(|e| format!("会话数据无效: {}", e))?;
+        .map_err(|e| format!("{}: {}", tr("会话数据无效"), e))?;
     
     // 检查会话是否过期
     if chrono::Utc::now().timestamp() > session.expires_at {
-        return Err("会话已过期，请重新登录".to_string());
+        return Err(tr("会话已过期，请重新登录").to_string());
     }
     
-    // 尝试从三层加密存储中读取密码
-    let password = match crypto::load_and_decrypt_password(&session.email) {
-        Ok(pwd) => pwd,
+    Ok(session)
+}
+
+/// 从存储中加载当前会话和登录凭据
+/// 会话不存在或已过期时返回错误
+fn load_credentials(app: &tauri::AppHandle) -> Result<(AuthSession, String), String> {
+    let session = load_session_info(app)?;
+    
+    // 从三层加密存储中读取密码，登录时无法保存的使用内存中的密码
+    let server = load_imap_config(app).server;
+    let password = match crypto::load_and_decrypt_password(&session.email, &server) {
+        Ok(password) => password,
+        Err(e) => app.state::<MemoryCredentials>().0.lock().unwrap()
+            .as_ref()
+            .filter(|(account, _)| *account == session.email)
+            .map(|(_, password)| password.clone())
+            .ok_or_else(|| format!("{}: {}", tr("未找到登录凭据"), e))?,
+    };
+    
+    Ok((session, password))
+}
+
+/// 借用IMAP会话执行操作的异步闭包返回类型
+type ImapFuture<'s, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 's>>;
+
+/// 使用连接池中的IMAP会话执行一次操作
+/// 操作成功后归还连接以便下次复用；失败或超时时会话状态未知，直接丢弃
+/// 
+/// 超时从等待名额开始计算，包括复用连接前的NOOP检查和建立新连接时的登录
+/// 
+/// # 参数
+/// * `app` - 应用句柄，用于读取会话、凭据和IMAP配置
+/// * `op` - 接收已登录会话的操作
+async fn with_imap_session<T, F>(app: &tauri::AppHandle, op: F) -> Result<T, String>
+where
+    F: for<'s> FnOnce(&'s mut ImapSession) -> ImapFuture<'s, T>,
+{
+    let (session, password) = load_credentials(app)?;
+    log::debug!("IMAP操作开始: {}", session.email);
+    
+    // 从连接池获取IMAP连接
+    let config = load_imap_config(app);
+    let pool = app.state::<ImapPool>();
+    connection_status::begin_connect(app, &session.email);
+    
+    let operation = async {
+        let mut imap_session = match pool.get_connection(&session.email, &password, &config).await {
+            Ok(imap_session) => imap_session,
+            Err(e) => {
+                connection_status::update(app, &session.email, ConnectionState::Offline, Some(e.to_string()));
+                return Err(e.into());
+            }
+        };
+        
+        let result = op(&mut imap_session).await;
+        match result {
+            // 操作成功，归还连接以便下次复用
+            Ok(_) => {
+                connection_status::update(app, &session.email, ConnectionState::Connected, None);
+                pool.return_connection(imap_session).await
+            }
+            // 操作失败时连接可能已处于异常状态，丢弃连接但释放并发名额
+            Err(ref e) => {
+                log::debug!("IMAP操作失败，丢弃连接: {}", e);
+                pool.discard_connection(imap_session)
+            }
+        }
+        result
+    };
+    
+    // 服务器可能在登录、NOOP或数据流中途停止响应，获取连接和操作一起设置超时，保证命令总能返回；
+    // 超时后借出的连接随操作一起丢弃，名额随之释放
+    let timeout = std::time::Duration::from_secs(config.operation_timeout_secs);
+    match async_std::future::timeout(timeout, operation).await {
+        Ok(result) => result,
         Err(_) => {
-            // 如果三层加密读取失败，尝试从旧的store读取（向后兼容）
-            let password_value = store.get("password")
-                .ok_or("未找到登录凭据")?;
-            
-            serde_json::from_value(password_value.clone())
-                .map_err(|e| format!("密码数据无效: {}", e))?
+            let error = AppError::OperationTimeout(config.operation_timeout_secs);
+            connection_status::update(app, &session.email, ConnectionState::Offline, Some(error.to_string()));
+            Err(error.into())
         }
+    }
+}
+
+/// 获取连接状态命令
+/// 返回各账户当前的连接状态，供界面首次渲染；之后的变化通过 `connection-status` 事件推送
+#[tauri::command]
+async fn get_connection_status(app: tauri::AppHandle) -> Result<Vec<ConnectionStatus>, String> {
+    Ok(app.state::<ConnectionStatusTracker>().snapshot())
+}
+
+/// 预热连接池命令
+/// 提前建立一个已登录的连接放入连接池，首次获取邮件时直接复用，省去连接和登录的往返。
+/// 没有会话或没有保存的密码时不做任何操作
+/// 
+/// 返回是否已预热
+#[tauri::command]
+async fn warm_pool(app: tauri::AppHandle) -> Result<bool, String> {
+    let Ok((session, password)) = load_credentials(&app) else {
+        return Ok(false);
     };
     
-    // 连接到IMAP服务器获取邮件
-    let imap_server = "imap.2925.com";
-    let imap_port = 993;
+    let config = load_imap_config(&app);
+    let pool = app.state::<ImapPool>();
+    connection_status::begin_connect(&app, &session.email);
+    let imap_session = match pool.get_connection(&session.email, &password, &config).await {
+        Ok(imap_session) => imap_session,
+        Err(e) => {
+            connection_status::update(&app, &session.email, ConnectionState::Offline, Some(e.to_string()));
+            return Err(e.into());
+        }
+    };
+    pool.return_connection(imap_session).await;
+    connection_status::update(&app, &session.email, ConnectionState::Connected, None);
     
-    fetch_emails_from_imap(&session.email, &password, imap_server, imap_port).await
+    Ok(true)
 }
 
-/// 从IMAP服务器获取邮件
+/// 重置连接命令
+/// 登出并移除当前账户在连接池中的所有空闲连接，然后重新建立一个连接确认能否连接服务器。
+/// 重置时推送 `reconnecting` 状态，之后按重新连接的结果推送 `connected` 或 `offline`
+/// 
+/// 返回登出的连接数
+#[tauri::command]
+async fn reset_connection(app: tauri::AppHandle) -> Result<usize, String> {
+    let session = load_session_info(&app)?;
+    let config = load_imap_config(&app);
+    
+    let logged_out = app.state::<ImapPool>().purge(&session.email, &config.server, config.port).await;
+    log::debug!("重置连接，已登出 {} 个IMAP连接", logged_out);
+    connection_status::update(&app, &session.email, ConnectionState::Reconnecting, Some(tr("连接已重置").to_string()));
+    
+    with_imap_session(&app, |imap_session| Box::pin(async move {
+        imap_session
+            .noop()
+            .await
+            .map_err(|e| format!("{}: {}", tr("网络连接异常"), e))
+    })).await?;
+    
+    Ok(logged_out)
+}
+
+/// 未指定文件夹时使用收件箱
+async fn resolve_folder(app: &tauri::AppHandle, folder: Option<String>) -> String {
+    match folder.filter(|name| !name.trim().is_empty()) {
+        Some(folder) => folder,
+        None => inbox_name(app).await,
+    }
+}
+
+/// 当前账户的收件箱名称
+/// 配置了非默认的 `inboxName` 时以配置为准；否则使用已缓存的特殊用途文件夹中的 `\Inbox`
+/// （服务器声明的收件箱，或没有INBOX时的 `\All`），都没有时为 `INBOX`
+/// 
+/// 特殊用途文件夹在登录时识别；这里等待缓存锁而不是在锁被占用时退回 `INBOX`，
+/// 否则与识别同时进行的命令会打开错误的文件夹
+async fn inbox_name(app: &tauri::AppHandle) -> String {
+    let configured = load_imap_config(app).inbox_name;
+    let configured = configured.trim();
+    if !configured.is_empty() && configured != DEFAULT_INBOX {
+        return configured.to_string();
+    }
+    
+    let Ok(session) = load_session_info(app) else {
+        return default_inbox_name();
+    };
+    let cache = app.state::<SpecialFoldersCache>();
+    let folders = cache.0.lock().await;
+    folders
+        .get(&session.email)
+        .and_then(|folders| folders.get("\\Inbox").cloned())
+        .unwrap_or_else(default_inbox_name)
+}
+
+/// 识别服务器上的收件箱后返回其名称，首次调用时获取特殊用途文件夹
+async fn discover_inbox(app: &tauri::AppHandle) -> String {
+    if let Err(e) = special_folders(app).await {
+        log::debug!("获取特殊用途文件夹失败，使用配置的收件箱: {}", e);
+    }
+    inbox_name(app).await
+}
+
+/// 解析邮件ID（UID）
+fn parse_message_id(message_id: &str) -> Result<u32, String> {
+    message_id
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("{}: {}", tr("无效的邮件ID"), message_id))
+}
+
+/// 修改单封邮件的标记
 /// 
 /// # 参数
-/// * `email` - 用户邮箱地址
-/// * `password` - 用户密码
-/// * `server` - IMAP服务器地址
-/// * `port` - IMAP服务器端口
-async fn fetch_emails_from_imap(
-    email: &str,
-    password: &str,
-    server: &str,
-    port: u16,
-) -> Result<Vec<serde_json::Value>, String> {
-    // 连接到IMAP服务器
-    let tcp_stream = TcpStream::connect((server, port))
+/// * `imap_session` - 已登录的IMAP会话
+/// * `folder` - 邮件所在文件夹
+/// * `message_id` - 邮件ID（UID）
+/// * `query` - STORE数据项，如 `+FLAGS (\Seen)`
+async fn store_message_flags(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+    query: &str,
+) -> Result<(), String> {
+    let uid = select_message(imap_session, folder, message_id).await?;
+    
+    let updates = imap_session
+        .uid_store(uid.to_string(), query)
+        .await
+        .map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+    
+    let mut updates = std::pin::pin!(updates);
+    while let Some(result) = updates.next().await {
+        result.map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+    }
+    
+    Ok(())
+}
+
+/// 打开文件夹并确认邮件存在，返回邮件UID
+/// STORE、COPY等命令对不存在的UID会静默成功，操作前需先确认
+async fn select_message(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+) -> Result<u32, String> {
+    select_message_in(imap_session, folder, message_id).await.map(|(_, uid)| uid)
+}
+
+/// 同 `select_message`，同时返回SELECT的结果（如PERMANENTFLAGS）
+async fn select_message_in(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+) -> Result<(async_imap::types::Mailbox, u32), String> {
+    let uid = parse_message_id(message_id)?;
+    
+    let mailbox = imap_session
+        .select(utf7::encode(folder))
         .await
-        .map_err(|e| format!("无法连接到邮件服务器: {}", e))?;
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
     
-    // 创建TLS连接
-    let tls = TlsConnector::new();
-    let tls_stream = tls
-        .connect(server, tcp_stream)
+    let found = imap_session
+        .uid_search(format!("UID {}", uid))
         .await
-        .map_err(|e| format!("TLS连接失败: {}", e))?;
+        .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?;
+    if !found.contains(&uid) {
+        return Err(format!("{}: {}", tr("邮件不存在"), message_id));
+    }
     
-    // 创建IMAP客户端并登录
-    let client = async_imap::Client::new(tls_stream);
-    let mut imap_session = client
-        .login(email, password)
+    Ok((mailbox, uid))
+}
+
+/// 仅压缩指定UID的邮件（UID EXPUNGE），邮件需已带 `\Deleted` 标记；返回邮件是否已被压缩
+/// 
+/// 服务器不支持UIDPLUS时无法只压缩单封邮件，普通EXPUNGE会一并删除其他已标记的邮件，
+/// 此时只保留删除标记并返回false，由用户通过压缩文件夹完成删除
+async fn expunge_uid(imap_session: &mut ImapSession, uid: u32) -> Result<bool, String> {
+    expunge_uid_set(imap_session, &uid.to_string()).await
+}
+
+/// 仅压缩指定UID集合中的邮件，不支持UIDPLUS时同 `expunge_uid` 只保留删除标记并返回false
+async fn expunge_uid_set(imap_session: &mut ImapSession, uid_set: &str) -> Result<bool, String> {
+    let capabilities = imap_session
+        .capabilities()
         .await
-        .map_err(|e| format!("登录失败: {:?}", e.0))?;
+        .map_err(|e| format!("{}: {}", tr("压缩文件夹失败"), e))?;
+    if !capabilities.has_str("UIDPLUS") {
+        log::warn!("服务器不支持UIDPLUS，邮件 {} 只设置了删除标记", uid_set);
+        return Ok(false);
+    }
     
-    // 选择收件箱
-    let mailbox = imap_session
-        .select("INBOX")
+    let expunged = imap_session
+        .uid_expunge(uid_set)
         .await
-        .map_err(|e| format!("无法打开收件箱: {}", e))?;
+        .map_err(|e| format!("{}: {}", tr("压缩文件夹失败"), e))?;
+    let mut expunged = std::pin::pin!(expunged);
+    while let Some(result) = expunged.next().await {
+        result.map_err(|e| format!("{}: {}", tr("压缩文件夹失败"), e))?;
+    }
     
-    let total_messages = mailbox.exists;
+    Ok(true)
+}
+
+/// 将邮件移动到另一个文件夹
+/// 服务器支持MOVE时使用UID MOVE，否则以COPY、删除标记和UID EXPUNGE代替
+/// 
+/// 返回原邮件是否已从原文件夹中移除；服务器既不支持MOVE也不支持UIDPLUS时，
+/// 原邮件只带上删除标记，返回false
+/// 
+/// # 参数
+/// * `imap_session` - 已登录的IMAP会话
+/// * `folder` - 邮件所在文件夹
+/// * `message_id` - 邮件ID（UID）
+/// * `destination` - 目标文件夹
+async fn move_message(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+    destination: &str,
+) -> Result<bool, String> {
+    let uid = select_message(imap_session, folder, message_id).await?;
     
-    if total_messages == 0 {
-        // 没有邮件，直接返回空列表
+    let capabilities = imap_session
+        .capabilities()
+        .await
+        .map_err(|e| format!("{}: {}", tr("移动邮件失败"), e))?;
+    if capabilities.has_str("MOVE") {
         imap_session
-            .logout()
+            .uid_mv(uid.to_string(), utf7::encode(destination))
             .await
-            .map_err(|e| format!("登出失败: {}", e))?;
-        return Ok(vec![]);
+            .map_err(|e| format!("{} {}: {}", tr("移动邮件失败"), destination, e))?;
+        return Ok(true);
     }
     
-    // 计算要获取的邮件范围（最新50封）
-    let start = if total_messages > 50 {
-        total_messages - 49
-    } else {
-        1
-    };
-    let end = total_messages;
-    
-    let fetch_range = format!("{}:{}", start, end);
+    imap_session
+        .uid_copy(uid.to_string(), utf7::encode(destination))
+        .await
+        .map_err(|e| format!("{} {}: {}", tr("移动邮件失败"), destination, e))?;
+    store_message_flags(imap_session, folder, message_id, "+FLAGS (\\Deleted)").await?;
+    expunge_uid(imap_session, uid).await
+}
+
+/// 设置垃圾邮件关键字（`$Junk` / `$NotJunk`），用于训练服务器端过滤器
+/// 文件夹的PERMANENTFLAGS不允许该关键字时跳过，不视为错误
+async fn set_junk_keyword(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+    junk: bool,
+) -> Result<(), String> {
+    let (set, clear) = if junk { ("$Junk", "$NotJunk") } else { ("$NotJunk", "$Junk") };
     
-    // 获取邮件（使用RFC822获取完整邮件，INTERNALDATE获取服务器时间）
-    let mut messages = imap_session
-        .fetch(&fetch_range, "(RFC822 FLAGS INTERNALDATE)")
+    let mailbox = imap_session
+        .select(utf7::encode(folder))
         .await
-        .map_err(|e| format!("获取邮件失败: {}", e))?;
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+    let supported = mailbox.permanent_flags.iter().any(|flag| {
+        *flag == Flag::MayCreate || *flag == Flag::Custom(set.into())
+    });
+    if !supported {
+        return Ok(());
+    }
+    
+    store_message_flags(imap_session, folder, message_id, &format!("+FLAGS ({})", set)).await?;
+    store_message_flags(imap_session, folder, message_id, &format!("-FLAGS ({})", clear)).await
+}
+
+/// 获取邮件列表命令
+/// 从IMAP服务器获取邮件
+/// 
+/// # 参数
+/// * `hide_deleted` - 为true时过滤掉已标记 `\Deleted` 但尚未压缩的邮件，
+///   默认保留并通过 `isDeleted` 字段标记，便于界面置灰显示
+/// * `sort_by` - 排序方式：`date`（默认，从新到旧）、`from`、`subject`
+/// * `order` - `newestFirst`（默认）获取最新的邮件，`oldestFirst` 获取最早的邮件并按日期从旧到新排列
+#[tauri::command]
+async fn fetch_emails(
+    app: tauri::AppHandle,
+    hide_deleted: Option<bool>,
+    sort_by: Option<SortKey>,
+    order: Option<Order>,
+) -> Result<Vec<Email>, String> {
+    let sort_by = sort_by.unwrap_or_default();
+    let order = order.unwrap_or_default();
+    let inbox = discover_inbox(&app).await;
+    let mut emails = with_imap_session(&app, {
+        let inbox = inbox.clone();
+        move |imap_session| Box::pin(async move {
+            fetch_emails_from_imap(imap_session, &inbox, sort_by, order).await
+        })
+    }).await.map_err(|e| {
+        if e.starts_with(tr("无法打开文件夹")) {
+            format!("{} ({}): {}", tr("无法打开收件箱，请检查IMAP配置中的收件箱名称"), inbox, e)
+        } else {
+            e
+        }
+    })?;
+    
+    // 写入离线缓存，失败不影响本次获取结果；缓存只保存最新的邮件
+    if let (Order::NewestFirst, Ok(session)) = (order, load_session_info(&app)) {
+        let preferences = current_preferences(&app);
+        let (limit, compress) = (preferences.cache_limit, preferences.compress_cache);
+        if let Err(e) = email_cache::write_folder(&app, &session.email, &inbox, &emails, limit, compress) {
+            log::warn!("写入邮件缓存失败: {}", e);
+        }
+    }
+    if let Ok(session) = load_session_info(&app) {
+        if let Err(e) = contacts::record(&app, &session.email, &inbox, &emails).await {
+            log::warn!("更新联系人索引失败: {}", e);
+        }
+    }
+    
+    if hide_deleted.unwrap_or(false) {
+        emails.retain(|email| !email.is_deleted);
+    }
+    tag_snoozed(&app, &inbox, &mut emails);
+    
+    Ok(emails)
+}
+
+/// 获取邮件列表（仅信封）命令
+/// 通过 `ENVELOPE` 获取服务器解析好的发件人、收件人、主题和日期，不下载正文，
+/// 比获取完整邮件更快；返回的邮件 `envelopeOnly` 为true，正文为空
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+/// * `sort_by` - 排序方式：`date`（默认，从新到旧）、`from`、`subject`
+/// * `order` - `newestFirst`（默认）或 `oldestFirst`，同 `fetch_emails`
+#[tauri::command]
+async fn fetch_email_envelopes(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+    sort_by: Option<SortKey>,
+    order: Option<Order>,
+) -> Result<Vec<Email>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let sort_by = sort_by.unwrap_or_default();
+    let order = order.unwrap_or_default();
+    let mut emails = with_imap_session(&app, {
+        let folder = folder.clone();
+        move |imap_session| Box::pin(async move {
+            fetch_list(imap_session, &folder, sort_by, order, "(UID ENVELOPE FLAGS INTERNALDATE)", build_envelope).await
+        })
+    }).await?;
+    tag_snoozed(&app, &folder, &mut emails);
+    Ok(emails)
+}
+
+/// 获取多个文件夹的合并邮件列表命令
+/// 依次获取每个文件夹最新邮件的信封并合并排序，每封邮件带 `folder` 字段表示其 `id` 所属的文件夹
+/// 
+/// 启用去重时按Message-ID合并同一封邮件（如同时出现在收件箱和标签文件夹中），
+/// 保留第一个文件夹中的副本，并在 `folders` 中列出它出现的所有文件夹；没有Message-ID的邮件视为不同的邮件
+/// 
+/// # 参数
+/// * `folders` - 文件夹名称列表，为空时只获取收件箱
+/// * `dedup` - 是否按Message-ID去重，默认false
+/// * `sort_by` - 排序方式，默认按日期从新到旧
+#[tauri::command]
+async fn fetch_unified(
+    app: tauri::AppHandle,
+    folders: Vec<String>,
+    dedup: Option<bool>,
+    sort_by: Option<SortKey>,
+) -> Result<Vec<Email>, String> {
+    let mut folders: Vec<String> = folders.into_iter().filter(|name| !name.trim().is_empty()).collect();
+    if folders.is_empty() {
+        folders.push(resolve_folder(&app, None).await);
+    }
+    let sort_by = sort_by.unwrap_or_default();
     
     let mut emails = Vec::new();
+    for folder in folders {
+        let mut fetched = with_imap_session(&app, {
+            let folder = folder.clone();
+            move |imap_session| Box::pin(async move {
+                fetch_list(imap_session, &folder, sort_by, Order::NewestFirst, "(UID ENVELOPE FLAGS INTERNALDATE)", build_envelope).await
+            })
+        }).await?;
+        for email in &mut fetched {
+            email.folder = Some(folder.clone());
+        }
+        emails.extend(fetched);
+    }
     
-    // 遍历消息流
-    while let Some(fetch_result) = messages.next().await {
-        match fetch_result {
-            Ok(message) => {
-                // 尝试获取邮件正文
-                let body_data = message.body();
-                
-                // 获取INTERNALDATE作为备用时间戳
-                let internal_date_timestamp = message.internal_date()
-                    .map(|dt| {
-                        // DateTime<FixedOffset>可以直接获取时间戳
-                        dt.timestamp_millis()
-                    });
-                
-                if let Some(body) = body_data {
-                    // 解析邮件
-                    match mailparse::parse_mail(body) {
-                        Ok(parsed) => {
-                            // 获取邮件时间戳 - 优先使用Date头部，其次使用INTERNALDATE
-                            let timestamp = if let Some(date_str) = parsed.headers.get_first_value("Date") {
-                                // 尝试解析RFC2822格式
-                                match chrono::DateTime::parse_from_rfc2822(&date_str) {
-                                    Ok(dt) => dt.timestamp_millis(),
-                                    Err(_) => {
-                                        // 解析失败，使用INTERNALDATE或当前时间
-                                        internal_date_timestamp.unwrap_or_else(|| {
-                                            eprintln!("邮件 {} Date头部解析失败，使用INTERNALDATE", message.message);
-                                            chrono::Utc::now().timestamp_millis()
-                                        })
-                                    }
-                                }
-                            } else {
-                                // 没有Date头部，使用INTERNALDATE
-                                internal_date_timestamp.unwrap_or_else(|| {
-                                    eprintln!("邮件 {} 没有Date头部，使用当前时间", message.message);
-                                    chrono::Utc::now().timestamp_millis()
-                                })
-                            };
-                            
-                            // 获取邮件正文 - 尝试多种方式
-                            let body_text = if let Ok(body_str) = parsed.get_body() {
-                                if body_str.trim().is_empty() {
-                                    // 如果纯文本为空，尝试获取HTML
-                                    parsed.subparts.iter()
-                                        .find(|part| {
-                                            part.ctype.mimetype.contains("text/html") ||
-                                            part.ctype.mimetype.contains("text/plain")
-                                        })
-                                        .and_then(|part| part.get_body().ok())
-                                        .unwrap_or_else(|| String::from("邮件内容为空"))
-                                } else {
-                                    body_str
-                                }
-                            } else {
-                                // 如果get_body失败，尝试从subparts获取
-                                parsed.subparts.iter()
-                                    .find(|part| {
-                                        part.ctype.mimetype.contains("text/html") ||
-                                        part.ctype.mimetype.contains("text/plain")
-                                    })
-                                    .and_then(|part| part.get_body().ok())
-                                    .unwrap_or_else(|| String::from("邮件内容为空"))
-                            };
-                            
-                            let email_json = serde_json::json!({
-                                "id": format!("{}", message.message),
-                                "from": parsed.headers.get_first_value("From").unwrap_or_else(|| String::from("未知发件人")),
-                                "to": parsed.headers.get_first_value("To").unwrap_or_else(|| String::from("未知收件人")),
-                                "subject": parsed.headers.get_first_value("Subject").unwrap_or_else(|| String::from("(无主题)")),
-                                "body": body_text,
-                                "timestamp": timestamp,
-                                "isRead": message.flags().any(|f| f == async_imap::types::Flag::Seen),
-                                "isSubEmailForwarded": false,
-                            });
-                            
-                            emails.push(email_json);
-                        }
-                        Err(e) => {
-                            eprintln!("解析邮件 {} 失败: {}", message.message, e);
-                        }
-                    }
-                } else {
-                    eprintln!("邮件 {} 没有正文数据", message.message);
+    if dedup.unwrap_or(false) {
+        emails = dedup_by_message_id(emails);
+    }
+    sort_by.sort_locally(&mut emails);
+    Ok(emails)
+}
+
+/// 按Message-ID合并重复的邮件，保留第一次出现的副本并记录所在的全部文件夹
+fn dedup_by_message_id(emails: Vec<Email>) -> Vec<Email> {
+    let mut merged: Vec<Email> = Vec::with_capacity(emails.len());
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    
+    for mut email in emails {
+        let folder = email.folder.clone().unwrap_or_default();
+        let message_id = email.message_id
+            .as_deref()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        
+        if let Some(&position) = message_id.as_ref().and_then(|id| positions.get(id)) {
+            if let Some(folders) = merged[position].folders.as_mut() {
+                if !folders.contains(&folder) {
+                    folders.push(folder);
                 }
             }
+            continue;
+        }
+        
+        email.folders = Some(vec![folder]);
+        if let Some(id) = message_id {
+            positions.insert(id, merged.len());
+        }
+        merged.push(email);
+    }
+    
+    merged
+}
+
+/// 发件人统计最多扫描的邮件数（最新的邮件）
+const SENDER_STATS_WINDOW: u32 = 2000;
+
+/// 单个发件人的邮件数量
+#[derive(Debug, Clone, Serialize)]
+struct SenderStat {
+    /// 发件人地址（小写）
+    address: String,
+    /// 最近一封邮件中的显示名称
+    name: Option<String>,
+    count: u32,
+}
+
+/// 发件人统计命令
+/// 获取文件夹中最新邮件的信封，按发件人地址（忽略显示名称和大小写）分组计数，
+/// 返回邮件数最多的发件人；最多扫描最新的2000封邮件
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+/// * `limit` - 返回的发件人数量，默认10
+#[tauri::command]
+async fn sender_stats(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SenderStat>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let limit = limit.unwrap_or(10);
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let mailbox = imap_session
+            .select(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        if mailbox.exists == 0 {
+            return Ok(vec![]);
+        }
+        
+        let start = mailbox.exists.saturating_sub(SENDER_STATS_WINDOW - 1).max(1);
+        
+        // 按序号从旧到新处理，后出现的显示名称覆盖之前的
+        let mut senders: HashMap<String, SenderStat> = HashMap::new();
+        fetch_in_chunks(imap_session, start..=mailbox.exists, "ENVELOPE", |message| {
+            let Some(sender) = message.envelope()
+                .and_then(|envelope| envelope.from.as_ref())
+                .and_then(|from| from.first())
+            else {
+                return;
+            };
+            let Some(address) = envelope::bare_address(sender) else {
+                return;
+            };
+            let name = sender.name.as_ref()
+                .map(|name| envelope::decode_text(name).trim().to_string())
+                .filter(|name| !name.is_empty());
+            
+            let address = address.to_lowercase();
+            let stat = senders.entry(address.clone()).or_insert(SenderStat {
+                address,
+                name: None,
+                count: 0,
+            });
+            stat.count += 1;
+            if name.is_some() {
+                stat.name = name;
+            }
+        }).await?;
+        
+        let mut stats: Vec<SenderStat> = senders.into_values().collect();
+        stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.address.cmp(&b.address)));
+        stats.truncate(limit);
+        Ok(stats)
+    })).await
+}
+
+/// 最大邮件列表命令
+/// 先获取文件夹中所有邮件的 `RFC822.SIZE`（不下载正文），再只对最大的几封获取信封，
+/// 按大小从大到小返回，每封邮件带 `size` 字段（字节），用于清理邮箱空间
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+/// * `limit` - 返回的邮件数量，默认20，最多200
+#[tauri::command]
+async fn largest_messages(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<Email>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let limit = limit.unwrap_or(20).min(200);
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let mailbox = imap_session
+            .examine(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        if mailbox.exists == 0 || limit == 0 {
+            return Ok(vec![]);
+        }
+        
+        // 每批只保留最大的limit封，内存占用与文件夹大小无关
+        let mut sizes: Vec<(u32, u32)> = Vec::new();
+        fetch_in_chunks(imap_session, 1..=mailbox.exists, "(UID RFC822.SIZE)", |message| {
+            if let (Some(uid), Some(size)) = (message.uid, message.size) {
+                sizes.push((uid, size));
+            }
+            if sizes.len() >= limit * 2 {
+                sizes.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+                sizes.truncate(limit);
+            }
+        }).await?;
+        sizes.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        sizes.truncate(limit);
+        if sizes.is_empty() {
+            return Ok(vec![]);
+        }
+        
+        let uid_set = sizes.iter().map(|(uid, _)| uid.to_string()).collect::<Vec<_>>().join(",");
+        let messages = imap_session
+            .uid_fetch(&uid_set, "(UID ENVELOPE FLAGS INTERNALDATE)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let mut emails = collect_with(messages, build_envelope).await?;
+        
+        let size_of = |email: &Email| {
+            let uid = email.uid();
+            sizes.iter().find(|(sorted, _)| Some(*sorted) == uid).map(|(_, size)| *size)
+        };
+        for email in &mut emails {
+            email.size = size_of(email);
+        }
+        emails.sort_by_key(|email| std::cmp::Reverse(email.size));
+        
+        Ok(emails)
+    })).await
+}
+
+/// 读取离线缓存的邮件命令
+/// 无法连接服务器时使用，返回上次获取时缓存的邮件（从新到旧）
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn load_cached_emails(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<Vec<Email>, String> {
+    let session = load_session_info(&app)?;
+    email_cache::read_folder(&app, &session.email, &resolve_folder(&app, folder).await)
+}
+
+/// 清除离线缓存命令
+/// 
+/// # 参数
+/// * `folder` - 只清除该文件夹的缓存，默认清除当前账户的全部缓存
+#[tauri::command]
+async fn clear_cache(app: tauri::AppHandle, folder: Option<String>) -> Result<(), String> {
+    let session = load_session_info(&app)?;
+    let folder = folder.filter(|name| !name.trim().is_empty());
+    email_cache::clear(&app, &session.email, folder.as_deref())
+}
+
+/// 删除邮件命令
+/// 仅设置 `\Deleted` 标记，不会立即EXPUNGE；在压缩文件夹之前可通过 `undelete_email` 撤销
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn delete_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<(), String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        store_message_flags(imap_session, &folder, &message_id, "+FLAGS (\\Deleted)").await
+    })).await
+}
+
+/// 撤销删除命令
+/// 清除邮件的 `\Deleted` 标记，仅在文件夹被压缩之前有效
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn undelete_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<(), String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        store_message_flags(imap_session, &folder, &message_id, "-FLAGS (\\Deleted)").await
+    })).await
+}
+
+/// 星标命令
+/// 通过UID STORE设置或清除邮件的 `\Flagged` 标记
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `flagged` - true为加星标，false为取消星标
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn set_flagged(
+    app: tauri::AppHandle,
+    message_id: String,
+    flagged: bool,
+    folder: Option<String>,
+) -> Result<(), String> {
+    let folder = resolve_folder(&app, folder).await;
+    let query = if flagged { "+FLAGS (\\Flagged)" } else { "-FLAGS (\\Flagged)" };
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        store_message_flags(imap_session, &folder, &message_id, query).await
+    })).await
+}
+
+/// 按标记设置邮件的已读、删除、星标状态和自定义关键字
+fn apply_flags(email: &mut Email, flags: &[Flag<'_>]) {
+    email.is_read = flags.contains(&Flag::Seen);
+    email.is_deleted = flags.contains(&Flag::Deleted);
+    email.is_flagged = flags.contains(&Flag::Flagged);
+    email.keywords = flags
+        .iter()
+        .filter_map(|flag| match flag {
+            Flag::Custom(keyword) if !keyword.starts_with('\\') => Some(keyword.to_string()),
+            _ => None,
+        })
+        .collect();
+}
+
+/// 邮件的自定义关键字（如 `$Important`、`work`），不含 `\Seen` 等系统标记
+fn message_keywords(message: &Fetch) -> Vec<String> {
+    message.flags()
+        .filter_map(|flag| match flag {
+            Flag::Custom(keyword) if !keyword.starts_with('\\') => Some(keyword.into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 检查关键字是否为合法的IMAP atom：非空、不以 `\` 开头，不含空白、控制字符和 `(){%*"\]`
+fn is_valid_keyword(keyword: &str) -> bool {
+    !keyword.is_empty()
+        && keyword.len() <= 128
+        && keyword.chars().all(|c| c.is_ascii_graphic() && !"(){%*\"\\]".contains(c))
+}
+
+/// 设置关键字命令
+/// 通过UID STORE添加或移除任意自定义关键字，用于给邮件打标签
+/// 
+/// 服务器的PERMANENTFLAGS不含 `\*` 且没有列出该关键字时，关键字无法永久保存，返回错误而不是静默丢失
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `keyword` - 关键字，需为合法的IMAP atom
+/// * `add` - true为添加，false为移除
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn set_keyword(
+    app: tauri::AppHandle,
+    message_id: String,
+    keyword: String,
+    add: bool,
+    folder: Option<String>,
+) -> Result<(), String> {
+    let folder = resolve_folder(&app, folder).await;
+    let keyword = keyword.trim().to_string();
+    if !is_valid_keyword(&keyword) {
+        return Err(format!("{}: {}", tr("关键字无效"), keyword));
+    }
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let (mailbox, uid) = select_message_in(imap_session, &folder, &message_id).await?;
+        
+        // 移除关键字不受限制；服务器没有返回PERMANENTFLAGS时直接尝试
+        let permanent = &mailbox.permanent_flags;
+        let storable = permanent.is_empty()
+            || permanent.iter().any(|flag| match flag {
+                Flag::MayCreate => true,
+                Flag::Custom(existing) => existing.eq_ignore_ascii_case(&keyword),
+                _ => false,
+            });
+        if add && !storable {
+            return Err(format!("{}: {}", tr("服务器不支持自定义关键字"), keyword));
+        }
+        
+        let query = format!("{}FLAGS ({})", if add { "+" } else { "-" }, keyword);
+        let updates = imap_session
+            .uid_store(uid.to_string(), &query)
+            .await
+            .map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+        let mut updates = std::pin::pin!(updates);
+        while let Some(result) = updates.next().await {
+            result.map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+        }
+        
+        Ok(())
+    })).await
+}
+
+/// 获取邮件标记命令
+/// 只执行 `UID FETCH (FLAGS)`，不下载正文，用于同步在其他客户端中修改的已读/星标状态
+/// 
+/// 返回邮件ID到标记状态的映射，已不存在的邮件不会出现在结果中
+/// 
+/// # 参数
+/// * `message_ids` - 邮件ID（UID）列表
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn get_flags(
+    app: tauri::AppHandle,
+    message_ids: Vec<String>,
+    folder: Option<String>,
+) -> Result<HashMap<String, MessageFlags>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let uids = message_ids
+        .iter()
+        .map(|id| parse_message_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    if uids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .select(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        
+        let uid_set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let messages = imap_session
+            .uid_fetch(&uid_set, "(UID FLAGS)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        
+        let mut messages = std::pin::pin!(messages);
+        let mut flags = HashMap::new();
+        while let Some(result) = messages.next().await {
+            let message = result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            if let Some(uid) = message.uid {
+                flags.insert(uid.to_string(), MessageFlags::from_fetch(&message));
+            }
+        }
+        
+        Ok(flags)
+    })).await
+}
+
+/// 文件夹标记同步状态（CONDSTORE）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FlagSyncState {
+    #[serde(rename = "uidValidity")]
+    uid_validity: u32,
+    /// 上次同步时文件夹的HIGHESTMODSEQ
+    #[serde(rename = "highestModseq")]
+    highest_modseq: u64,
+}
+
+/// 标记同步结果
+#[derive(Debug, Clone, Serialize)]
+struct FlagSyncResult {
+    /// 上次同步以来标记发生变化的邮件，完整同步时为文件夹内全部邮件
+    changes: HashMap<String, MessageFlags>,
+    /// 本次同步后的HIGHESTMODSEQ，服务器不支持CONDSTORE时为None
+    #[serde(rename = "highestModseq")]
+    highest_modseq: Option<u64>,
+    /// 为true表示返回的是全部邮件的标记（首次同步、UIDVALIDITY变化或服务器不支持CONDSTORE）
+    #[serde(rename = "fullSync")]
+    full_sync: bool,
+}
+
+/// 增量同步邮件标记命令
+/// 
+/// 服务器支持CONDSTORE时，每个文件夹在存储中记录HIGHESTMODSEQ，之后通过
+/// `UID FETCH 1:* (FLAGS) (CHANGEDSINCE modseq)` 只获取在其他设备上修改过标记的邮件；
+/// 不支持CONDSTORE、首次同步或UIDVALIDITY变化时回退为获取全部邮件的标记
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn sync_flags(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<FlagSyncResult, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let session = load_session_info(&app)?;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    let key = sync_state_key(&session.email, &folder);
+    let mut sync_states: serde_json::Map<String, serde_json::Value> = store
+        .get("flag_sync_state")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    let previous: Option<FlagSyncState> = sync_states
+        .get(&key)
+        .and_then(|value| serde_json::from_value(value.clone()).ok());
+    
+    let (result, uid_validity) = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let capabilities = imap_session
+            .capabilities()
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let condstore = capabilities.has_str("CONDSTORE");
+        
+        let mailbox = if condstore {
+            imap_session.select_condstore(utf7::encode(&folder)).await
+        } else {
+            imap_session.select(utf7::encode(&folder)).await
+        }
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        let uid_validity = mailbox.uid_validity.unwrap_or(0);
+        let highest_modseq = mailbox.highest_modseq.filter(|_| condstore);
+        
+        let changed_since = match (&previous, highest_modseq) {
+            (Some(state), Some(_)) if state.uid_validity == uid_validity => Some(state.highest_modseq),
+            _ => None,
+        };
+        
+        // 文件夹自上次同步后没有任何变化，无需请求
+        if let (Some(since), Some(current)) = (changed_since, highest_modseq) {
+            if current <= since {
+                return Ok((FlagSyncResult {
+                    changes: HashMap::new(),
+                    highest_modseq,
+                    full_sync: false,
+                }, uid_validity));
+            }
+        }
+        
+        let mut changes = HashMap::new();
+        if mailbox.exists > 0 {
+            let query = match changed_since {
+                Some(since) => format!("(UID FLAGS) (CHANGEDSINCE {})", since),
+                None => "(UID FLAGS)".to_string(),
+            };
+            let messages = imap_session
+                .uid_fetch("1:*", &query)
+                .await
+                .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            
+            let mut messages = std::pin::pin!(messages);
+            while let Some(result) = messages.next().await {
+                let message = result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+                if let Some(uid) = message.uid {
+                    changes.insert(uid.to_string(), MessageFlags::from_fetch(&message));
+                }
+            }
+        }
+        
+        Ok((FlagSyncResult {
+            changes,
+            highest_modseq,
+            full_sync: changed_since.is_none(),
+        }, uid_validity))
+    })).await?;
+    
+    // 保存新的HIGHESTMODSEQ；不支持CONDSTORE时清除旧状态
+    match result.highest_modseq {
+        Some(highest_modseq) => {
+            sync_states.insert(key, serde_json::to_value(FlagSyncState {
+                uid_validity,
+                highest_modseq,
+            }).unwrap());
+        }
+        None => {
+            sync_states.remove(&key);
+        }
+    }
+    store.set("flag_sync_state", serde_json::Value::Object(sync_states));
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save sync state: {}", e))?;
+    
+    Ok(result)
+}
+
+/// 标记为垃圾邮件命令
+/// 设置 `$Junk` 关键字并将邮件移动到 `\Junk` 特殊用途文件夹，返回目标文件夹
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn mark_junk(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<String, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let junk = special_folders(&app)
+        .await?
+        .remove("\\Junk")
+        .ok_or_else(|| tr("未找到垃圾邮件文件夹").to_string())?;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        // 关键字需在移动前设置，MOVE/COPY会保留邮件的标记
+        set_junk_keyword(imap_session, &folder, &message_id, true).await?;
+        if folder != junk {
+            move_message(imap_session, &folder, &message_id, &junk).await?;
+        }
+        Ok(junk)
+    })).await
+}
+
+/// 标记为非垃圾邮件命令
+/// 设置 `$NotJunk` 关键字并将邮件移回收件箱，返回目标文件夹
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn mark_not_junk(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<String, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let inbox = resolve_folder(&app, None).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        set_junk_keyword(imap_session, &folder, &message_id, false).await?;
+        if folder != inbox {
+            move_message(imap_session, &folder, &message_id, &inbox).await?;
+        }
+        Ok(inbox)
+    })).await
+}
+
+/// 移到废纸篓的结果
+#[derive(Debug, Clone, Serialize)]
+struct TrashOutcome {
+    /// 邮件移到的废纸篓文件夹，永久删除时为None
+    folder: Option<String>,
+    /// 服务器不支持UIDPLUS，原文件夹中的邮件只带上了删除标记，压缩文件夹后才会移除
+    #[serde(rename = "markedDeleted")]
+    marked_deleted: bool,
+}
+
+/// 移到废纸篓命令
+/// 将邮件移动到 `\Trash` 特殊用途文件夹，结果中为实际使用的目标文件夹
+/// 
+/// 邮件已在废纸篓中，或账户没有废纸篓时，改为标记删除并压缩该邮件（永久删除），目标文件夹为None。
+/// 服务器不支持UIDPLUS时不执行会删除其他已标记邮件的普通EXPUNGE，`markedDeleted` 为true
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn trash_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<TrashOutcome, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let trash = special_folders(&app)
+        .await?
+        .remove("\\Trash")
+        .filter(|trash| *trash != folder);
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        match trash {
+            Some(trash) => {
+                let removed = move_message(imap_session, &folder, &message_id, &trash).await?;
+                Ok(TrashOutcome { folder: Some(trash), marked_deleted: !removed })
+            }
+            None => {
+                store_message_flags(imap_session, &folder, &message_id, "+FLAGS (\\Deleted)").await?;
+                let expunged = expunge_uid(imap_session, parse_message_id(&message_id)?).await?;
+                Ok(TrashOutcome { folder: None, marked_deleted: !expunged })
+            }
+        }
+    })).await
+}
+
+/// 邮件列表排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SortKey {
+    /// 按日期从新到旧
+    #[default]
+    Date,
+    /// 按发件人
+    From,
+    /// 按主题
+    Subject,
+}
+
+/// 邮件列表获取顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Order {
+    /// 获取最新的邮件
+    #[default]
+    NewestFirst,
+    /// 获取最早的邮件，按日期排序时从旧到新排列
+    OldestFirst,
+}
+
+impl SortKey {
+    /// SORT命令的排序条件
+    fn criteria(self, order: Order) -> &'static str {
+        match (self, order) {
+            (SortKey::Date, Order::NewestFirst) => "REVERSE DATE",
+            (SortKey::Date, Order::OldestFirst) => "DATE",
+            (SortKey::From, _) => "FROM",
+            (SortKey::Subject, _) => "SUBJECT",
+        }
+    }
+
+    /// 按指定顺序在本地排序，只有按日期排序时区分方向
+    fn sort_in_order(self, order: Order, emails: &mut [Email]) {
+        match (self, order) {
+            (SortKey::Date, Order::OldestFirst) => emails.sort_by_key(|email| email.timestamp),
+            _ => self.sort_locally(emails),
+        }
+    }
+
+    /// 服务器不支持SORT时在本地对获取到的邮件排序
+    fn sort_locally(self, emails: &mut [Email]) {
+        match self {
+            SortKey::Date => emails.sort_by_key(|email| std::cmp::Reverse(email.timestamp)),
+            SortKey::From => emails.sort_by_cached_key(|email| email.from.to_lowercase()),
+            SortKey::Subject => emails.sort_by_cached_key(|email| email.subject.to_lowercase()),
+        }
+    }
+}
+
+/// 执行 `UID SORT`，返回排序后的UID列表
+/// async-imap未封装SORT扩展（RFC 5256），这里直接发送命令并读取响应
+async fn uid_sort<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    sort_by: SortKey,
+    order: Order,
+) -> Result<Vec<u32>, String> {
+    let command = format!("UID SORT ({}) UTF-8 ALL", sort_by.criteria(order));
+    let mut uids = Vec::new();
+    run_raw_command(imap_session, &command, "获取邮件失败", |response| {
+        if let Response::MailboxData(MailboxDatum::Sort(ids)) = response {
+            uids.extend_from_slice(ids);
+        }
+    }).await?;
+    
+    Ok(uids)
+}
+
+/// 从IMAP服务器获取邮件
+/// 
+/// 服务器支持SORT时由服务器排序并取前50封，否则取序号最大（`oldestFirst` 时最小）的50封并在本地排序
+/// 
+/// # 参数
+/// * `imap_session` - 已登录的IMAP会话
+/// * `folder` - 要获取的文件夹
+/// * `sort_by` - 排序方式
+/// * `order` - 获取顺序
+async fn fetch_emails_from_imap<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    folder: &str,
+    sort_by: SortKey,
+    order: Order,
+) -> Result<Vec<Email>, String> {
+    // 使用BODY.PEEK[]获取完整邮件（RFC822会给邮件设置 `\Seen`），INTERNALDATE获取服务器时间，
+    // UID作为稳定的邮件ID
+    fetch_list(imap_session, folder, sort_by, order, "(UID BODY.PEEK[] FLAGS INTERNALDATE)", build_email).await
+}
+
+/// 获取邮件列表窗口（最新、最早或按SORT排列的前50封）
+/// 
+/// # 参数
+/// * `query` - FETCH数据项
+/// * `build` - 将单条FETCH响应转换为邮件
+async fn fetch_list<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    folder: &str,
+    sort_by: SortKey,
+    order: Order,
+    query: &str,
+    build: fn(&Fetch) -> Option<Email>,
+) -> Result<Vec<Email>, String> {
+    // 选择文件夹
+    let mailbox = imap_session
+        .select(utf7::encode(folder))
+        .await
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+    
+    let total_messages = mailbox.exists;
+    
+    if total_messages == 0 {
+        // 没有邮件，直接返回空列表
+        return Ok(vec![]);
+    }
+    
+    let capabilities = imap_session
+        .capabilities()
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    if capabilities.has_str("SORT") {
+        let window: Vec<u32> = uid_sort(imap_session, sort_by, order).await?.into_iter().take(50).collect();
+        if window.is_empty() {
+            return Ok(vec![]);
+        }
+        
+        let uid_set = window.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let messages = imap_session
+            .uid_fetch(&uid_set, query)
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let mut emails = collect_with(messages, build).await?;
+        
+        // FETCH响应按序号返回，按SORT结果重新排列
+        emails.sort_by_key(|email| {
+            let uid = email.uid();
+            window.iter().position(|&sorted| Some(sorted) == uid)
+        });
+        return Ok(emails);
+    }
+    
+    // 计算要获取的邮件范围（最新或最早的50封）
+    let (start, end) = match order {
+        Order::NewestFirst => (total_messages.saturating_sub(49).max(1), total_messages),
+        Order::OldestFirst => (1, total_messages.min(50)),
+    };
+    
+    let fetch_range = format!("{}:{}", start, end);
+    
+    let messages = imap_session
+        .fetch(&fetch_range, query)
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    
+    let mut emails = collect_with(messages, build).await?;
+    sort_by.sort_in_order(order, &mut emails);
+    Ok(emails)
+}
+
+/// 全文件夹扫描每批获取的邮件数
+const SCAN_CHUNK_SIZE: u32 = 500;
+
+/// 按序号分批获取当前选中文件夹中 `range` 范围内邮件的 `query` 数据项，每封邮件交给 `visit` 处理
+/// 每批处理完毕后才请求下一批，扫描上万封邮件的文件夹时内存占用也保持在一批以内。
+/// 无法解析的响应记录日志并跳过；命令本身失败或连接中断时返回错误，不返回不完整的结果
+async fn fetch_in_chunks<T, F>(
+    imap_session: &mut async_imap::Session<T>,
+    range: std::ops::RangeInclusive<u32>,
+    query: &str,
+    mut visit: F,
+) -> Result<(), String>
+where
+    T: ImapTransport,
+    F: FnMut(Fetch),
+{
+    let (first, last) = (*range.start().max(&1), *range.end());
+    let mut start = first;
+    while start <= last {
+        let end = start.saturating_add(SCAN_CHUNK_SIZE - 1).min(last);
+        let messages = imap_session
+            .fetch(format!("{}:{}", start, end), query)
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let mut messages = std::pin::pin!(messages);
+        while let Some(fetch_result) = messages.next().await {
+            match fetch_result {
+                Ok(message) => visit(message),
+                Err(e @ (async_imap::error::Error::Io(_) | async_imap::error::Error::ConnectionLost)) => {
+                    return Err(format!("{}: {}", tr("获取邮件失败"), e));
+                }
+                Err(e) => log::warn!("获取邮件时出错: {}", e),
+            }
+        }
+        
+        if end == u32::MAX {
+            break;
+        }
+        start = end + 1;
+    }
+    
+    Ok(())
+}
+
+/// 遍历FETCH响应流，将每封邮件转换为 `Email`
+/// 单封邮件解析失败时记录日志并跳过，连接中断时返回错误
+async fn collect_emails<S>(messages: S) -> Result<Vec<Email>, String>
+where
+    S: async_std::stream::Stream<Item = async_imap::error::Result<Fetch>>,
+{
+    collect_with(messages, build_email).await
+}
+
+/// 遍历FETCH响应流，用 `build` 将每封邮件转换为 `Email`
+/// 
+/// 服务器把UID、FLAGS和邮件数据分在同一序号的不同响应中返回时，`build` 得到的响应中没有UID
+/// 或标记，邮件ID暂为序号；读取完响应流后按序号换成其他响应中的UID，并合并各条响应中的标记
+/// 
+/// 单条响应无法解析时记录日志并跳过；读取响应时连接中断则返回错误，避免把只收到一部分的结果
+/// 当作完整的文件夹内容
+async fn collect_with<S>(messages: S, build: fn(&Fetch) -> Option<Email>) -> Result<Vec<Email>, String>
+where
+    S: async_std::stream::Stream<Item = async_imap::error::Result<Fetch>>,
+{
+    let started = std::time::Instant::now();
+    let mut messages = std::pin::pin!(messages);
+    let mut emails = Vec::new();
+    let mut uids = HashMap::new();
+    let mut without_uid = Vec::new();
+    let mut built = Vec::new();
+    // 每个序号收到的响应数和各条响应中标记的并集
+    let mut flags: HashMap<u32, (usize, Vec<Flag<'static>>)> = HashMap::new();
+    
+    // 遍历消息流
+    while let Some(fetch_result) = messages.next().await {
+        match fetch_result {
+            Ok(message) => {
+                if let Some(uid) = message.uid {
+                    uids.insert(message.message, uid);
+                }
+                let (responses, merged) = flags.entry(message.message).or_default();
+                *responses += 1;
+                fetch_parts::merge_flags(merged, &message);
+                if let Some(email) = build(&message) {
+                    if message.uid.is_none() {
+                        without_uid.push((emails.len(), message.message));
+                    }
+                    built.push((emails.len(), message.message));
+                    emails.push(email);
+                }
+            }
+            Err(e @ (async_imap::error::Error::Io(_) | async_imap::error::Error::ConnectionLost)) => {
+                return Err(format!("{}: {}", tr("获取邮件失败"), e));
+            }
+            Err(e) => {
+                log::warn!("获取邮件时出错: {}", e);
+            }
+        }
+    }
+    for (index, seq) in without_uid {
+        if let Some(uid) = uids.get(&seq) {
+            emails[index].id = uid.to_string();
+        }
+    }
+    for (index, seq) in built {
+        if let Some((responses, merged)) = flags.get(&seq).filter(|(responses, _)| *responses > 1) {
+            log::trace!("邮件 {} 的数据分在 {} 条响应中", seq, responses);
+            apply_flags(&mut emails[index], merged);
+        }
+    }
+    perf::record_fetch(started.elapsed());
+    
+    Ok(emails)
+}
+
+/// 将单条FETCH响应解析为邮件
+/// 需要FETCH包含 `UID BODY.PEEK[] FLAGS INTERNALDATE`
+fn build_email(message: &Fetch) -> Option<Email> {
+    // 完整邮件：服务器以 `BODY[]` 返回BODY.PEEK[]的数据，`body()` 同时接受旧查询的RFC822
+    let body = match message.body() {
+        Some(body) => body,
+        None => {
+            log::warn!("邮件 {} 没有正文数据", message.message);
+            return None;
+        }
+    };
+    
+    // 获取INTERNALDATE作为备用时间戳
+    let internal_date_timestamp = message.internal_date()
+        .map(|dt| {
+            // DateTime<FixedOffset>可以直接获取时间戳
+            dt.timestamp_millis()
+        });
+    
+    // 使用UID而非序号作为ID：EXPUNGE后序号会整体前移，UID保持不变
+    let id = message.uid.unwrap_or(message.message).to_string();
+    let parse_started = std::time::Instant::now();
+    let mut email = parse_email(&id, body, internal_date_timestamp)?;
+    perf::record_parse(parse_started.elapsed());
+    
+    email.is_read = message.flags().any(|f| f == async_imap::types::Flag::Seen);
+    email.is_deleted = message.flags().any(|f| f == async_imap::types::Flag::Deleted);
+    email.is_flagged = message.flags().any(|f| f == async_imap::types::Flag::Flagged);
+    email.keywords = message_keywords(message);
+    Some(email)
+}
+
+/// 将单条FETCH响应解析为邮件详情，HTML正文中的内嵌图片替换为data URI
+/// 需要FETCH包含 `UID BODY.PEEK[] FLAGS INTERNALDATE`
+fn build_detail_email(message: &Fetch) -> Option<Email> {
+    let mut email = build_email(message)?;
+    if email.body_html.as_deref().is_some_and(inline_images::has_cid_references) {
+        let parsed = mailparse::parse_mail(message.body()?).ok()?;
+        email.body_html = inline_images::html_with_inline_images(&parsed);
+    }
+    Some(email)
+}
+
+/// 将包含ENVELOPE的FETCH响应转换为邮件
+/// 需要FETCH包含 `UID ENVELOPE FLAGS INTERNALDATE`；不含正文，`envelopeOnly` 为true
+fn build_envelope(message: &Fetch) -> Option<Email> {
+    let Some(envelope) = message.envelope() else {
+        log::warn!("邮件 {} 没有信封数据", message.message);
+        return None;
+    };
+    
+    let internal_date_timestamp = message.internal_date().map(|dt| dt.timestamp_millis());
+    let timestamp = envelope::timestamp(envelope)
+        .or(internal_date_timestamp)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let delivered_to = envelope::recipients(envelope)
+        .iter()
+        .find_map(|address| sub_email_suffix(address));
+    let delivered_to_suffix = delivered_to.as_ref().map(|(_, suffix)| suffix.clone());
+    
+    let mut email = Email {
+        id: message.uid.unwrap_or(message.message).to_string(),
+        from: envelope::format_addresses(envelope.from.as_ref()).unwrap_or_else(|| String::from("未知发件人")),
+        to: envelope::format_addresses(envelope.to.as_ref()).unwrap_or_else(|| String::from("未知收件人")),
+        cc: envelope::format_addresses(envelope.cc.as_ref()),
+        subject: envelope.subject.as_ref()
+            .map(|subject| envelope::decode_text(subject))
+            .filter(|subject| !subject.trim().is_empty())
+            .unwrap_or_else(|| String::from("(无主题)")),
+        message_id: envelope.message_id.as_ref().map(|id| String::from_utf8_lossy(id).into_owned()),
+        timestamp,
+        received_at: internal_date_timestamp,
+        is_read: message.flags().any(|f| f == Flag::Seen),
+        is_deleted: message.flags().any(|f| f == Flag::Deleted),
+        is_flagged: message.flags().any(|f| f == Flag::Flagged),
+        keywords: message_keywords(message),
+        is_sub_email_forwarded: delivered_to.is_some(),
+        original_sub_email: delivered_to.map(|(address, _)| address),
+        delivered_to_suffix,
+        // 只有信封信息，正文需要在详情中另外获取
+        envelope_only: true,
+        ..Email::default()
+    };
+    avatar::annotate(&mut email);
+    Some(email)
+}
+
+/// 按Content-Type中的charset将邮件部分解码为UTF-8文本
+/// 
+/// GB2312/GBK、ISO-8859-1等字符集通过encoding_rs解码（GB2312按其超集GBK处理），
+/// 字符集未知时按UTF-8有损解码；传输编码（base64/quoted-printable）解码失败时返回None
+/// 
+/// 没有声明charset时mailparse按us-ascii（即windows-1252）处理，会破坏未标注的UTF-8正文；
+/// us-ascii是UTF-8的子集，因此未声明和声明为us-ascii时都按UTF-8解码
+fn decode_part_text(part: &mailparse::ParsedMail) -> Option<String> {
+    let bytes = part.get_body_raw().ok()?;
+    
+    let encoding = part.ctype.params
+        .get("charset")
+        .map(|label| label.trim())
+        .filter(|label| !label.eq_ignore_ascii_case("us-ascii"))
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()));
+    match encoding {
+        Some(encoding) => {
+            let (text, _, _) = encoding.decode(&bytes);
+            Some(text.into_owned())
+        }
+        None => Some(String::from_utf8_lossy(&bytes).into_owned()),
+    }
+}
+
+/// 将原始邮件解析为邮件（不含标记字段）
+/// 
+/// # 参数
+/// * `id` - 邮件ID
+/// * `body` - RFC822原始邮件
+/// * `internal_date_timestamp` - 服务器接收时间（毫秒），Date头部缺失或无效时作为时间戳
+fn parse_email(
+    id: &str,
+    body: &[u8],
+    internal_date_timestamp: Option<i64>,
+) -> Option<Email> {
+    // 解析邮件
+    let parsed = match mailparse::parse_mail(body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            // 解析失败时仍返回基本信息，避免邮件从列表中消失
+            log::warn!("解析邮件 {} 失败: {}", id, e);
+            let mut email = fallback_email(id, body, internal_date_timestamp);
+            avatar::annotate(&mut email);
+            return Some(email);
+        }
+    };
+    
+    // 获取邮件时间戳 - 优先使用Date头部，其次使用INTERNALDATE
+    let timestamp = if let Some(date_str) = parsed.headers.get_first_value("Date") {
+        // 尝试解析RFC2822格式
+        match chrono::DateTime::parse_from_rfc2822(&date_str) {
+            Ok(dt) => dt.timestamp_millis(),
+            Err(_) => {
+                // 解析失败，使用INTERNALDATE或当前时间
+                internal_date_timestamp.unwrap_or_else(|| {
+                    log::warn!("邮件 {} Date头部解析失败，使用INTERNALDATE", id);
+                    chrono::Utc::now().timestamp_millis()
+                })
+            }
+        }
+    } else {
+        // 没有Date头部，使用INTERNALDATE
+        internal_date_timestamp.unwrap_or_else(|| {
+            log::warn!("邮件 {} 没有Date头部，使用当前时间", id);
+            chrono::Utc::now().timestamp_millis()
+        })
+    };
+    
+    // 获取邮件正文 - 尝试多种方式
+    let body_text = if let Some(body_str) = decode_part_text(&parsed) {
+        if body_str.trim().is_empty() {
+            // 如果纯文本为空，尝试获取HTML
+            parsed.subparts.iter()
+                .find(|part| {
+                    part.ctype.mimetype.contains("text/html") ||
+                    part.ctype.mimetype.contains("text/plain")
+                })
+                .and_then(decode_part_text)
+                .unwrap_or_else(|| String::from("邮件内容为空"))
+        } else {
+            body_str
+        }
+    } else {
+        // 如果get_body失败，尝试从subparts获取
+        parsed.subparts.iter()
+            .find(|part| {
+                part.ctype.mimetype.contains("text/html") ||
+                part.ctype.mimetype.contains("text/plain")
+            })
+            .and_then(decode_part_text)
+            .unwrap_or_else(|| String::from("邮件内容为空"))
+    };
+    
+    let snippet = snippet::build_snippet(&body_text);
+    let body_html = inline_images::html_body(&parsed);
+    let calendar_event = calendar::calendar_event(&parsed);
+    let delivery_status = delivery_status::delivery_status(&parsed);
+    let delivered_to = delivered_to_sub_email(&parsed.headers);
+    let delivered_to_suffix = delivered_to.as_ref().map(|(_, suffix)| suffix.clone());
+    
+    let mut email = Email {
+        id: id.to_string(),
+        from: parsed.headers.get_first_value("From").unwrap_or_else(|| String::from("未知发件人")),
+        to: parsed.headers.get_first_value("To").unwrap_or_else(|| String::from("未知收件人")),
+        cc: parsed.headers.get_first_value("Cc"),
+        subject: parsed.headers.get_first_value("Subject").unwrap_or_else(|| String::from("(无主题)")),
+        message_id: parsed.headers.get_first_value("Message-ID").map(|id| id.trim().to_string()),
+        body: body_text,
+        body_html,
+        calendar_event,
+        delivery_status,
+        snippet,
+        timestamp,
+        received_at: internal_date_timestamp,
+        is_sub_email_forwarded: delivered_to.is_some(),
+        original_sub_email: delivered_to.map(|(address, _)| address),
+        delivered_to_suffix,
+        unsubscribe: unsubscribe::from_headers(&parsed.headers),
+        ..Email::default()
+    };
+    // 发件人头像颜色和Gravatar哈希
+    avatar::annotate(&mut email);
+    Some(email)
+}
+
+/// 简单扫描原始头部，返回指定头部的值
+/// 只处理头部区域（第一个空行之前），支持折行，能单独解码时按RFC 2047解码
+fn scan_raw_header(raw: &[u8], name: &str) -> Option<String> {
+    let text = String::from_utf8_lossy(raw);
+    let header_block = text
+        .split("\r\n\r\n")
+        .next()
+        .and_then(|block| block.split("\n\n").next())
+        .unwrap_or_default();
+    
+    let mut value: Option<String> = None;
+    for line in header_block.lines() {
+        match value.as_mut() {
+            // 以空白开头的行是上一个头部的续行
+            Some(current) if line.starts_with([' ', '\t']) => {
+                current.push(' ');
+                current.push_str(line.trim());
+                continue;
+            }
+            Some(_) => break,
+            None => {}
+        }
+        if let Some((key, rest)) = line.split_once(':') {
+            if key.trim().eq_ignore_ascii_case(name) {
+                value = Some(rest.trim().to_string());
+            }
+        }
+    }
+    
+    value.map(|value| {
+        let line = format!("X: {}", value);
+        match mailparse::parse_header(line.as_bytes()) {
+            Ok((header, _)) => header.get_value(),
+            Err(_) => value,
+        }
+    })
+}
+
+/// mailparse解析失败时的最小邮件
+/// 发件人、主题等通过简单的头部扫描获取，正文显示为无法解析，并设置 `parseError` 标记
+fn fallback_email(id: &str, raw: &[u8], internal_date_timestamp: Option<i64>) -> Email {
+    let timestamp = scan_raw_header(raw, "Date")
+        .and_then(|date| chrono::DateTime::parse_from_rfc2822(&date).ok())
+        .map(|date| date.timestamp_millis())
+        .or(internal_date_timestamp)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    // 与正常解析相同的投递头部顺序，地址取尖括号内的部分
+    let delivered_to = ["Delivered-To", "X-Original-To", "To", "Cc"]
+        .iter()
+        .filter_map(|name| scan_raw_header(raw, name))
+        .flat_map(|value| {
+            value
+                .split(',')
+                .map(|part| {
+                    let part = part.trim();
+                    match (part.rfind('<'), part.rfind('>')) {
+                        (Some(start), Some(end)) if start < end => part[start + 1..end].trim().to_string(),
+                        _ => part.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .find_map(|address| sub_email_suffix(&address));
+    let delivered_to_suffix = delivered_to.as_ref().map(|(_, suffix)| suffix.clone());
+    
+    Email {
+        id: id.to_string(),
+        from: scan_raw_header(raw, "From").unwrap_or_else(|| String::from("未知发件人")),
+        to: scan_raw_header(raw, "To").unwrap_or_else(|| String::from("未知收件人")),
+        subject: scan_raw_header(raw, "Subject").unwrap_or_else(|| String::from("(无主题)")),
+        body: String::from("(无法解析此邮件)"),
+        snippet: String::from("(无法解析此邮件)"),
+        timestamp,
+        received_at: internal_date_timestamp,
+        is_sub_email_forwarded: delivered_to.is_some(),
+        original_sub_email: delivered_to.map(|(address, _)| address),
+        delivered_to_suffix,
+        parse_error: true,
+        ..Email::default()
+    }
+}
+
+/// 从投递头部中找出邮件实际投递到的子邮箱
+/// 依次检查 `Delivered-To`、`X-Original-To`、`To`、`Cc`，每个头部可能出现多次或包含多个收件人，
+/// 返回第一个 `local+suffix@2925.com` 形式的地址及其后缀；都没有时返回None
+fn delivered_to_sub_email(headers: &[mailparse::MailHeader]) -> Option<(String, String)> {
+    ["Delivered-To", "X-Original-To", "To", "Cc"]
+        .iter()
+        .flat_map(|name| headers.get_all_headers(name))
+        .flat_map(|header| match mailparse::addrparse_header(header) {
+            Ok(list) => list
+                .iter()
+                .flat_map(|addr| match addr {
+                    mailparse::MailAddr::Single(info) => vec![info.addr.clone()],
+                    mailparse::MailAddr::Group(group) => group.addrs.iter().map(|info| info.addr.clone()).collect(),
+                })
+                .collect::<Vec<_>>(),
+            // 无法按地址列表解析时（如Delivered-To只有裸地址的变体），按逗号拆分原始值
+            Err(_) => header.get_value().split(',').map(|part| part.trim().to_string()).collect(),
+        })
+        .find_map(|address| sub_email_suffix(&address))
+}
+
+/// 判断地址是否为 `local+suffix@2925.com` 形式的子邮箱，返回小写地址和后缀
+fn sub_email_suffix(address: &str) -> Option<(String, String)> {
+    let (local, domain) = address.rsplit_once('@')?;
+    if !domain.eq_ignore_ascii_case("2925.com") {
+        return None;
+    }
+    let (_, suffix) = local.split_once('+')?;
+    if suffix.is_empty() {
+        return None;
+    }
+    Some((address.to_lowercase(), suffix.to_string()))
+}
+
+/// 本地归档索引条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedEmail {
+    /// 归档ID
+    id: String,
+    from: String,
+    subject: String,
+    /// 邮件时间戳（毫秒）
+    date: i64,
+    /// 服务器收到邮件的时间（INTERNALDATE，毫秒），早期版本归档的条目没有
+    #[serde(rename = "receivedAt", default)]
+    received_at: Option<i64>,
+    /// 压缩文件路径
+    path: String,
+}
+
+/// 读取归档索引（store键 "archive_index"）
+fn load_archive_index(app: &tauri::AppHandle) -> Result<Vec<ArchivedEmail>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    Ok(store
+        .get("archive_index")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+/// 获取邮件的部分结构树（只执行 `UID FETCH (BODYSTRUCTURE)`，不下载正文）
+async fn fetch_structure(
+    imap_session: &mut ImapSession,
+    folder: &str,
+    message_id: &str,
+) -> Result<mime_structure::MessagePart, String> {
+    let uid = select_message(imap_session, folder, message_id).await?;
+    let messages = imap_session
+        .uid_fetch(uid.to_string(), "(UID BODYSTRUCTURE)")
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取邮件结构失败"), e))?;
+    
+    let mut messages = std::pin::pin!(messages);
+    while let Some(result) = messages.next().await {
+        let message = result.map_err(|e| format!("{}: {}", tr("获取邮件结构失败"), e))?;
+        if let Some(structure) = message.bodystructure() {
+            return Ok(mime_structure::parse_structure(structure));
+        }
+    }
+    
+    Err(format!("{}: {}", tr("邮件不存在"), message_id))
+}
+
+/// 获取单封邮件命令
+/// 使用 `BODY.PEEK[]` 获取完整邮件，获取本身不会改变已读状态；
+/// 之后按 `mark_read` 参数（未提供时按偏好设置 `markReadOnOpen`）决定是否标记为已读
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+/// * `mark_read` - 是否标记为已读，覆盖偏好设置
+#[tauri::command]
+async fn get_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+    mark_read: Option<bool>,
+) -> Result<Email, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let mark_read = mark_read.unwrap_or_else(|| current_preferences(&app).mark_read_on_open);
+    
+    // 命中预取缓存时只需在需要时修改已读标记
+    let account = load_session_info(&app)?.email;
+    let cache_key = prefetch::key(&account, &folder, parse_message_id(&message_id)?);
+    // 预取的邮件没有替换内嵌图片，有 `cid:` 引用时重新获取
+    let prefetched = app.state::<prefetch::PrefetchCache>().take(&cache_key).await.filter(|email| {
+        !email.body_html.as_deref().is_some_and(inline_images::has_cid_references)
+    });
+    if let Some(mut email) = prefetched {
+        if mark_read && !email.is_read {
+            with_imap_session(&app, move |imap_session| Box::pin(async move {
+                store_message_flags(imap_session, &folder, &message_id, "+FLAGS (\\Seen)").await
+            })).await?;
+            email.is_read = true;
+        }
+        return Ok(email);
+    }
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let uid = select_message(imap_session, &folder, &message_id).await?;
+        
+        let messages = imap_session
+            .uid_fetch(uid.to_string(), "(UID BODY.PEEK[] FLAGS INTERNALDATE)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let mut email = collect_with(messages, build_detail_email)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("{}: {}", tr("邮件不存在"), message_id))?;
+        
+        if mark_read && !email.is_read {
+            let updates = imap_session
+                .uid_store(uid.to_string(), "+FLAGS (\\Seen)")
+                .await
+                .map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+            let mut updates = std::pin::pin!(updates);
+            while let Some(result) = updates.next().await {
+                result.map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+            }
+            email.is_read = true;
+        }
+        
+        Ok(email)
+    })).await
+}
+
+/// 只读打开文件夹并获取邮件的完整头部（`BODY.PEEK[HEADER]`），不改变已读状态
+async fn fetch_message_header(imap_session: &mut ImapSession, folder: &str, uid: u32) -> Result<Vec<u8>, String> {
+    imap_session
+        .examine(utf7::encode(folder))
+        .await
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+    let messages = imap_session
+        .uid_fetch(uid.to_string(), "(UID BODY.PEEK[HEADER])")
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    let mut messages = std::pin::pin!(messages);
+    let mut parts = fetch_parts::FetchParts::default();
+    while let Some(message) = messages.next().await {
+        let message = message.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        parts.add(&message);
+    }
+    parts
+        .get(uid)
+        .and_then(|message| message.header.clone())
+        .ok_or_else(|| format!("{}: {}", tr("邮件不存在"), uid))
+}
+
+/// 邮件头部字段
+#[derive(Debug, Clone, Serialize)]
+struct HeaderField {
+    name: String,
+    /// 展开折行并按RFC 2047解码后的值
+    value: String,
+    /// 展开折行后的原始值，未解码
+    raw: String,
+}
+
+/// 展开头部折行（RFC 5322 2.2.3）：删除后面跟着空白的换行符，保留空白本身
+fn unfold_header_value(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    let mut unfolded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' if matches!(chars.peek(), Some(' ') | Some('\t')) => {}
+            '\n' => unfolded.push(' '),
+            _ => unfolded.push(c),
+        }
+    }
+    unfolded.trim().to_string()
+}
+
+/// 获取邮件头部命令
+/// 通过 `BODY.PEEK[HEADER]` 获取头部，按原顺序返回所有字段，同名字段（如多个 `Received`）都保留；
+/// 不会将邮件标记为已读
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn get_headers(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<Vec<HeaderField>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let uid = parse_message_id(&message_id)?;
+    
+    let header = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        fetch_message_header(imap_session, &folder, uid).await
+    })).await?;
+    
+    let (headers, _) = mailparse::parse_headers(&header)
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    Ok(headers
+        .iter()
+        .map(|header| HeaderField {
+            name: header.get_key(),
+            value: header.get_value(),
+            raw: unfold_header_value(header.get_value_raw()),
+        })
+        .collect())
+}
+
+/// 文件夹的会话列表
+#[derive(Debug, Clone, Serialize)]
+struct ThreadList {
+    threads: Vec<threading::ThreadNode>,
+    /// 由服务器的THREAD扩展计算；为false时在本地根据引用头部构建
+    #[serde(rename = "serverSide")]
+    server_side: bool,
+}
+
+/// 获取文件夹的会话树命令
+/// 
+/// 服务器声明 `THREAD=REFERENCES` 时使用 `UID THREAD REFERENCES UTF-8 ALL`，
+/// 无需下载任何头部；否则获取所有邮件的Message-ID、In-Reply-To和References在本地构建。
+/// 服务器端计算失败时同样回退到本地构建
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn fetch_threads(app: tauri::AppHandle, folder: Option<String>) -> Result<ThreadList, String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    let supported = with_imap_session(&app, |imap_session| Box::pin(async move {
+        let capabilities = imap_session
+            .capabilities()
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取服务器能力失败"), e))?;
+        Ok(capabilities.has_str("THREAD=REFERENCES"))
+    })).await?;
+    
+    if supported {
+        // async-imap无法解析THREAD响应，需要单独的连接
+        let (session, password) = load_credentials(&app)?;
+        let config = load_imap_config(&app);
+        let timeout = std::time::Duration::from_secs(config.operation_timeout_secs);
+        let encoded = utf7::encode(&folder);
+        let pool = app.state::<ImapPool>();
+        let thread = pool.uid_thread(&config, &session.email, &password, &encoded);
+        match async_std::future::timeout(timeout, thread).await {
+            Ok(Ok(lines)) => {
+                return Ok(ThreadList {
+                    threads: lines.iter().flat_map(|line| threading::parse_thread_response(line)).collect(),
+                    server_side: true,
+                });
+            }
+            Ok(Err(e)) => log::warn!("服务器计算会话失败，改为本地构建: {}", e),
+            Err(_) => log::warn!("服务器计算会话超时，改为本地构建"),
+        }
+    }
+    
+    let threads = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let mailbox = imap_session
+            .examine(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        
+        let mut parts = fetch_parts::FetchParts::default();
+        fetch_in_chunks(
+            imap_session,
+            1..=mailbox.exists,
+            "(UID BODY.PEEK[HEADER.FIELDS (MESSAGE-ID IN-REPLY-TO REFERENCES)])",
+            |message| parts.add(&message),
+        ).await?;
+        
+        let messages = parts
+            .into_messages()
+            .filter_map(|(uid, message)| {
+                let (headers, _) = mailparse::parse_headers(message.header.as_deref()?).ok()?;
+                Some(threading::ReferenceHeaders::from_headers(uid, &headers))
+            })
+            .collect();
+        Ok(threading::build_threads(messages))
+    })).await?;
+    
+    Ok(ThreadList {
+        threads,
+        server_side: false,
+    })
+}
+
+/// 获取服务器上的自动回复状态命令
+/// 
+/// 服务器不支持METADATA扩展时返回 `supported: false`
+#[tauri::command]
+async fn get_vacation_status(app: tauri::AppHandle) -> Result<vacation::VacationStatus, String> {
+    with_imap_session(&app, |imap_session| Box::pin(vacation::fetch(imap_session))).await
+}
+
+/// 悬停预览默认获取的正文字节数
+const DEFAULT_PREVIEW_BYTES: u32 = 2048;
+/// 悬停预览最多获取的正文字节数
+const MAX_PREVIEW_BYTES: u32 = 64 * 1024;
+
+/// 邮件预览命令
+/// 通过 `BODY.PEEK[TEXT]<0.n>` 只获取正文开头的一部分，按传输编码和字符集解码后返回纯文本预览，
+/// 比 `get_email` 开销小，且不会将邮件标记为已读
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+/// * `max_bytes` - 获取的正文字节数，默认2048，最多64KB
+#[tauri::command]
+async fn preview_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+    max_bytes: Option<u32>,
+) -> Result<String, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let uid = parse_message_id(&message_id)?;
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_PREVIEW_BYTES).clamp(1, MAX_PREVIEW_BYTES);
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .examine(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        // 头部提供Content-Type和传输编码，正文只取开头
+        let messages = imap_session
+            .uid_fetch(uid.to_string(), format!("(UID BODY.PEEK[HEADER] BODY.PEEK[TEXT]<0.{}>)", max_bytes))
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let mut messages = std::pin::pin!(messages);
+        let mut parts = fetch_parts::FetchParts::default();
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            parts.add(&message);
+        }
+        let message = parts
+            .get(uid)
+            .ok_or_else(|| format!("{}: {}", tr("邮件不存在"), message_id))?;
+        Ok(snippet::partial_preview(
+            message.header.as_deref().unwrap_or_default(),
+            message.text.as_deref().unwrap_or_default(),
+        ))
+    })).await
+}
+
+/// 预取邮件命令
+/// 在一次 `UID FETCH` 中获取多封完整邮件并缓存，之后的 `get_email` 直接使用缓存（每封使用一次，5分钟内有效），
+/// 用于预先加载当前打开邮件前后的邮件。获取使用 `BODY.PEEK[]`，不会改变已读状态。
+/// 
+/// 每次最多预取10封、总大小10MB（按 `RFC822.SIZE`），超出的邮件跳过；返回实际预取的邮件ID
+/// 
+/// # 参数
+/// * `message_ids` - 邮件ID（UID）列表，按优先级排列
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn prefetch_emails(
+    app: tauri::AppHandle,
+    message_ids: Vec<String>,
+    folder: Option<String>,
+) -> Result<Vec<String>, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let account = load_session_info(&app)?.email;
+    let mut uids = Vec::new();
+    for message_id in &message_ids {
+        let uid = parse_message_id(message_id)?;
+        if !uids.contains(&uid) {
+            uids.push(uid);
+        }
+    }
+    uids.truncate(prefetch::MAX_PREFETCH_COUNT);
+    if uids.is_empty() {
+        return Ok(vec![]);
+    }
+    
+    let fetch_folder = folder.clone();
+    let emails = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .select(utf7::encode(&fetch_folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), fetch_folder, e))?;
+        
+        // 先获取大小，按优先级选择总大小不超过上限的邮件
+        let uid_set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let sizes: HashMap<u32, u64> = {
+            let messages = imap_session
+                .uid_fetch(&uid_set, "(UID RFC822.SIZE)")
+                .await
+                .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            let mut messages = std::pin::pin!(messages);
+            let mut sizes = HashMap::new();
+            while let Some(fetch_result) = messages.next().await {
+                let message = fetch_result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+                if let (Some(uid), Some(size)) = (message.uid, message.size) {
+                    sizes.insert(uid, u64::from(size));
+                }
+            }
+            sizes
+        };
+        
+        let mut total = 0;
+        let selected: Vec<u32> = uids
+            .into_iter()
+            .filter(|uid| {
+                // 服务器没有返回大小的邮件已不存在
+                let Some(&size) = sizes.get(uid) else {
+                    return false;
+                };
+                if total + size > prefetch::MAX_PREFETCH_BYTES {
+                    return false;
+                }
+                total += size;
+                true
+            })
+            .collect();
+        if selected.is_empty() {
+            return Ok(vec![]);
+        }
+        
+        let uid_set = selected.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let messages = imap_session
+            .uid_fetch(&uid_set, "(UID BODY.PEEK[] FLAGS INTERNALDATE)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        collect_emails(messages).await
+    })).await?;
+    
+    let mut prefetched = Vec::with_capacity(emails.len());
+    let mut entries = Vec::with_capacity(emails.len());
+    for email in emails {
+        let Some(uid) = email.uid() else {
+            continue;
+        };
+        prefetched.push(uid.to_string());
+        entries.push((prefetch::key(&account, &folder, uid), email));
+    }
+    app.state::<prefetch::PrefetchCache>().insert(entries).await;
+    
+    Ok(prefetched)
+}
+
+/// 按ID获取邮件的结果
+#[derive(Debug, Clone, Serialize)]
+struct FetchByIds {
+    /// 按请求顺序排列的邮件，格式与邮件列表相同
+    emails: Vec<Email>,
+    /// 服务器上已不存在的邮件ID
+    omitted: Vec<String>,
+}
+
+/// 按ID获取邮件命令
+/// 在一次 `UID FETCH` 中获取指定的邮件，用于只刷新界面中可见的几行而无需重新获取整个列表。
+/// 以只读方式打开文件夹，不会改变已读状态
+/// 
+/// # 参数
+/// * `ids` - 邮件ID（UID）列表
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+/// * `headers_only` - 为true时只获取信封（同 `fetch_email_envelopes`），否则获取完整邮件
+#[tauri::command]
+async fn fetch_by_ids(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+    folder: Option<String>,
+    headers_only: bool,
+) -> Result<FetchByIds, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let mut uids = Vec::new();
+    for id in &ids {
+        let uid = parse_message_id(id)?;
+        if !uids.contains(&uid) {
+            uids.push(uid);
+        }
+    }
+    if uids.is_empty() {
+        return Ok(FetchByIds {
+            emails: vec![],
+            omitted: vec![],
+        });
+    }
+    
+    let (query, build): (&'static str, fn(&Fetch) -> Option<Email>) = if headers_only {
+        ("(UID ENVELOPE FLAGS INTERNALDATE)", build_envelope)
+    } else {
+        ("(UID BODY.PEEK[] FLAGS INTERNALDATE)", build_email)
+    };
+    let fetch_folder = folder.clone();
+    let uid_set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let mut emails = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .examine(utf7::encode(&fetch_folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), fetch_folder, e))?;
+        let messages = imap_session
+            .uid_fetch(&uid_set, query)
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        collect_with(messages, build).await
+    })).await?;
+    
+    // FETCH响应按序号返回，按请求顺序重新排列
+    let position = |email: &Email| {
+        let uid = email.uid();
+        uids.iter().position(|&requested| Some(requested) == uid)
+    };
+    emails.retain(|email| position(email).is_some());
+    emails.sort_by_key(position);
+    tag_snoozed(&app, &folder, &mut emails);
+    
+    let omitted = uids
+        .iter()
+        .map(u32::to_string)
+        .filter(|id| !emails.iter().any(|email| email.id == *id))
+        .collect();
+    
+    Ok(FetchByIds { emails, omitted })
+}
+
+/// 获取邮件结构命令
+/// 返回MIME部分树（部分编号、类型、文件名、大小、传输编码），用于在不下载正文的情况下列出附件
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn get_structure(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<mime_structure::MessagePart, String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        fetch_structure(imap_session, &folder, &message_id).await
+    })).await
+}
+
+/// 分段下载附件时每次获取的字节数
+const DOWNLOAD_CHUNK_SIZE: u32 = 256 * 1024;
+
+/// 附件下载进度事件
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    #[serde(rename = "partId")]
+    part_id: String,
+    /// 已下载的字节数（传输编码后）
+    transferred: u64,
+    /// 部分总字节数（传输编码后）
+    total: u64,
+}
+
+/// 下载附件命令
+/// 通过 `BODY.PEEK[part_id]<offset.length>` 分段获取指定部分，每段完成后发送 `download-progress` 事件，
+/// 全部下载后解码传输编码写入文件，返回写入的字节数
+/// 
+/// 已下载的分段保存在 `<save_path>.<邮件ID>-<部分编号>.part` 中，下载中断后再次调用会从断点继续；
+/// 解码结果先写入临时文件再重命名，取消下载不会留下不完整的目标文件
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `part_id` - 部分编号，来自 `get_structure`
+/// * `save_path` - 保存路径
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn download_attachment(
+    app: tauri::AppHandle,
+    message_id: String,
+    part_id: String,
+    save_path: String,
+    folder: Option<String>,
+) -> Result<usize, String> {
+    // 邮件ID会写入分段文件名和FETCH命令，先确认是UID
+    let uid = parse_message_id(&message_id)?;
+    let folder = resolve_folder(&app, folder).await;
+    let section = mime_structure::parse_part_id(&part_id)?;
+    let partial_path = format!("{}.{}-{}.part", save_path, uid, part_id);
+    let emitter = app.clone();
+    
+    let (encoding, partial_path) = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let structure = fetch_structure(imap_session, &folder, &message_id).await?;
+        let part = mime_structure::find_part(&structure, &part_id)
+            .ok_or_else(|| format!("{}: {}", tr("邮件中没有该部分"), part_id))?;
+        let encoding = part.encoding.clone();
+        let total = part.size as u64;
+        
+        let mut partial = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&partial_path)
+            .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?;
+        let mut offset = partial
+            .metadata()
+            .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?
+            .len();
+        
+        let path = async_imap::imap_proto::SectionPath::Part(section, None);
+        while offset < total {
+            // PEEK不会把邮件标记为已读
+            let messages = imap_session
+                .uid_fetch(
+                    uid.to_string(),
+                    format!("(UID BODY.PEEK[{}]<{}.{}>)", part_id, offset, DOWNLOAD_CHUNK_SIZE),
+                )
+                .await
+                .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            
+            let mut chunk = Vec::new();
+            let mut messages = std::pin::pin!(messages);
+            while let Some(result) = messages.next().await {
+                let message = result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+                if let Some(data) = message.section(&path) {
+                    chunk.extend_from_slice(data);
+                }
+            }
+            if chunk.is_empty() {
+                // BODYSTRUCTURE中的大小只是参考，服务器没有更多数据即视为下载完成
+                break;
+            }
+            
+            partial
+                .write_all(&chunk)
+                .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?;
+            offset += chunk.len() as u64;
+            
+            let _ = emitter.emit("download-progress", DownloadProgress {
+                message_id: message_id.clone(),
+                part_id: part_id.clone(),
+                transferred: offset.min(total),
+                total,
+            });
+        }
+        
+        Ok((encoding, partial_path))
+    })).await?;
+    
+    let encoded = std::fs::read(&partial_path)
+        .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?;
+    let data = mime_structure::decode_part(&encoded, &encoding)?;
+    
+    let temp_path = format!("{}.tmp", save_path);
+    std::fs::write(&temp_path, &data)
+        .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?;
+    std::fs::rename(&temp_path, &save_path)
+        .map_err(|e| format!("{}: {}", tr("保存附件失败"), e))?;
+    let _ = std::fs::remove_file(&partial_path);
+    
+    Ok(data.len())
+}
+
+/// 归档邮件命令
+/// 下载原始邮件并压缩保存到本地，服务器上的邮件被删除后仍可通过 `read_archived` 读取
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn archive_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<ArchivedEmail, String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    let (raw, received_at) = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let uid = select_message(imap_session, &folder, &message_id).await?;
+        let messages = imap_session
+            .uid_fetch(uid.to_string(), "(UID BODY.PEEK[] INTERNALDATE)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        
+        let mut messages = std::pin::pin!(messages);
+        while let Some(result) = messages.next().await {
+            let message = result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            if let Some(body) = message.body() {
+                let received_at = message.internal_date().map(|dt| dt.timestamp_millis());
+                return Ok((body.to_vec(), received_at));
+            }
+        }
+        
+        Err(format!("{}: {}", tr("邮件不存在"), message_id))
+    })).await?;
+    
+    let id = uuid::Uuid::new_v4().to_string();
+    let email = parse_email(&id, &raw, received_at)
+        .ok_or_else(|| tr("归档邮件失败").to_string())?;
+    
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let dir = archive::archive_dir(&app_data_dir)?;
+    let path = archive::write_message(&dir, &id, &raw)?;
+    
+    let entry = ArchivedEmail {
+        id,
+        from: email.from,
+        subject: email.subject,
+        date: email.timestamp,
+        received_at,
+        path: path.to_string_lossy().into_owned(),
+    };
+    
+    let lock = app.state::<archive::ArchiveLock>();
+    let _guard = lock.0.lock().await;
+    let mut index = load_archive_index(&app)?;
+    index.push(entry.clone());
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    store.set("archive_index", serde_json::to_value(&index).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("{}: {}", tr("归档邮件失败"), e))?;
+    
+    Ok(entry)
+}
+
+/// 列出本地归档的邮件
+#[tauri::command]
+async fn list_archived(app: tauri::AppHandle) -> Result<Vec<ArchivedEmail>, String> {
+    load_archive_index(&app)
+}
+
+/// 读取归档邮件命令
+/// 解压后按邮件列表相同的方式解析
+/// 
+/// # 参数
+/// * `id` - 归档ID
+#[tauri::command]
+async fn read_archived(app: tauri::AppHandle, id: String) -> Result<Email, String> {
+    let entry = load_archive_index(&app)?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("{}: {}", tr("归档邮件不存在"), id))?;
+    
+    let raw = archive::read_message(std::path::Path::new(&entry.path))?;
+    let mut email = parse_email(&entry.id, &raw, entry.received_at)
+        .ok_or_else(|| tr("读取归档失败").to_string())?;
+    email.is_read = true;
+    
+    Ok(email)
+}
+
+/// 文件夹增量同步状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FolderSyncState {
+    /// 记录高水位时文件夹的UIDVALIDITY，变化后旧UID全部失效
+    #[serde(rename = "uidValidity")]
+    uid_validity: u32,
+    /// 已同步的最大UID
+    #[serde(rename = "lastUid")]
+    last_uid: u32,
+}
+
+/// 增量获取结果
+#[derive(Debug, Clone, Serialize)]
+struct NewMessagesResult {
+    emails: Vec<Email>,
+    #[serde(rename = "lastUid")]
+    last_uid: u32,
+    #[serde(rename = "uidValidity")]
+    uid_validity: u32,
+    /// 为true表示没有可用的高水位（首次同步或UIDVALIDITY变化），返回的是完整的最新列表
+    #[serde(rename = "fullSync")]
+    full_sync: bool,
+}
+
+/// 增量同步状态在存储中的键，按账户和文件夹区分
+fn sync_state_key(email: &str, folder: &str) -> String {
+    format!("{}/{}", email, folder)
+}
+
+/// 仅获取上次同步以来的新邮件
+/// 
+/// 每个文件夹在存储中记录已见过的最大UID，之后只通过 `UID SEARCH UID n:*` 获取新邮件。
+/// UIDVALIDITY变化时回退为完整获取并重置高水位。
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn fetch_new_since(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<NewMessagesResult, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let session = load_session_info(&app)?;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    let key = sync_state_key(&session.email, &folder);
+    let mut sync_states: serde_json::Map<String, serde_json::Value> = store
+        .get("sync_state")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    let previous: Option<FolderSyncState> = sync_states
+        .get(&key)
+        .and_then(|value| serde_json::from_value(value.clone()).ok());
+    
+    let contacts_folder = folder.clone();
+    let result = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let mailbox = imap_session
+            .select(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        let uid_validity = mailbox.uid_validity.unwrap_or(0);
+        // UIDNEXT - 1 是当前可能存在的最大UID
+        let uid_next_high = mailbox.uid_next.map(|next| next.saturating_sub(1));
+        
+        let last_uid = match previous {
+            Some(state) if state.uid_validity == uid_validity => state.last_uid,
+            _ => {
+                // 首次同步或UIDVALIDITY变化，回退为完整获取
+                let emails = fetch_emails_from_imap(imap_session, &folder, SortKey::Date, Order::NewestFirst).await?;
+                let max_fetched = emails
+                    .iter()
+                    .filter_map(Email::uid)
+                    .max()
+                    .unwrap_or(0);
+                return Ok(NewMessagesResult {
+                    emails,
+                    last_uid: uid_next_high.unwrap_or(0).max(max_fetched),
+                    uid_validity,
+                    full_sync: true,
+                });
+            }
+        };
+        
+        // `n:*` 在没有新邮件时仍会返回最大UID的邮件，需要再过滤一次
+        let new_uids: Vec<u32> = imap_session
+            .uid_search(format!("UID {}:*", last_uid.saturating_add(1)))
+            .await
+            .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?
+            .into_iter()
+            .filter(|uid| *uid > last_uid)
+            .collect();
+        
+        if new_uids.is_empty() {
+            return Ok(NewMessagesResult {
+                emails: vec![],
+                last_uid,
+                uid_validity,
+                full_sync: false,
+            });
+        }
+        
+        let uid_set = new_uids
+            .iter()
+            .map(|uid| uid.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let messages = imap_session
+            .uid_fetch(&uid_set, "(UID BODY.PEEK[] FLAGS INTERNALDATE)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        let emails = collect_emails(messages).await?;
+        
+        Ok(NewMessagesResult {
+            emails,
+            last_uid: new_uids.into_iter().max().unwrap_or(last_uid),
+            uid_validity,
+            full_sync: false,
+        })
+    })).await?;
+    
+    // 保存新的高水位
+    sync_states.insert(key, serde_json::to_value(FolderSyncState {
+        uid_validity: result.uid_validity,
+        last_uid: result.last_uid,
+    }).unwrap());
+    store.set("sync_state", serde_json::Value::Object(sync_states));
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save sync state: {}", e))?;
+    
+    if let Err(e) = contacts::record(&app, &session.email, &contacts_folder, &result.emails).await {
+        log::warn!("更新联系人索引失败: {}", e);
+    }
+    
+    Ok(result)
+}
+
+/// 获取联系人建议命令
+/// 
+/// 联系人从获取过的邮件的发件人、收件人和抄送中收集，按往来次数和最近往来时间排序
+/// 
+/// # 参数
+/// * `prefix` - 地址或显示名称的前缀，不区分大小写
+/// * `limit` - 最多返回的数量，默认10，最大50
+#[tauri::command]
+async fn contact_suggestions(
+    app: tauri::AppHandle,
+    prefix: String,
+    limit: Option<usize>,
+) -> Result<Vec<contacts::Contact>, String> {
+    let session = load_session_info(&app)?;
+    let limit = limit.unwrap_or(10).min(contacts::MAX_SUGGESTIONS);
+    Ok(contacts::suggestions(&app, &session.email, &prefix, limit))
+}
+
+/// 邮件标记状态
+#[derive(Debug, Clone, Serialize)]
+struct MessageFlags {
+    seen: bool,
+    flagged: bool,
+    answered: bool,
+    deleted: bool,
+    draft: bool,
+}
+
+impl MessageFlags {
+    fn from_fetch(message: &Fetch) -> Self {
+        let mut flags = Self {
+            seen: false,
+            flagged: false,
+            answered: false,
+            deleted: false,
+            draft: false,
+        };
+        for flag in message.flags() {
+            match flag {
+                Flag::Seen => flags.seen = true,
+                Flag::Flagged => flags.flagged = true,
+                Flag::Answered => flags.answered = true,
+                Flag::Deleted => flags.deleted = true,
+                Flag::Draft => flags.draft = true,
+                _ => {}
+            }
+        }
+        flags
+    }
+}
+
+/// 文件夹统计信息
+#[derive(Debug, Clone, Serialize)]
+struct FolderStats {
+    folder: String,
+    total: u32,
+    unread: u32,
+    recent: u32,
+}
+
+/// 列出所有可选择的文件夹
+/// 跳过带 `\Noselect` 属性的层级节点
+async fn list_selectable_folders(imap_session: &mut ImapSession) -> Result<Vec<String>, String> {
+    let names = imap_session
+        .list(Some(""), Some("*"))
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取文件夹列表失败"), e))?;
+    
+    let mut names = std::pin::pin!(names);
+    let mut folders = Vec::new();
+    while let Some(result) = names.next().await {
+        let name = result.map_err(|e| format!("{}: {}", tr("获取文件夹列表失败"), e))?;
+        if name.attributes().contains(&NameAttribute::NoSelect) {
+            continue;
+        }
+        folders.push(utf7::decode(name.name()));
+    }
+    
+    Ok(folders)
+}
+
+/// 特殊用途文件夹缓存（RFC 6154），按账户缓存用途到实际文件夹名称的映射
+#[derive(Default)]
+struct SpecialFoldersCache(async_std::sync::Mutex<HashMap<String, HashMap<String, String>>>);
+
+/// 服务器未声明特殊用途时按常见名称匹配的候选文件夹
+const SPECIAL_FOLDER_FALLBACKS: [(&str, &[&str]); 5] = [
+    ("\\Sent", &["Sent", "Sent Messages", "Sent Items", "已发送"]),
+    ("\\Drafts", &["Drafts", "草稿箱"]),
+    ("\\Trash", &["Trash", "Deleted Messages", "Deleted Items", "已删除"]),
+    ("\\Junk", &["Junk", "Spam", "垃圾邮件"]),
+    ("\\Archive", &["Archive", "归档"]),
+];
+
+/// 将LIST响应中的属性映射为特殊用途名称
+fn special_use_of(attribute: &NameAttribute) -> Option<&'static str> {
+    match attribute {
+        NameAttribute::Sent => Some("\\Sent"),
+        NameAttribute::Drafts => Some("\\Drafts"),
+        NameAttribute::Trash => Some("\\Trash"),
+        NameAttribute::Junk => Some("\\Junk"),
+        NameAttribute::Archive => Some("\\Archive"),
+        NameAttribute::All => Some("\\All"),
+        // 部分服务器（如Gmail的XLIST）以非标准的 `\Inbox` 属性标记收件箱
+        NameAttribute::Extension(name) if name.eq_ignore_ascii_case("\\Inbox") => Some("\\Inbox"),
+        _ => None,
+    }
+}
+
+/// 执行async-imap未封装的命令，逐条处理响应直到对应的完成响应
+/// 
+/// # 参数
+/// * `imap_session` - 已登录的IMAP会话
+/// * `command` - 完整的命令（不含标签）
+/// * `error` - 失败时错误信息的前缀（消息键）
+/// * `handle` - 处理每条非完成响应
+async fn run_raw_command<T, F>(
+    imap_session: &mut async_imap::Session<T>,
+    command: &str,
+    error: &'static str,
+    mut handle: F,
+) -> Result<(), String>
+where
+    T: ImapTransport,
+    F: FnMut(&Response) + Send,
+{
+    let tag = imap_session
+        .run_command(command)
+        .await
+        .map_err(|e| format!("{}: {}", tr(error), e))?;
+    
+    while let Some(response) = imap_session.read_response().await {
+        let response = response.map_err(|e| format!("{}: {}", tr(error), e))?;
+        match response.parsed() {
+            Response::Done { tag: done, status, information, .. } if *done == tag => {
+                if *status != Status::Ok {
+                    return Err(format!("{}: {}", tr(error), information.as_deref().unwrap_or_default()));
+                }
+                return Ok(());
+            }
+            parsed => handle(parsed),
+        }
+    }
+    
+    Err(format!("{}: {}", tr(error), tr("连接意外中断")))
+}
+
+/// 执行 `LIST (SPECIAL-USE) "" "*"`，返回声明了特殊用途的文件夹
+/// async-imap未封装LIST选择选项，这里直接发送命令并读取响应
+async fn list_special_use(imap_session: &mut ImapSession) -> Result<HashMap<String, String>, String> {
+    let mut found = HashMap::new();
+    run_raw_command(imap_session, "LIST (SPECIAL-USE) \"\" \"*\"", "获取文件夹列表失败", |response| {
+        if let Response::MailboxData(MailboxDatum::List { name_attributes, name, .. }) = response {
+            for role in name_attributes.iter().filter_map(special_use_of) {
+                found.entry(role.to_string()).or_insert_with(|| utf7::decode(name));
+            }
+        }
+    }).await?;
+    
+    Ok(found)
+}
+
+/// 获取当前账户的特殊用途文件夹，优先读取缓存
+/// 
+/// 服务器支持SPECIAL-USE时以其声明为准，缺失的用途再按常见名称在现有文件夹中匹配；
+/// 都找不到的用途不会出现在结果中
+async fn special_folders(app: &tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let account = load_session_info(app)?.email;
+    let cache = app.state::<SpecialFoldersCache>();
+    // 识别期间持有锁，同时需要收件箱名称的命令等待识别结果
+    let mut cache = cache.0.lock().await;
+    if let Some(folders) = cache.get(&account) {
+        return Ok(folders.clone());
+    }
+    
+    let folders = with_imap_session(app, |imap_session| Box::pin(async move {
+        let capabilities = imap_session
+            .capabilities()
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取文件夹列表失败"), e))?;
+        let mut folders = if capabilities.has_str("SPECIAL-USE") {
+            list_special_use(imap_session).await?
+        } else {
+            HashMap::new()
+        };
+        
+        let existing = list_selectable_folders(imap_session).await?;
+        for (role, candidates) in SPECIAL_FOLDER_FALLBACKS {
+            if folders.contains_key(role) {
+                continue;
+            }
+            let matched = candidates.iter().find_map(|candidate| {
+                existing.iter().find(|name| name.eq_ignore_ascii_case(candidate))
+            });
+            if let Some(name) = matched {
+                folders.insert(role.to_string(), name.clone());
+            }
+        }
+        
+        // 服务器没有声明收件箱时：存在INBOX就使用INBOX，否则退而使用包含全部邮件的 `\All`
+        if !folders.contains_key("\\Inbox") {
+            let inbox = existing
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(DEFAULT_INBOX))
+                .cloned()
+                .or_else(|| folders.get("\\All").cloned());
+            if let Some(inbox) = inbox {
+                folders.insert("\\Inbox".to_string(), inbox);
+            }
+        }
+        
+        Ok(folders)
+    })).await?;
+    
+    cache.insert(account, folders.clone());
+    Ok(folders)
+}
+
+/// 获取特殊用途文件夹命令
+/// 返回 `\Inbox`、`\Sent`、`\Drafts`、`\Trash`、`\Junk`、`\Archive`、`\All` 到实际文件夹名称的映射，
+/// 结果按账户缓存
+#[tauri::command]
+async fn get_special_folders(app: tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    special_folders(&app).await
+}
+
+/// 获取所有文件夹的邮件总数、未读数和新邮件数
+/// 一次调用即可渲染带未读角标的文件夹侧边栏
+/// 
+/// 对每个文件夹执行 `STATUS (MESSAGES UNSEEN RECENT)`，不会改变邮件的已读或Recent状态
+#[tauri::command]
+async fn folder_stats(app: tauri::AppHandle) -> Result<Vec<FolderStats>, String> {
+    with_imap_session(&app, |imap_session| Box::pin(async move {
+        let folders = list_selectable_folders(imap_session).await?;
+        
+        let mut stats = Vec::with_capacity(folders.len());
+        for folder in folders {
+            let status = imap_session
+                .status(utf7::encode(&folder), "(MESSAGES UNSEEN RECENT)")
+                .await
+                .map_err(|e| format!("{} {}: {}", tr("获取文件夹状态失败"), folder, e))?;
+            stats.push(FolderStats {
+                folder,
+                total: status.exists,
+                unread: status.unseen.unwrap_or(0),
+                recent: status.recent,
+            });
+        }
+        
+        Ok(stats)
+    })).await
+}
+
+/// 统计所有文件夹的未读数，更新缓存以及托盘和角标上的总数
+/// 
+/// 对每个文件夹只执行 `STATUS (UNSEEN)`，比 `folder_stats` 更轻量，适合后台定时调用
+async fn refresh_unread_counts(app: &tauri::AppHandle) -> Result<unread::UnreadCounts, String> {
+    let session = load_session_info(app)?;
+    
+    let folders = with_imap_session(app, |imap_session| Box::pin(async move {
+        let folders = list_selectable_folders(imap_session).await?;
+        
+        let mut unread = HashMap::with_capacity(folders.len());
+        for folder in folders {
+            let status = imap_session
+                .status(utf7::encode(&folder), "(UNSEEN)")
+                .await
+                .map_err(|e| format!("{} {}: {}", tr("获取文件夹状态失败"), folder, e))?;
+            unread.insert(folder, status.unseen.unwrap_or(0));
+        }
+        
+        Ok(unread)
+    })).await?;
+    
+    let counts = unread::UnreadCounts::new(folders);
+    app.state::<unread::UnreadCountsCache>().set(&session.email, counts.clone()).await;
+    unread::show_total(app, counts.total);
+    
+    Ok(counts)
+}
+
+/// 获取所有文件夹的未读数和总未读数
+/// 默认返回后台刷新缓存的结果，尚无缓存时立即统计
+/// 
+/// # 参数
+/// * `refresh` - 为true时忽略缓存重新统计
+#[tauri::command]
+async fn all_unread_counts(app: tauri::AppHandle, refresh: Option<bool>) -> Result<unread::UnreadCounts, String> {
+    if !refresh.unwrap_or(false) {
+        let session = load_session_info(&app)?;
+        if let Some(counts) = app.state::<unread::UnreadCountsCache>().get(&session.email).await {
+            return Ok(counts);
+        }
+    }
+    
+    refresh_unread_counts(&app).await
+}
+
+/// 跨文件夹搜索时每个文件夹最多返回的邮件数（最新的）
+const MAX_RESULTS_PER_FOLDER: usize = 100;
+/// 同时搜索的文件夹数
+const FOLDER_SEARCH_CONCURRENCY: usize = 3;
+
+/// 跨文件夹搜索的条件
+#[derive(Debug, Clone)]
+enum SearchCriteria {
+    /// 只含ASCII的搜索条件，原样发送
+    Ascii(String),
+    /// 按 `TEXT` 搜索的非ASCII搜索词
+    /// 部分服务器不接受带引号字符串中的8位字符，以 `CHARSET UTF-8 TEXT {n}` 字面量发送
+    Utf8Text(String),
+}
+
+/// 执行 `UID SEARCH CHARSET UTF-8 TEXT {n}`，搜索词作为同步字面量（RFC 3501 4.3）发送
+/// async-imap的 `uid_search` 只能发送整行命令，这里在服务器给出继续请求后再发送字面量内容
+async fn uid_search_utf8_text<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    term: &str,
+) -> Result<Vec<u32>, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", tr("查找邮件失败"), e);
+    
+    let tag = imap_session
+        .run_command(format!("UID SEARCH CHARSET UTF-8 TEXT {{{}}}", term.len()))
+        .await
+        .map_err(|e| error(&e))?;
+    
+    let mut uids = Vec::new();
+    let mut sent = false;
+    while let Some(response) = imap_session.read_response().await {
+        let response = response.map_err(|e| error(&e))?;
+        match response.parsed() {
+            Response::Continue { .. } if !sent => {
+                let stream = imap_session.as_mut();
+                stream.write_all(term.as_bytes()).await.map_err(|e| error(&e))?;
+                stream.write_all(b"\r\n").await.map_err(|e| error(&e))?;
+                stream.flush().await.map_err(|e| error(&e))?;
+                sent = true;
+            }
+            Response::MailboxData(MailboxDatum::Search(found)) => uids.extend(found),
+            Response::Done { tag: done, status, information, .. } if *done == tag => {
+                if *status != Status::Ok {
+                    return Err(error(&information.as_deref().unwrap_or_default()));
+                }
+                return Ok(uids);
+            }
+            _ => {}
+        }
+    }
+    
+    Err(error(&tr("连接意外中断")))
+}
+
+/// 在文件夹中执行 `UID SEARCH` 并获取最新匹配邮件的信封，每封邮件带 `folder` 字段
+async fn search_folder<T: ImapTransport>(
+    imap_session: &mut async_imap::Session<T>,
+    folder: &str,
+    criteria: &SearchCriteria,
+) -> Result<Vec<Email>, String> {
+    // 只读打开，不影响邮件的Recent状态
+    imap_session
+        .examine(utf7::encode(folder))
+        .await
+        .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+    
+    let mut uids: Vec<u32> = match criteria {
+        SearchCriteria::Ascii(criteria) => imap_session
+            .uid_search(criteria)
+            .await
+            .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?
+            .into_iter()
+            .collect(),
+        SearchCriteria::Utf8Text(term) => uid_search_utf8_text(imap_session, term).await?,
+    };
+    if uids.is_empty() {
+        return Ok(vec![]);
+    }
+    uids.sort_unstable_by(|a, b| b.cmp(a));
+    uids.truncate(MAX_RESULTS_PER_FOLDER);
+    
+    let uid_set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let messages = imap_session
+        .uid_fetch(&uid_set, "(UID ENVELOPE FLAGS INTERNALDATE)")
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    let mut emails = collect_with(messages, build_envelope).await?;
+    for email in &mut emails {
+        email.folder = Some(folder.to_string());
+    }
+    
+    Ok(emails)
+}
+
+/// 在所有可选择的文件夹（跳过 `\Noselect`）中执行同一搜索，返回每个文件夹的结果
+/// 最多同时搜索3个文件夹，每个文件夹使用连接池中的一个连接；单个文件夹失败不影响其他文件夹
+async fn search_each_folder(
+    app: &tauri::AppHandle,
+    criteria: SearchCriteria,
+) -> Result<Vec<(String, Result<Vec<Email>, String>)>, String> {
+    let folders = with_imap_session(app, |imap_session| Box::pin(async move {
+        list_selectable_folders(imap_session).await
+    })).await?;
+    
+    let mut results = Vec::with_capacity(folders.len());
+    for chunk in folders.chunks(FOLDER_SEARCH_CONCURRENCY) {
+        let tasks: Vec<_> = chunk
+            .iter()
+            .cloned()
+            .map(|folder| {
+                let app = app.clone();
+                let criteria = criteria.clone();
+                let name = folder.clone();
+                let task = tauri::async_runtime::spawn(async move {
+                    with_imap_session(&app, move |imap_session| Box::pin(async move {
+                        search_folder(imap_session, &folder, &criteria).await
+                    })).await
+                });
+                (name, task)
+            })
+            .collect();
+        
+        for (folder, task) in tasks {
+            let result = task.await.unwrap_or_else(|e| Err(e.to_string()));
+            results.push((folder, result));
+        }
+    }
+    
+    Ok(results)
+}
+
+/// 列出所有文件夹中带星标的邮件命令
+/// 对每个可选择的文件夹（跳过 `\Noselect`）执行 `UID SEARCH FLAGGED` 并获取信封，
+/// 合并后按日期从新到旧排列，每封邮件带 `folder` 字段表示所在文件夹
+/// 
+/// 每个文件夹最多返回最新的100封；最多同时搜索3个文件夹，每个文件夹使用连接池中的一个连接。
+/// 单个文件夹搜索失败时记录日志并跳过
+#[tauri::command]
+async fn list_flagged(app: tauri::AppHandle) -> Result<Vec<Email>, String> {
+    let mut emails = Vec::new();
+    for (folder, result) in search_each_folder(&app, SearchCriteria::Ascii("FLAGGED".to_string())).await? {
+        match result {
+            Ok(found) => emails.extend(found),
+            Err(e) => log::warn!("搜索文件夹 {} 的星标邮件失败: {}", folder, e),
+        }
+    }
+    
+    SortKey::Date.sort_locally(&mut emails);
+    Ok(emails)
+}
+
+/// 单个文件夹的搜索错误
+#[derive(Debug, Clone, Serialize)]
+struct FolderError {
+    folder: String,
+    error: String,
+}
+
+/// 跨文件夹搜索结果
+#[derive(Debug, Clone, Serialize)]
+struct MultiFolderSearch {
+    /// 所有文件夹的匹配邮件，按日期从新到旧排列，每封邮件带 `folder` 字段
+    emails: Vec<Email>,
+    /// 搜索失败的文件夹，其余文件夹的结果仍然返回
+    errors: Vec<FolderError>,
+}
+
+/// 将搜索词转换为IMAP带引号的字符串
+/// 拒绝换行，转义反斜杠和引号
+fn quote_search_term(term: &str) -> Result<String, String> {
+    if term.contains(['\r', '\n']) {
+        return Err(format!("{}: {}", tr("搜索词无效"), term));
+    }
+    Ok(format!("\"{}\"", term.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+/// 按 `TEXT` 搜索 `term` 的条件，非ASCII的搜索词以字面量发送
+fn text_search_criteria(term: &str) -> Result<SearchCriteria, String> {
+    let quoted = quote_search_term(term)?;
+    if term.is_ascii() {
+        Ok(SearchCriteria::Ascii(format!("TEXT {}", quoted)))
+    } else {
+        Ok(SearchCriteria::Utf8Text(term.to_string()))
+    }
+}
+
+/// 跨文件夹搜索命令
+/// 在所有可选择的文件夹中按 `TEXT`（头部和正文）搜索，合并结果并按日期从新到旧排列。
+/// 每个文件夹最多返回最新的100封，单个文件夹失败时在 `errors` 中报告而不中止整个搜索
+/// 
+/// # 参数
+/// * `query` - 搜索词
+#[tauri::command]
+async fn search_all_folders(app: tauri::AppHandle, query: String) -> Result<MultiFolderSearch, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(MultiFolderSearch {
+            emails: vec![],
+            errors: vec![],
+        });
+    }
+    
+    let criteria = text_search_criteria(query)?;
+    
+    let mut emails = Vec::new();
+    let mut errors = Vec::new();
+    for (folder, result) in search_each_folder(&app, criteria).await? {
+        match result {
+            Ok(found) => emails.extend(found),
+            Err(error) => errors.push(FolderError { folder, error }),
+        }
+    }
+    
+    SortKey::Date.sort_locally(&mut emails);
+    Ok(MultiFolderSearch { emails, errors })
+}
+
+/// 压缩文件夹命令
+/// 对文件夹执行EXPUNGE，永久删除所有带 `\Deleted` 标记的邮件，返回删除的邮件数量
+/// 
+/// 删除标记与EXPUNGE分为两步，在EXPUNGE之前清除 `\Deleted` 即可撤销删除。
+/// 邮件ID为UID，EXPUNGE不会改变其余邮件的UID，已加载的列表可继续使用；
+/// 被删除邮件的UID不会被重用，对其再次操作会因找不到邮件而失败。
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn expunge_folder(app: tauri::AppHandle, folder: Option<String>) -> Result<u32, String> {
+    let folder = resolve_folder(&app, folder).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .select(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        
+        let expunged = imap_session
+            .expunge()
+            .await
+            .map_err(|e| format!("{}: {}", tr("压缩文件夹失败"), e))?;
+        
+        // 每封被删除的邮件对应一条EXPUNGE响应
+        let mut count = 0;
+        let mut expunged = std::pin::pin!(expunged);
+        while let Some(result) = expunged.next().await {
+            result.map_err(|e| format!("{}: {}", tr("压缩文件夹失败"), e))?;
+            count += 1;
+        }
+        
+        Ok(count)
+    })).await
+}
+
+/// 已发送邮件保留期限检查间隔
+const SENT_RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+/// 删除已发送文件夹中超过保留天数的邮件，返回删除的邮件数量
+/// 
+/// 只处理识别为 `\Sent` 的文件夹，未设置 `sentRetentionDays` 或找不到已发送文件夹时不做任何操作。
+/// 服务器不支持UIDPLUS时只设置删除标记：普通EXPUNGE会一并删除用户自己标记的其他邮件
+async fn purge_expired_sent(app: &tauri::AppHandle) -> Result<u32, String> {
+    let Some(days) = current_preferences(app).sent_retention_days.filter(|&days| days > 0) else {
+        return Ok(0);
+    };
+    let Some(sent_folder) = special_folders(app).await?.remove("\\Sent") else {
+        return Ok(0);
+    };
+    
+    // SEARCH BEFORE按INTERNALDATE的日期比较，不含时间；
+    // 截止时间超出日期范围或早于1970年时不会有邮件过期
+    let Some(cutoff) = chrono::Duration::try_days(i64::from(days))
+        .and_then(|retention| chrono::Utc::now().checked_sub_signed(retention))
+        .filter(|cutoff| cutoff.timestamp() > 0)
+    else {
+        log::warn!("已发送邮件保留天数 {} 超出日期范围，不删除邮件", days);
+        return Ok(0);
+    };
+    let before = cutoff.format("%d-%b-%Y").to_string();
+    
+    with_imap_session(app, move |imap_session| Box::pin(async move {
+        imap_session
+            .select(utf7::encode(&sent_folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), sent_folder, e))?;
+        
+        let mut uids: Vec<u32> = imap_session
+            .uid_search(format!("BEFORE {} UNDELETED", before))
+            .await
+            .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?
+            .into_iter()
+            .collect();
+        if uids.is_empty() {
+            return Ok(0);
+        }
+        uids.sort_unstable();
+        let uid_set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        
+        {
+            let updates = imap_session
+                .uid_store(&uid_set, "+FLAGS.SILENT (\\Deleted)")
+                .await
+                .map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+            let mut updates = std::pin::pin!(updates);
+            while let Some(result) = updates.next().await {
+                result.map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+            }
+        }
+        
+        expunge_uid_set(imap_session, &uid_set).await?;
+        Ok(uids.len() as u32)
+    })).await
+}
+
+/// 执行已发送邮件保留期限命令
+/// 立即删除已发送文件夹中超过 `sentRetentionDays` 天的邮件，返回删除的数量；未启用时返回0
+#[tauri::command]
+async fn apply_sent_retention(app: tauri::AppHandle) -> Result<u32, String> {
+    purge_expired_sent(&app).await
+}
+
+/// 地址校验结果
+#[derive(Debug, Clone, Serialize)]
+struct AddressValidation {
+    valid: bool,
+    /// 无效原因，有效时为None
+    reason: Option<String>,
+}
+
+impl AddressValidation {
+    fn invalid(reason: &str) -> Self {
+        Self {
+            valid: false,
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// 检查地址语法，2925.com地址额外检查用户名字符
+/// 有问题时返回待翻译的原因
+fn address_syntax_problem(address: &str) -> Option<&'static str> {
+    if !smtp::is_valid_address(address) {
+        return Some("邮箱地址格式无效");
+    }
+    let (local, domain) = address.rsplit_once('@').unwrap_or_default();
+    let local_ok = local
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if domain.eq_ignore_ascii_case("2925.com") && !local_ok {
+        return Some("2925邮箱用户名只能包含字母、数字、下划线、点和短横线");
+    }
+    None
+}
+
+/// 查询域名能否接收邮件
+/// 优先查询MX记录；域名存在但没有MX记录时按隐式MX（RFC 5321 5.1）查询A/AAAA记录。
+/// 只有一条空MX（RFC 7505）或域名不存在时返回false；网络不可用、超时等解析错误返回错误，
+/// 避免把查询失败误报为地址无效
+async fn domain_accepts_mail(domain: &str) -> Result<bool, String> {
+    use async_std_resolver::proto::op::ResponseCode;
+    use hickory_resolver::error::ResolveErrorKind;
+    
+    let lookup_failed = |e: async_std_resolver::ResolveError| format!("{}: {}", tr("域名解析失败"), e);
+    let resolver = async_std_resolver::resolver_from_system_conf()
+        .await
+        .map_err(lookup_failed)?;
+    let name = format!("{}.", domain);
+    
+    match resolver.mx_lookup(name.clone()).await {
+        Ok(records) => {
+            if records.iter().next().is_some() {
+                return Ok(!records.iter().all(|mx| mx.exchange().is_root()));
+            }
+        }
+        Err(e) => match e.kind() {
+            ResolveErrorKind::NoRecordsFound { response_code: ResponseCode::NXDomain, .. } => return Ok(false),
+            ResolveErrorKind::NoRecordsFound { .. } => {}
+            _ => return Err(lookup_failed(e)),
+        },
+    }
+    
+    match resolver.lookup_ip(name).await {
+        Ok(ips) => Ok(ips.iter().next().is_some()),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(false),
+        Err(e) => Err(lookup_failed(e)),
+    }
+}
+
+/// 校验邮箱地址
+/// 检查地址语法和域名格式；2925.com地址额外检查用户名只含2925允许的字符，
+/// 其他域名可选查询MX记录（没有MX时查询A/AAAA记录）确认域名能接收邮件
+/// 
+/// # 参数
+/// * `address` - 邮箱地址
+/// * `check_mx` - 为true时对非2925.com域名查询MX记录，默认不查询；解析出错时返回错误
+#[tauri::command]
+async fn validate_address(
+    address: String,
+    check_mx: Option<bool>,
+) -> Result<AddressValidation, String> {
+    let address = address.trim();
+    if let Some(reason) = address_syntax_problem(address) {
+        return Ok(AddressValidation::invalid(tr(reason)));
+    }
+    
+    let (_, domain) = address.rsplit_once('@').unwrap_or_default();
+    if !domain.eq_ignore_ascii_case("2925.com")
+        && check_mx.unwrap_or(false)
+        && !domain_accepts_mail(domain).await?
+    {
+        return Ok(AddressValidation::invalid(tr("该域名没有邮件服务器")));
+    }
+    
+    Ok(AddressValidation {
+        valid: true,
+        reason: None,
+    })
+}
+
+/// 读取已保存的偏好设置，未保存或数据无效时使用默认值
+fn current_preferences(app: &tauri::AppHandle) -> UserPreferences {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("preferences"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// 发送结果
+#[derive(Debug, Clone, Serialize)]
+struct SendOutcome {
+    /// 邮件的Message-ID
+    #[serde(rename = "messageId")]
+    message_id: String,
+    /// 无法连接SMTP服务器时邮件进入发件箱，此时为发件箱ID，之后通过 `mail-sent` / `mail-send-failed` 事件通知结果
+    #[serde(rename = "queuedId")]
+    queued_id: Option<String>,
+}
+
+/// 发送邮件命令
+/// 通过SMTP以纯文本发送邮件；无法连接服务器时放入发件箱，网络恢复后自动重试
+/// 
+/// # 参数
+/// * `to` - 收件人地址，多个地址以逗号分隔
+/// * `subject` - 邮件主题
+/// * `body` - 邮件正文
+/// * `from_alias` - 可选的发件地址（如子邮箱），此时Sender头部为登录账户
+/// * `idempotency_key` - 可选的幂等键，相同的键在一段时间内只发送一次，重复调用直接返回之前的结果
+/// * `include_signature` - 是否附加偏好设置中的签名，默认附加
+#[tauri::command]
+async fn send_email(
+    app: tauri::AppHandle,
+    to: String,
+    subject: String,
+    body: String,
+    from_alias: Option<String>,
+    idempotency_key: Option<String>,
+    include_signature: Option<bool>,
+) -> Result<SendOutcome, String> {
+    submit_email(&app, outbox::OutgoingRequest {
+        to,
+        subject,
+        body,
+        from_alias,
+        attachment_paths: Vec::new(),
+        idempotency_key,
+        include_signature: include_signature.unwrap_or(true),
+    }).await
+}
+
+/// 发送带附件的邮件
+/// 所有附件在连接SMTP之前读取并校验大小，任一文件缺失时不会发送；
+/// 无法连接服务器时与 `send_email` 一样放入发件箱，重试时重新读取附件
+/// 
+/// # 参数
+/// * `to` - 收件人地址，多个地址以逗号分隔
+/// * `subject` - 邮件主题
+/// * `body` - 邮件正文
+/// * `attachment_paths` - 附件文件路径
+/// * `from_alias` - 可选的发件地址（如子邮箱）
+/// * `idempotency_key` - 可选的幂等键，见 `send_email`
+/// * `include_signature` - 是否附加偏好设置中的签名，默认附加
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn send_email_with_attachments(
+    app: tauri::AppHandle,
+    to: String,
+    subject: String,
+    body: String,
+    attachment_paths: Vec<String>,
+    from_alias: Option<String>,
+    idempotency_key: Option<String>,
+    include_signature: Option<bool>,
+) -> Result<SendOutcome, String> {
+    submit_email(&app, outbox::OutgoingRequest {
+        to,
+        subject,
+        body,
+        from_alias,
+        attachment_paths,
+        idempotency_key,
+        include_signature: include_signature.unwrap_or(true),
+    }).await
+}
+
+/// 发送邮件，无法连接SMTP服务器时放入发件箱
+async fn submit_email(
+    app: &tauri::AppHandle,
+    mut request: outbox::OutgoingRequest,
+) -> Result<SendOutcome, String> {
+    // 没有幂等键时生成一个，放入发件箱后重试使用相同的Message-ID
+    if request.idempotency_key.as_deref().is_none_or(|key| key.trim().is_empty()) {
+        request.idempotency_key = Some(uuid::Uuid::new_v4().to_string());
+    }
+    
+    match deliver_email(app, &request).await {
+        Ok(message_id) => Ok(SendOutcome {
+            message_id,
+            queued_id: None,
+        }),
+        Err(DeliveryError::Unreachable { message_id, reason }) => {
+            let account = load_session_info(app)?.email;
+            let queued = outbox::enqueue(app, &account, request, reason).await?;
+            Ok(SendOutcome {
+                message_id,
+                queued_id: Some(queued.id),
+            })
+        }
+        Err(DeliveryError::Transient(e) | DeliveryError::Rejected(e)) => Err(e),
+    }
+}
+
+/// 读取附件并校验总大小
+fn load_attachments(app: &tauri::AppHandle, paths: &[String]) -> Result<Vec<smtp::Attachment>, String> {
+    let attachments = paths
+        .iter()
+        .map(|path| smtp::Attachment::from_path(std::path::Path::new(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let limit_mb = current_preferences(app).attachment_limit_mb;
+    let total: u64 = attachments.iter().map(|a| a.data.len() as u64).sum();
+    if total > limit_mb.saturating_mul(1024 * 1024) {
+        return Err(format!(
+            "{}: {:.1} MB > {} MB",
+            tr("附件总大小超过限制"),
+            total as f64 / (1024.0 * 1024.0),
+            limit_mb
+        ));
+    }
+    
+    Ok(attachments)
+}
+
+/// 幂等键的有效期，期间相同的键不会重复发送
+const IDEMPOTENCY_WINDOW: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// 幂等键对应的发送状态
+enum SendRecord {
+    /// 正在发送
+    Pending,
+    /// 已发送成功，记录Message-ID和发送时间
+    Sent(String, std::time::Instant),
+    /// 登录之后发送失败，服务器可能已经接收了邮件，记录Message-ID和失败时间
+    Ambiguous(String, std::time::Instant),
+}
+
+/// 最近使用过的幂等键，键为 `账户/幂等键`
+#[derive(Default)]
+struct SentKeys(async_std::sync::Mutex<HashMap<String, SendRecord>>);
+
+/// 投递失败的原因
+enum DeliveryError {
+    /// 无法连接SMTP服务器，邮件肯定没有发出，可以稍后重试
+    Unreachable { message_id: String, reason: String },
+    /// 读取凭据或附件失败、超时、连接中断、服务器暂时拒绝（4xx）等，稍后重试可能成功
+    Transient(String),
+    /// 头部或收件人无效、邮件过大、服务器永久拒绝（5xx），重试也不会成功
+    Rejected(String),
+}
+
+impl From<String> for DeliveryError {
+    fn from(error: String) -> Self {
+        DeliveryError::Transient(error)
+    }
+}
+
+impl From<AppError> for DeliveryError {
+    fn from(error: AppError) -> Self {
+        let permanent = match &error {
+            AppError::InvalidRecipient(_) | AppError::InvalidHeader(_) | AppError::MessageTooLarge { .. } => true,
+            // SMTP错误以响应码开头，5xx为永久错误
+            AppError::Smtp(reply) => reply.starts_with('5'),
+            // 认证失败在用户重新登录后可以成功，由重试次数上限兜底
+            _ => false,
+        };
+        if permanent {
+            DeliveryError::Rejected(error.to_string())
+        } else {
+            DeliveryError::Transient(error.to_string())
+        }
+    }
+}
+
+impl From<DeliveryError> for String {
+    fn from(error: DeliveryError) -> Self {
+        match error {
+            DeliveryError::Unreachable { reason, .. } => reason,
+            DeliveryError::Transient(error) | DeliveryError::Rejected(error) => error,
+        }
+    }
+}
+
+/// 构造邮件并通过SMTP投递，返回Message-ID
+/// 提供幂等键时，有效期内已成功发送的键直接返回之前的Message-ID，正在发送或上次结果未知的键返回错误
+async fn deliver_email(
+    app: &tauri::AppHandle,
+    request: &outbox::OutgoingRequest,
+) -> Result<String, DeliveryError> {
+    let (session, password) = load_credentials(app)?;
+    let idempotency_key = request.idempotency_key
+        .as_ref()
+        .filter(|key| !key.trim().is_empty())
+        .map(|key| format!("{}/{}", session.email, key));
+    let attachments = load_attachments(app, &request.attachment_paths)?;
+    
+    // 所有进入单行头部的参数都要在连接SMTP前校验，防止头部注入
+    let preferences = current_preferences(app);
+    let display_name = preferences.display_name;
+    if let Some(name) = &display_name {
+        smtp::check_header_value("From", name)?;
+    }
+    smtp::check_header_value("Subject", &request.subject)?;
+    for attachment in &attachments {
+        smtp::check_header_value("Content-Disposition", &attachment.filename)?;
+    }
+    let recipients = smtp::parse_recipients(&request.to)?;
+    
+    let from_address = request.from_alias
+        .as_deref()
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty())
+        .unwrap_or(&session.email)
+        .to_string();
+    if !smtp::is_valid_address(&from_address) {
+        return Err(AppError::InvalidHeader("From".to_string()).into());
+    }
+    let sender = (from_address != session.email)
+        .then(|| smtp::format_mailbox(display_name.as_deref(), &session.email));
+    
+    let domain = session.email.rsplit('@').next().unwrap_or("2925.com");
+    let message = smtp::OutgoingMessage {
+        // 同一幂等键的重试使用相同的Message-ID
+        message_id: smtp::generate_message_id(domain, idempotency_key.as_deref()),
+        from: smtp::format_mailbox(display_name.as_deref(), &from_address),
+        sender,
+        to: recipients.clone(),
+        subject: request.subject.clone(),
+        body: match preferences.signature.as_deref() {
+            Some(signature) if request.include_signature => smtp::append_signature(&request.body, signature),
+            _ => request.body.clone(),
+        },
+        attachments,
+    };
+    let content = message.to_rfc5322();
+    
+    if let Some(key) = &idempotency_key {
+        let sent_keys = app.state::<SentKeys>();
+        let mut records = sent_keys.0.lock().await;
+        records.retain(|_, record| match record {
+            SendRecord::Pending => true,
+            SendRecord::Sent(_, at) | SendRecord::Ambiguous(_, at) => at.elapsed() < IDEMPOTENCY_WINDOW,
+        });
+        match records.get(key) {
+            Some(SendRecord::Sent(message_id, _)) => return Ok(message_id.clone()),
+            // 自动重发可能产生重复的邮件，由用户确认后使用新的幂等键重新发送
+            Some(SendRecord::Ambiguous(message_id, _)) => {
+                return Err(DeliveryError::Rejected(format!("{}: {}", tr("上次发送的结果未知，邮件可能已经发出"), message_id)));
+            }
+            Some(SendRecord::Pending) => return Err(tr("邮件正在发送中").to_string().into()),
+            None => {
+                records.insert(key.clone(), SendRecord::Pending);
+            }
+        }
+    }
+    
+    let config = load_smtp_config(app);
+    let result = smtp_send(&config, &session.email, &password, &recipients, &content).await;
+    
+    if let Some(key) = idempotency_key {
+        let sent_keys = app.state::<SentKeys>();
+        let mut records = sent_keys.0.lock().await;
+        match &result {
+            Ok(()) => {
+                records.insert(key, SendRecord::Sent(message.message_id.clone(), std::time::Instant::now()));
+            }
+            // 连接、TLS握手或认证阶段失败时邮件肯定没有发出，允许使用相同的键重试
+            Err(AppError::Connection(_) | AppError::Tls(_) | AppError::AuthRejected(_)) => {
+                records.remove(&key);
+            }
+            // 之后的失败（如DATA之后连接中断）无法确定服务器是否已接收，保留记录，避免重复发送
+            Err(_) => {
+                records.insert(key, SendRecord::Ambiguous(message.message_id.clone(), std::time::Instant::now()));
+            }
+        }
+    }
+    
+    match result {
+        Ok(()) => Ok(message.message_id),
+        // 连接或TLS握手阶段失败时邮件肯定没有发出
+        Err(error @ (AppError::Connection(_) | AppError::Tls(_))) => Err(DeliveryError::Unreachable {
+            message_id: message.message_id,
+            reason: error.to_string(),
+        }),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// 连接SMTP服务器并发送已生成的邮件内容
+/// 
+/// 整个过程限定在 `timeout_secs` 内完成。DATA之前超时时邮件肯定没有发出，按无法连接处理；
+/// 之后超时时无法确定服务器是否已接收，返回 `OperationTimeout`；服务器确认接收后QUIT超时不影响结果
+async fn smtp_send(
+    config: &SmtpConfig,
+    email: &str,
+    password: &str,
+    recipients: &[String],
+    content: &str,
+) -> Result<(), AppError> {
+    let mut data_started = false;
+    let mut delivered = false;
+    let result = async_std::future::timeout(std::time::Duration::from_secs(config.timeout_secs), async {
+        let mut connection = smtp::SmtpConnection::connect(&config.server, config.port, config.security, config.min_tls_version).await?;
+        connection.login(email, password).await?;
+        // 信封发件人始终为登录账户
+        connection
+            .start_data(email, recipients, content.len() as u64, config.size_limit_mb.saturating_mul(1024 * 1024))
+            .await?;
+        data_started = true;
+        connection.send_data(content).await?;
+        delivered = true;
+        connection.quit().await;
+        Ok(())
+    }).await;
+    
+    match result {
+        Ok(result) => result,
+        Err(_) if delivered => Ok(()),
+        Err(_) if data_started => Err(AppError::OperationTimeout(config.timeout_secs)),
+        Err(_) => Err(AppError::Connection(AppError::OperationTimeout(config.timeout_secs).to_string())),
+    }
+}
+
+/// 原邮件To和Cc中的收件人地址（去重，跳过无效地址）
+fn original_recipients(headers: &[mailparse::MailHeader]) -> Vec<String> {
+    let mut recipients: Vec<String> = Vec::new();
+    for header in ["To", "Cc"].iter().flat_map(|name| headers.get_all_headers(name)) {
+        let Ok(list) = mailparse::addrparse_header(header) else {
+            continue;
+        };
+        for addr in list.iter() {
+            let infos = match addr {
+                mailparse::MailAddr::Single(info) => vec![info.clone()],
+                mailparse::MailAddr::Group(group) => group.addrs.clone(),
+            };
+            for info in infos {
+                if smtp::is_valid_address(&info.addr)
+                    && !recipients.iter().any(|existing| existing.eq_ignore_ascii_case(&info.addr))
+                {
+                    recipients.push(info.addr);
+                }
+            }
+        }
+    }
+    recipients
+}
+
+/// 重新发送邮件命令，用于收到退信后重新投递原邮件
+/// 
+/// 获取原邮件的完整内容，在最前面添加 `Resent-Date`、`Resent-From`、`Resent-To` 和 `Resent-Message-ID`
+/// （RFC 5322 3.6.6）后原样发送，正文和附件不变。按RFC的规定原有的To、Cc等头部不修改，
+/// 实际收件人由 `Resent-To` 和SMTP信封决定。不经过发件箱，无法连接服务器时直接返回错误
+/// 
+/// 返回 `Resent-Message-ID`
+/// 
+/// # 参数
+/// * `message_id` - 原邮件ID（UID）
+/// * `folder` - 原邮件所在文件夹，默认为收件箱
+/// * `new_recipients` - 新的收件人，默认为原邮件To和Cc中的收件人
+#[tauri::command]
+async fn resend_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+    new_recipients: Option<Vec<String>>,
+) -> Result<String, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let uid = parse_message_id(&message_id)?;
+    
+    let raw = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        imap_session
+            .examine(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        let messages = imap_session
+            .uid_fetch(uid.to_string(), "(UID BODY.PEEK[])")
+            .await
+            .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+        
+        let mut messages = std::pin::pin!(messages);
+        while let Some(result) = messages.next().await {
+            let message = result.map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+            if let Some(body) = message.body() {
+                return Ok(body.to_vec());
+            }
+        }
+        Err(format!("{}: {}", tr("邮件不存在"), message_id))
+    })).await?;
+    
+    // SMTP发送的是文本内容，8位非UTF-8的原始内容无法在不改变正文的情况下发送
+    let raw = String::from_utf8(raw).map_err(|_| tr("邮件包含无法重新发送的8位内容").to_string())?;
+    
+    let recipients = match new_recipients {
+        Some(list) => smtp::parse_recipients(&list.join(",")).map_err(|e| e.to_string())?,
+        None => {
+            let (headers, _) = mailparse::parse_headers(raw.as_bytes())
+                .map_err(|e| format!("{}: {}", tr("解析邮件失败"), e))?;
+            original_recipients(&headers)
+        }
+    };
+    if recipients.is_empty() {
+        return Err(tr("收件人不能为空").to_string());
+    }
+    
+    let (session, password) = load_credentials(&app)?;
+    let display_name = current_preferences(&app).display_name;
+    if let Some(name) = &display_name {
+        smtp::check_header_value("From", name).map_err(|e| e.to_string())?;
+    }
+    let domain = session.email.rsplit('@').next().unwrap_or("2925.com");
+    let resent = smtp::ResentHeaders {
+        message_id: smtp::generate_message_id(domain, None),
+        from: smtp::format_mailbox(display_name.as_deref(), &session.email),
+        to: recipients.clone(),
+    };
+    
+    let config = load_smtp_config(&app);
+    smtp_send(&config, &session.email, &password, &recipients, &resent.prepend_to(&raw))
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    Ok(resent.message_id)
+}
+
+/// 将草稿同步到服务器的草稿箱命令
+/// 
+/// 按草稿生成邮件，以 `\Draft` 标记APPEND到草稿箱（SPECIAL-USE声明的 `\Drafts` 文件夹）。
+/// 同一草稿之前同步过时，新副本追加成功后删除旧副本；删除失败只记录警告，不影响本次同步。
+/// 草稿不会发送，收件人可以为空或尚未填写完整
+/// 
+/// 返回新副本的UID
+/// 
+/// # 参数
+/// * `draft` - 要同步的草稿
+#[tauri::command]
+async fn sync_draft_to_server(app: tauri::AppHandle, draft: drafts::EmailDraft) -> Result<u32, String> {
+    if draft.id.trim().is_empty() {
+        return Err(tr("草稿ID不能为空").to_string());
+    }
+    let session = load_session_info(&app)?;
+    let attachments = load_attachments(&app, &draft.attachment_paths)?;
+    
+    // 草稿会被其他客户端打开和发送，头部同样需要防止注入
+    let display_name = current_preferences(&app).display_name;
+    if let Some(name) = &display_name {
+        smtp::check_header_value("From", name).map_err(|e| e.to_string())?;
+    }
+    smtp::check_header_value("To", &draft.to).map_err(|e| e.to_string())?;
+    smtp::check_header_value("Subject", &draft.subject).map_err(|e| e.to_string())?;
+    for attachment in &attachments {
+        smtp::check_header_value("Content-Disposition", &attachment.filename).map_err(|e| e.to_string())?;
+    }
+    
+    let folder = special_folders(&app)
+        .await?
+        .remove("\\Drafts")
+        .ok_or_else(|| tr("找不到草稿箱").to_string())?;
+    let domain = session.email.rsplit('@').next().unwrap_or("2925.com");
+    let message = smtp::OutgoingMessage {
+        // 同一草稿的各个版本使用相同的Message-ID，服务器不支持UIDPLUS时按它查找新副本
+        message_id: smtp::generate_message_id(domain, Some(&format!("draft/{}/{}", session.email, draft.id))),
+        from: smtp::format_mailbox(display_name.as_deref(), &session.email),
+        sender: None,
+        to: draft.to
+            .split(',')
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty())
+            .collect(),
+        subject: draft.subject.clone(),
+        body: draft.body.clone(),
+        attachments,
+    };
+    let content = message.to_rfc5322();
+    
+    let lock = app.state::<drafts::DraftsLock>();
+    let _guard = lock.0.lock().await;
+    let previous = drafts::get(&app, &session.email, &draft.id).filter(|previous| previous.folder == folder);
+    
+    let message_id = message.message_id.clone();
+    let drafts_folder = folder.clone();
+    let (server_draft, stale) = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let appended = drafts::append(imap_session, &utf7::encode(&folder), content.as_bytes()).await?;
+        let mailbox = imap_session
+            .select(utf7::encode(&folder))
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), folder, e))?;
+        let uid_validity = mailbox.uid_validity.unwrap_or(0);
+        
+        let uid = match appended {
+            Some((validity, uid)) if validity == uid_validity => uid,
+            // UID严格递增，同一Message-ID中最大的UID就是刚追加的副本
+            _ => imap_session
+                .uid_search(format!("HEADER Message-ID {}", quote_search_term(&message_id)?))
+                .await
+                .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?
+                .into_iter()
+                .max()
+                .ok_or_else(|| format!("{}: {}", tr("邮件不存在"), message_id))?,
+        };
+        
+        // 旧副本已被其他客户端删除时设置标记不会影响任何邮件
+        let stale = previous
+            .filter(|previous| previous.uid_validity == uid_validity && previous.uid != uid)
+            .map(|previous| previous.uid);
+        let removed = match stale {
+            Some(stale) => remove_stale_draft(imap_session, stale).await,
+            None => Ok(()),
+        };
+        Ok((drafts::ServerDraft { folder: folder.clone(), uid, uid_validity }, removed))
+    })).await?;
+    if let Err(e) = stale {
+        log::warn!("删除草稿箱 {} 中的旧草稿失败: {}", drafts_folder, e);
+    }
+    
+    drafts::set(&app, &session.email, &draft.id, &server_draft)?;
+    Ok(server_draft.uid)
+}
+
+/// 删除当前选中的草稿箱中的旧草稿副本
+async fn remove_stale_draft(imap_session: &mut ImapSession, uid: u32) -> Result<(), String> {
+    {
+        let updates = imap_session
+            .uid_store(uid.to_string(), "+FLAGS (\\Deleted)")
+            .await
+            .map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+        let mut updates = std::pin::pin!(updates);
+        while let Some(result) = updates.next().await {
+            result.map_err(|e| format!("{}: {}", tr("修改邮件标记失败"), e))?;
+        }
+    }
+    
+    expunge_uid(imap_session, uid).await?;
+    Ok(())
+}
+
+/// SMTP测试失败的阶段
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SmtpFailure {
+    /// 无法连接服务器或TLS握手失败
+    Connection,
+    /// 服务器拒绝了登录凭据
+    Auth,
+    /// 其他错误（如协议错误、未登录）
+    Other,
+}
+
+/// SMTP配置测试结果
+#[derive(Debug, Clone, Serialize)]
+struct SmtpTestResult {
+    ok: bool,
+    /// EHLO中声明了AUTH扩展；未能完成EHLO时为false
+    #[serde(rename = "supportsAuth")]
+    supports_auth: bool,
+    /// SIZE扩展声明的最大邮件大小（字节）
+    #[serde(rename = "maxMessageSize")]
+    max_message_size: Option<u64>,
+    failure: Option<SmtpFailure>,
+    error: Option<String>,
+}
+
+impl SmtpTestResult {
+    fn failed(failure: SmtpFailure, error: impl std::fmt::Display) -> Self {
+        SmtpTestResult {
+            ok: false,
+            supports_auth: false,
+            max_message_size: None,
+            failure: Some(failure),
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// 测试SMTP配置命令
+/// 连接配置的SMTP服务器，完成TLS、EHLO和使用已保存凭据的AUTH后直接QUIT，不发送邮件。
+/// 测试本身的失败通过结果中的 `failure` 区分连接失败和认证失败，而不是返回错误
+#[tauri::command]
+async fn test_smtp(app: tauri::AppHandle) -> Result<SmtpTestResult, String> {
+    let (session, password) = match load_credentials(&app) {
+        Ok(credentials) => credentials,
+        Err(e) => return Ok(SmtpTestResult::failed(SmtpFailure::Other, e)),
+    };
+    
+    let config = load_smtp_config(&app);
+    let mut connection = match smtp::SmtpConnection::connect(&config.server, config.port, config.security, config.min_tls_version).await {
+        Ok(connection) => connection,
+        Err(e @ (AppError::Connection(_) | AppError::Tls(_))) => {
+            return Ok(SmtpTestResult::failed(SmtpFailure::Connection, e));
+        }
+        Err(e) => return Ok(SmtpTestResult::failed(SmtpFailure::Other, e)),
+    };
+    
+    let supports_auth = connection.supports_auth();
+    let max_message_size = connection.max_message_size();
+    let login = connection.login(&session.email, &password).await;
+    connection.quit().await;
+    
+    let (failure, error) = match login {
+        Ok(()) => (None, None),
+        Err(e @ AppError::AuthRejected(_)) => (Some(SmtpFailure::Auth), Some(e.to_string())),
+        Err(e) => (Some(SmtpFailure::Other), Some(e.to_string())),
+    };
+    
+    Ok(SmtpTestResult {
+        ok: failure.is_none(),
+        supports_auth,
+        max_message_size,
+        failure,
+        error,
+    })
+}
+
+/// 发件箱轮询间隔
+const OUTBOX_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// 发件箱事件负载
+#[derive(Debug, Clone, Serialize)]
+struct OutboxEvent {
+    id: String,
+    #[serde(rename = "messageId")]
+    message_id: Option<String>,
+    error: Option<String>,
+    /// 失败后是否还会重试
+    #[serde(rename = "willRetry")]
+    will_retry: bool,
+}
+
+/// 尝试发送发件箱中已到重试时间的邮件
+/// 发送成功发送 `mail-sent` 事件；失败发送 `mail-send-failed` 事件，
+/// 无法连接或暂时性错误时按退避间隔安排重试，服务器永久拒绝、邮件无效或超过重试次数时放弃
+async fn drain_outbox(app: &tauri::AppHandle) {
+    let Ok(session) = load_session_info(app) else {
+        return;
+    };
+    
+    for mail in outbox::due(app, &session.email) {
+        match deliver_email(app, &mail.request).await {
+            Ok(message_id) => {
+                let _ = outbox::remove(app, &mail.id).await;
+                let _ = app.emit("mail-sent", OutboxEvent {
+                    id: mail.id,
+                    message_id: Some(message_id),
+                    error: None,
+                    will_retry: false,
+                });
+            }
+            Err(DeliveryError::Unreachable { reason, .. }) => {
+                let will_retry = matches!(outbox::record_failure(app, &mail.id, reason.clone()).await, Ok(Some(_)));
+                let _ = app.emit("mail-send-failed", OutboxEvent {
+                    id: mail.id,
+                    message_id: None,
+                    error: Some(reason),
+                    will_retry,
+                });
+                // 仍然离线，本轮不再尝试其他邮件
+                break;
+            }
+            Err(DeliveryError::Transient(error)) => {
+                let will_retry = matches!(outbox::record_failure(app, &mail.id, error.clone()).await, Ok(Some(_)));
+                let _ = app.emit("mail-send-failed", OutboxEvent {
+                    id: mail.id,
+                    message_id: None,
+                    error: Some(error),
+                    will_retry,
+                });
+            }
+            // 只有重试也不会成功的错误才从发件箱移除
+            Err(DeliveryError::Rejected(error)) => {
+                let _ = outbox::remove(app, &mail.id).await;
+                let _ = app.emit("mail-send-failed", OutboxEvent {
+                    id: mail.id,
+                    message_id: None,
+                    error: Some(error),
+                    will_retry: false,
+                });
+            }
+        }
+    }
+}
+
+/// 列出发件箱命令
+/// 返回尚未发出的邮件及其重试状态
+#[tauri::command]
+async fn list_outbox(app: tauri::AppHandle) -> Result<Vec<outbox::QueuedMail>, String> {
+    Ok(outbox::list(&app))
+}
+
+/// 取消发送命令
+/// 从发件箱移除邮件；正在发送中的邮件可能已经发出
+/// 
+/// # 参数
+/// * `id` - 发件箱ID
+#[tauri::command]
+async fn cancel_queued(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    if !outbox::remove(&app, &id).await? {
+        return Err(format!("{}: {}", tr("发件箱中没有该邮件"), id));
+    }
+    Ok(())
+}
+
+/// 为休眠未到期的邮件添加 `snoozedUntil` 字段（毫秒），邮件本身仍保留在列表中
+fn tag_snoozed(app: &tauri::AppHandle, folder: &str, emails: &mut [Email]) {
+    let Ok(session) = load_session_info(app) else {
+        return;
+    };
+    let snoozed = snooze::active_in(app, &session.email, folder);
+    if snoozed.is_empty() {
+        return;
+    }
+    for email in emails {
+        if let Some(until) = snoozed.get(&email.id) {
+            email.snoozed_until = Some(*until);
+        }
+    }
+}
+
+/// 休眠到期检查间隔
+const SNOOZE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 唤醒到期的休眠邮件，对每封邮件发送 `snooze-expired` 事件
+async fn wake_snoozed(app: &tauri::AppHandle) {
+    let expired = match snooze::take_expired(app).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            log::warn!("读取休眠邮件失败: {}", e);
+            return;
+        }
+    };
+    for snoozed in expired {
+        let _ = app.emit("snooze-expired", snoozed);
+    }
+}
+
+/// 休眠邮件命令
+/// 只在本地记录，之后获取的邮件列表中该邮件带 `snoozedUntil` 字段，到期后发送 `snooze-expired` 事件
+/// 
+/// # 参数
+/// * `message_id` - 邮件UID
+/// * `until` - 唤醒时间（毫秒），必须晚于当前时间
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn snooze_email(
+    app: tauri::AppHandle,
+    message_id: String,
+    until: i64,
+    folder: Option<String>,
+) -> Result<(), String> {
+    let session = load_session_info(&app)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    if until <= now {
+        return Err(tr("休眠时间必须晚于当前时间").to_string());
+    }
+    
+    snooze::add(&app, snooze::Snooze {
+        account: session.email,
+        folder: resolve_folder(&app, folder).await,
+        message_id,
+        until,
+        snoozed_at: now,
+    }).await
+}
+
+/// 列出当前账户的休眠邮件，按唤醒时间排列
+#[tauri::command]
+async fn list_snoozed(app: tauri::AppHandle) -> Result<Vec<snooze::Snooze>, String> {
+    let session = load_session_info(&app)?;
+    Ok(snooze::list(&app, &session.email))
+}
+
+/// 取消休眠命令，返回邮件之前是否处于休眠中
+/// 
+/// # 参数
+/// * `message_id` - 邮件UID
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn unsnooze(app: tauri::AppHandle, message_id: String, folder: Option<String>) -> Result<bool, String> {
+    let session = load_session_info(&app)?;
+    snooze::remove(&app, &session.email, &resolve_folder(&app, folder).await, &message_id).await
+}
+
+/// 获取文件夹当前的UIDVALIDITY
+async fn folder_uid_validity(app: &tauri::AppHandle, folder: &str) -> Result<u32, String> {
+    let folder = folder.to_string();
+    with_imap_session(app, move |imap_session| Box::pin(async move {
+        let status = imap_session
+            .status(utf7::encode(&folder), "(UIDVALIDITY)")
+            .await
+            .map_err(|e| format!("{} {}: {}", tr("获取文件夹状态失败"), folder, e))?;
+        Ok(status.uid_validity.unwrap_or(0))
+    })).await
+}
+
+/// 记录文件夹的阅读位置命令
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+/// * `uid` - 最后查看的邮件UID
+#[tauri::command]
+async fn set_read_position(app: tauri::AppHandle, folder: Option<String>, uid: u32) -> Result<(), String> {
+    let session = load_session_info(&app)?;
+    let folder = resolve_folder(&app, folder).await;
+    let uid_validity = folder_uid_validity(&app, &folder).await?;
+    
+    read_position::set(&app, &session.email, &folder, read_position::ReadPosition {
+        uid,
+        uid_validity,
+        updated_at: chrono::Utc::now().timestamp_millis(),
+    }).await
+}
+
+/// 获取文件夹的阅读位置命令，没有记录时返回null
+/// 
+/// 文件夹的UIDVALIDITY已变化时清除记录并返回null；无法连接服务器时直接返回记录的位置
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn get_read_position(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<Option<read_position::ReadPosition>, String> {
+    let session = load_session_info(&app)?;
+    let folder = resolve_folder(&app, folder).await;
+    let Some(position) = read_position::get(&app, &session.email, &folder) else {
+        return Ok(None);
+    };
+    
+    match folder_uid_validity(&app, &folder).await {
+        Ok(uid_validity) if uid_validity != position.uid_validity => {
+            read_position::clear(&app, &session.email, &folder).await?;
+            Ok(None)
+        }
+        Ok(_) => Ok(Some(position)),
+        Err(e) => {
+            log::debug!("无法检查 {} 的UIDVALIDITY，使用记录的阅读位置: {}", folder, e);
+            Ok(Some(position))
+        }
+    }
+}
+
+/// 实际使用的退订方式
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum UnsubscribeMethod {
+    /// 向退订链接发送了一键退订POST
+    OneClick,
+    /// 向退订地址发送了退订邮件
+    Mailto,
+}
+
+/// 退订邮件列表命令
+/// 读取邮件的 `List-Unsubscribe` 头部：声明了一键退订时向HTTPS链接发送POST，
+/// 否则通过SMTP向mailto地址发送退订邮件。只有网页链接时返回错误，由界面打开 `unsubscribe.http`
+/// 
+/// # 参数
+/// * `message_id` - 邮件ID（UID）
+/// * `folder` - 邮件所在文件夹，默认为收件箱
+#[tauri::command]
+async fn unsubscribe(
+    app: tauri::AppHandle,
+    message_id: String,
+    folder: Option<String>,
+) -> Result<UnsubscribeMethod, String> {
+    let folder = resolve_folder(&app, folder).await;
+    let uid = parse_message_id(&message_id)?;
+    
+    let headers = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        fetch_message_header(imap_session, &folder, uid).await
+    })).await?;
+    
+    let (headers, _) = mailparse::parse_headers(&headers)
+        .map_err(|e| format!("{}: {}", tr("获取邮件失败"), e))?;
+    let options = unsubscribe::from_headers(&headers)
+        .ok_or_else(|| tr("该邮件没有退订链接").to_string())?;
+    
+    if options.one_click {
+        if let Some(link) = &options.http {
+            unsubscribe::post_one_click(link).await?;
+            return Ok(UnsubscribeMethod::OneClick);
+        }
+    }
+    
+    let Some(target) = options.mailto.as_deref().and_then(unsubscribe::parse_mailto) else {
+        return Err(tr("该邮件只能通过网页退订").to_string());
+    };
+    let request = outbox::OutgoingRequest {
+        to: target.to,
+        subject: target.subject,
+        body: target.body,
+        from_alias: None,
+        attachment_paths: Vec::new(),
+        idempotency_key: None,
+        include_signature: false,
+    };
+    deliver_email(&app, &request).await?;
+    
+    Ok(UnsubscribeMethod::Mailto)
+}
+
+/// 保存会话到加密存储
+#[tauri::command]
+async fn save_session(
+    app: tauri::AppHandle,
+    session: AuthSession,
+) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    store.set("session", serde_json::to_value(&session).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save session: {}", e))?;
+    
+    Ok(())
+}
+
+/// 保存密码到加密存储（用于后续IMAP操作）
+/// 使用三层加密保护密码安全
+#[tauri::command]
+async fn save_password(
+    app: tauri::AppHandle,
+    password: String,
+) -> Result<(), String> {
+    // 从存储中获取当前会话以获取邮箱地址
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    let session_value = store.get("session")
+        .ok_or(tr("未找到会话信息"))?;
+    
+    let session: AuthSession = serde_json::from_value(session_value.clone())
+        .map_err(|e| format!("{}: {}", tr("会话数据无效"), e))?;
+    
+    // 使用三层加密保存密码
+    let server = load_imap_config(&app).server;
+    crypto::encrypt_and_save_password(&password, &session.email, &server)
+        .map_err(|e| format!("{}: {}", tr("保存密码失败"), e))?;
+    
+    Ok(())
+}
+
+/// 存储数据的结构版本，修改已保存数据的结构时递增并在 `migrate_store` 中添加迁移步骤
+const STORE_SCHEMA_VERSION: u64 = 1;
+
+/// 将存储中的数据迁移到当前结构版本
+/// 
+/// 版本号保存在 `schema_version` 中，缺失时视为0。存储来自更新版本的应用（降级）时不做任何修改，
+/// 读取时依靠字段默认值和忽略未知字段兼容
+fn migrate_store(app: &tauri::AppHandle) {
+    let Ok(store) = app.store("store.json") else {
+        return;
+    };
+    let version = store.get("schema_version").and_then(|value| value.as_u64()).unwrap_or(0);
+    if version > STORE_SCHEMA_VERSION {
+        log::warn!("存储数据来自更新的版本（{} > {}），不进行迁移", version, STORE_SCHEMA_VERSION);
+        return;
+    }
+    if version == STORE_SCHEMA_VERSION {
+        return;
+    }
+    
+    // 0 -> 1：补全旧版本偏好设置中缺少的字段
+    if version < 1 {
+        if let Some(value) = store.get("preferences") {
+            match serde_json::from_value::<UserPreferences>(value) {
+                Ok(preferences) => store.set("preferences", serde_json::to_value(&preferences).unwrap()),
+                Err(e) => log::warn!("迁移偏好设置失败，保留原数据: {}", e),
+            }
+        }
+    }
+    
+    store.set("schema_version", serde_json::Value::from(STORE_SCHEMA_VERSION));
+    if let Err(e) = persist::save(app, &store, "store.json") {
+        log::warn!("保存迁移后的存储失败: {}", e);
+    }
+}
+
+/// 迁移旧版本明文保存在store中的密码
+/// 重新加密保存到keyring并确认可以解密后，删除store中的明文副本；
+/// 任一步骤失败时保留明文副本，下次启动时重试
+fn migrate_legacy_password(app: &tauri::AppHandle) {
+    let Ok(store) = app.store("store.json") else {
+        return;
+    };
+    let Some(value) = store.get("password") else {
+        return;
+    };
+    
+    // 会话过期后仍可用keyring中的密码自动重新登录，因此不检查过期时间
+    let session = store.get("session")
+        .and_then(|value| serde_json::from_value::<AuthSession>(value).ok());
+    let migrated = match (session, serde_json::from_value::<String>(value)) {
+        (Some(session), Ok(password)) => {
+            let server = load_imap_config(app).server;
+            let saved = crypto::encrypt_and_save_password(&password, &session.email, &server)
+                .and_then(|_| crypto::load_and_decrypt_password(&session.email, &server));
+            match saved {
+                Ok(decrypted) if decrypted == password => true,
+                Ok(_) => {
+                    log::warn!("迁移旧密码失败: keyring中的密码与旧密码不一致");
+                    false
+                }
+                Err(e) => {
+                    log::warn!("迁移旧密码失败: {}", e);
+                    false
+                }
+            }
+        }
+        // 没有会话时不知道密码属于哪个账户，数据无效时也无法迁移，直接删除
+        _ => true,
+    };
+    
+    if migrated {
+        store.delete("password");
+        if let Err(e) = persist::save(app, &store, "store.json") {
+            log::warn!("删除旧密码失败: {}", e);
+        }
+    }
+}
+
+/// 从加密存储加载会话
+#[tauri::command]
+async fn load_session(app: tauri::AppHandle) -> Result<Option<AuthSession>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    match store.get("session") {
+        Some(value) => {
+            let session: AuthSession = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to deserialize session: {}", e))?;
+            // 没有邮箱地址的会话无法使用
+            Ok(Some(session).filter(|session| !session.email.is_empty()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// 从加密存储加载密码
+/// 使用三层解密恢复密码
+#[tauri::command]
+async fn load_password(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    // 从存储中获取当前会话以获取邮箱地址
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    let session_value = match store.get("session") {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    
+    let session: AuthSession = serde_json::from_value(session_value.clone())
+        .map_err(|e| format!("{}: {}", tr("会话数据无效"), e))?;
+    
+    // 从三层加密存储中读取密码
+    let server = load_imap_config(&app).server;
+    Ok(crypto::load_and_decrypt_password(&session.email, &server).ok())
+}
+
+/// 会话状态
+#[derive(Debug, Clone, Serialize)]
+struct SessionStatus {
+    /// 会话存在且未过期
+    valid: bool,
+    /// 距离过期的秒数，已过期或没有会话时为0
+    #[serde(rename = "expiresInSecs")]
+    expires_in_secs: i64,
+    /// keyring中保存了密码，过期后可自动重新登录
+    #[serde(rename = "canAutoRefresh")]
+    can_auto_refresh: bool,
+}
+
+/// 凭据诊断命令
+/// 报告保存的密码在哪个阶段无法读取（keyring、第二层解密、机器ID、UTF-8），不返回密码本身
+/// 
+/// # 参数
+/// * `email` - 要诊断的邮箱
+#[tauri::command]
+async fn diagnose_credential(
+    app: tauri::AppHandle,
+    email: String,
+) -> Result<crypto::CredentialDiagnosis, String> {
+    let server = load_imap_config(&app).server;
+    Ok(crypto::diagnose_password(&email, &server))
+}
+
+/// 凭据检查结论
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum CredentialVerdict {
+    /// 保存的密码可以登录
+    Valid,
+    /// keyring中没有保存该账户的密码
+    NotStored,
+    /// 保存了密码但无法读取或解密，原因见 `diagnosis`
+    Unreadable,
+    /// 密码可以读取，但服务器拒绝登录：保存的密码已过时或被其他用户、其他版本覆盖
+    Stale,
+    /// 无法连接服务器，未能验证密码
+    Unverified,
+}
+
+/// 凭据检查结果，不包含密码本身
+#[derive(Debug, Clone, Serialize)]
+struct CredentialCheck {
+    verdict: CredentialVerdict,
+    diagnosis: crypto::CredentialDiagnosis,
+    /// 保存了不同密码的其他keyring位置（`服务名/用户名`），为空表示没有冲突
+    #[serde(rename = "conflictingEntries")]
+    conflicting_entries: Vec<String>,
+    /// 登录失败或无法连接时的错误信息
+    detail: Option<String>,
+}
+
+/// 检查凭据冲突命令
+/// 读取keyring中保存的密码并用单独的连接静默登录一次（不使用连接池，不发送连接状态事件），
+/// 区分没有保存密码、无法解密、密码已被服务器拒绝和无法验证；同时报告其他keyring位置中保存的不同密码
+/// 
+/// # 参数
+/// * `email` - 要检查的邮箱，默认为当前会话的账户（会话已过期也可以）
+#[tauri::command]
+async fn check_credential_conflicts(app: tauri::AppHandle, email: Option<String>) -> Result<CredentialCheck, String> {
+    let email = match email.filter(|email| !email.trim().is_empty()) {
+        Some(email) => email,
+        None => app.store("store.json")
+            .ok()
+            .and_then(|store| store.get("session"))
+            .and_then(|value| serde_json::from_value::<AuthSession>(value).ok())
+            .map(|session| session.email)
+            .ok_or_else(|| tr("未登录，请先登录").to_string())?,
+    };
+    let config = load_imap_config(&app);
+    let diagnosis = crypto::diagnose_password(&email, &config.server);
+    
+    let stored = crypto::stored_passwords(&email, &config.server);
+    let conflicting_entries = match stored.split_first() {
+        Some(((_, current), others)) => others
+            .iter()
+            .filter(|(_, password)| password != current)
+            .map(|(location, _)| location.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    
+    let (verdict, detail) = match (diagnosis.status, stored.first()) {
+        (crypto::CredentialStatus::KeyringMissing, _) => (CredentialVerdict::NotStored, None),
+        (crypto::CredentialStatus::Ok, Some((_, password))) => {
+            let timeout = std::time::Duration::from_secs(config.operation_timeout_secs);
+            match async_std::future::timeout(timeout, imap_pool::connect(&email, password, &config)).await {
+                Ok(Ok(mut imap_session)) => {
+                    let _ = imap_session.logout().await;
+                    (CredentialVerdict::Valid, None)
+                }
+                Ok(Err(e @ AppError::AuthRejected(_))) => (CredentialVerdict::Stale, Some(e.to_string())),
+                Ok(Err(e)) => (CredentialVerdict::Unverified, Some(e.to_string())),
+                Err(_) => (
+                    CredentialVerdict::Unverified,
+                    Some(AppError::OperationTimeout(config.operation_timeout_secs).to_string()),
+                ),
+            }
+        }
+        _ => (CredentialVerdict::Unreadable, diagnosis.detail.clone()),
+    };
+    
+    Ok(CredentialCheck {
+        verdict,
+        diagnosis,
+        conflicting_entries,
+        detail,
+    })
+}
+
+/// keyring后端信息命令
+/// 返回当前平台使用的keyring后端及能否访问，用于排查密码无法保存的原因
+#[tauri::command]
+async fn keyring_info() -> Result<crypto::KeyringInfo, String> {
+    Ok(crypto::keyring_info())
+}
+
+/// 查询会话状态命令
+/// 只读取存储的会话和keyring，不连接服务器；前端可据此显示倒计时并在过期前刷新会话
+#[tauri::command]
+async fn session_status(app: tauri::AppHandle) -> Result<SessionStatus, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    let session = match store.get("session")
+        .and_then(|value| serde_json::from_value::<AuthSession>(value).ok())
+    {
+        Some(session) => session,
+        None => {
+            return Ok(SessionStatus {
+                valid: false,
+                expires_in_secs: 0,
+                can_auto_refresh: false,
+            })
+        }
+    };
+    
+    let expires_in_secs = (session.expires_at - chrono::Utc::now().timestamp()).max(0);
+    let server = load_imap_config(&app).server;
+    let can_auto_refresh = crypto::load_and_decrypt_password(&session.email, &server).is_ok();
+    
+    Ok(SessionStatus {
+        valid: expires_in_secs > 0,
+        expires_in_secs,
+        can_auto_refresh,
+    })
+}
+
+/// 保存子邮箱列表到加密存储
+#[tauri::command]
+async fn save_sub_emails(
+    app: tauri::AppHandle,
+    sub_emails: Vec<SubEmail>,
+) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    store.set("sub_emails", serde_json::to_value(&sub_emails).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save sub emails: {}", e))?;
+    
+    Ok(())
+}
+
+/// 子邮箱核对结果
+#[derive(Debug, Clone, Serialize)]
+struct SubEmailReconcile {
+    /// 本次标记为 `orphaned` 的地址
+    orphaned: Vec<String>,
+    /// 之前为 `orphaned`、现在收到过邮件而恢复为 `active` 的地址
+    restored: Vec<String>,
+    /// 更新后的完整子邮箱列表
+    #[serde(rename = "subEmails")]
+    sub_emails: Vec<SubEmail>,
+}
+
+/// 新建的子邮箱在此期限（毫秒）内没有收到邮件属于正常情况，核对时不标记为 `orphaned`
+const SUB_EMAIL_GRACE_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// 核对子邮箱列表命令
+/// 2925没有列出子邮箱的接口，子邮箱只能通过收到的邮件判断是否仍在使用：
+/// 在所有可选择的文件夹中搜索投递到每个子邮箱（`Delivered-To` 或 `To`）的邮件，
+/// 邮件被过滤规则移走或归档后仍能找到。从未收到过邮件的 `active` 子邮箱标记为 `orphaned`，
+/// 收到过邮件的 `orphaned` 子邮箱恢复为 `active`；创建不足 `SUB_EMAIL_GRACE_MS` 的 `active`
+/// 子邮箱和 `creating` / `failed` 状态的条目不变，更新后的列表保存到存储
+#[tauri::command]
+async fn reconcile_sub_emails(app: tauri::AppHandle) -> Result<SubEmailReconcile, String> {
+    let mut sub_emails = load_sub_emails(app.clone()).await?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let candidates: Vec<String> = sub_emails
+        .iter()
+        .filter(|sub| match sub.status.as_str() {
+            "active" => now.saturating_sub(sub.created_at) >= SUB_EMAIL_GRACE_MS,
+            "orphaned" => true,
+            _ => false,
+        })
+        .map(|sub| sub.address.clone())
+        .collect();
+    if candidates.is_empty() {
+        return Ok(SubEmailReconcile {
+            orphaned: vec![],
+            restored: vec![],
+            sub_emails,
+        });
+    }
+    
+    let received: HashSet<String> = with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let folders = list_selectable_folders(imap_session).await?;
+        
+        let mut received = HashSet::new();
+        for folder in folders {
+            if received.len() == candidates.len() {
+                break;
+            }
+            // 无法打开的文件夹跳过，其中的邮件不计入
+            if let Err(e) = imap_session.examine(utf7::encode(&folder)).await {
+                log::warn!("核对子邮箱时无法打开文件夹 {}: {}", folder, e);
+                continue;
+            }
+            for address in &candidates {
+                if received.contains(address) {
+                    continue;
+                }
+                let term = quote_search_term(address)?;
+                let found = imap_session
+                    .uid_search(format!("OR HEADER Delivered-To {} TO {}", term, term))
+                    .await
+                    .map_err(|e| format!("{}: {}", tr("查找邮件失败"), e))?;
+                if !found.is_empty() {
+                    received.insert(address.clone());
+                }
+            }
+        }
+        Ok(received)
+    })).await?;
+    
+    let mut orphaned = Vec::new();
+    let mut restored = Vec::new();
+    for sub in &mut sub_emails {
+        // 宽限期内的子邮箱没有参与搜索
+        if sub.status == "active" && now.saturating_sub(sub.created_at) < SUB_EMAIL_GRACE_MS {
+            continue;
+        }
+        match (sub.status.as_str(), received.contains(&sub.address)) {
+            ("active", false) => {
+                sub.status = "orphaned".to_string();
+                orphaned.push(sub.address.clone());
+            }
+            ("orphaned", true) => {
+                sub.status = "active".to_string();
+                restored.push(sub.address.clone());
+            }
+            _ => {}
+        }
+    }
+    
+    if !orphaned.is_empty() || !restored.is_empty() {
+        save_sub_emails(app, sub_emails.clone()).await?;
+    }
+    
+    Ok(SubEmailReconcile {
+        orphaned,
+        restored,
+        sub_emails,
+    })
+}
+
+/// 从加密存储加载子邮箱列表
+#[tauri::command]
+async fn load_sub_emails(app: tauri::AppHandle) -> Result<Vec<SubEmail>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    match store.get("sub_emails") {
+        Some(value) => {
+            let sub_emails: Vec<SubEmail> = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to deserialize sub emails: {}", e))?;
+            Ok(sub_emails)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// 保存过滤规则命令
+/// 
+/// # 参数
+/// * `filters` - 按执行顺序排列的规则
+#[tauri::command]
+async fn save_filters(
+    app: tauri::AppHandle,
+    filters: Vec<filters::FilterRule>,
+) -> Result<(), String> {
+    for rule in &filters {
+        if let filters::FilterAction::Move { folder } = &rule.action {
+            if folder.trim().is_empty() {
+                return Err(format!("{}: {}", tr("过滤规则无效"), rule.name));
+            }
+        }
+    }
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("filters", serde_json::to_value(&filters).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save filters: {}", e))?;
+    
+    Ok(())
+}
+
+/// 加载过滤规则命令
+#[tauri::command]
+async fn load_filters(app: tauri::AppHandle) -> Result<Vec<filters::FilterRule>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    match store.get("filters") {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to deserialize filters: {}", e)),
+        None => Ok(vec![]),
+    }
+}
+
+/// 一次规则执行记录
+#[derive(Debug, Clone, Serialize)]
+struct AppliedFilter {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    /// 命中的规则名称
+    rule: String,
+}
+
+/// 执行过滤规则命令
+/// 获取文件夹中最新的邮件（仅信封），按顺序对每封邮件评估所有规则并执行命中规则的操作；
+/// 邮件被移动后不再评估后续规则，已处于目标状态的邮件跳过
+/// 
+/// 返回实际执行的操作
+/// 
+/// # 参数
+/// * `folder` - 文件夹名称，默认为收件箱
+#[tauri::command]
+async fn apply_filters(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<Vec<AppliedFilter>, String> {
+    let rules = load_filters(app.clone()).await?;
+    if rules.is_empty() {
+        return Ok(vec![]);
+    }
+    let folder = resolve_folder(&app, folder).await;
+    
+    with_imap_session(&app, move |imap_session| Box::pin(async move {
+        let emails = fetch_list(imap_session, &folder, SortKey::Date, Order::NewestFirst, "(UID ENVELOPE FLAGS INTERNALDATE)", build_envelope).await?;
+        
+        let mut applied = Vec::new();
+        for email in &emails {
+            let message_id = email.id.as_str();
+            for rule in rules.iter().filter(|rule| rule.matches(email)) {
+                if rule.already_applied(email) {
+                    continue;
+                }
+                match &rule.action {
+                    filters::FilterAction::MarkRead => {
+                        store_message_flags(imap_session, &folder, message_id, "+FLAGS (\\Seen)").await?;
+                    }
+                    filters::FilterAction::Flag => {
+                        store_message_flags(imap_session, &folder, message_id, "+FLAGS (\\Flagged)").await?;
+                    }
+                    filters::FilterAction::Move { folder: destination } => {
+                        if *destination == folder {
+                            continue;
+                        }
+                        move_message(imap_session, &folder, message_id, destination).await?;
+                    }
+                }
+                applied.push(AppliedFilter {
+                    message_id: message_id.to_string(),
+                    rule: rule.name.clone(),
+                });
+                if matches!(rule.action, filters::FilterAction::Move { .. }) {
+                    break;
+                }
+            }
+        }
+        
+        Ok(applied)
+    })).await
+}
+
+/// 备份文件格式版本
+const BACKUP_VERSION: u32 = 1;
+
+/// 备份中的账户信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupAccount {
+    email: String,
+    /// 明文密码，仅在导出时明确要求才会包含
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+}
+
+/// 设置备份文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupBundle {
+    version: u32,
+    #[serde(rename = "exportedAt")]
+    exported_at: i64,
+    /// 包含明文密码时的提示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    #[serde(default)]
+    preferences: Option<UserPreferences>,
+    #[serde(rename = "subEmails", default)]
+    sub_emails: Vec<SubEmail>,
+    #[serde(default)]
+    accounts: Vec<BackupAccount>,
+    #[serde(rename = "imapConfig", default)]
+    imap_config: Option<ImapConfig>,
+}
+
+/// 导出备份的结果
+#[derive(Debug, Clone, Serialize)]
+struct BackupExport {
+    /// 要求包含密码但本机无法解密时的提示，此时备份不含密码
+    warning: Option<String>,
+}
+
+/// 导出备份命令
+/// 将偏好设置、子邮箱、IMAP配置和账户列表写入一个JSON文件，用于迁移到其他电脑
+/// 
+/// 密码的三层加密与本机机器ID绑定，无法直接迁移；`include_password` 为true时
+/// 在本机解密后以明文写入备份，任何拿到该文件的人都能看到密码，请妥善保管。
+/// 解密失败时仍导出其余内容，并在结果的 `warning` 中说明备份不含密码
+/// 
+/// # 参数
+/// * `path` - 备份文件路径
+/// * `include_password` - 是否包含明文密码，默认不包含
+#[tauri::command]
+async fn export_backup(
+    app: tauri::AppHandle,
+    path: String,
+    include_password: Option<bool>,
+) -> Result<BackupExport, String> {
+    let include_password = include_password.unwrap_or(false);
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    let preferences = store.get("preferences")
+        .and_then(|value| serde_json::from_value(value).ok());
+    let sub_emails = store.get("sub_emails")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    let imap_config = load_imap_config(&app);
+    
+    let mut accounts = Vec::new();
+    let mut warning = None;
+    if let Some(session) = store.get("session")
+        .and_then(|value| serde_json::from_value::<AuthSession>(value).ok())
+    {
+        let password = if include_password {
+            match crypto::load_and_decrypt_password(&session.email, &imap_config.server) {
+                Ok(password) => Some(password),
+                Err(e) => {
+                    log::warn!("导出备份时解密密码失败: {}", e);
+                    warning = Some(tr("无法在本机解密密码，备份中不包含密码").to_string());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        accounts.push(BackupAccount {
+            email: session.email,
+            password,
+        });
+    }
+    
+    let has_password = accounts.iter().any(|account| account.password.is_some());
+    let bundle = BackupBundle {
+        version: BACKUP_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        warning: has_password.then(|| tr("此备份包含明文密码，请妥善保管").to_string()),
+        preferences,
+        sub_emails,
+        accounts,
+        imap_config: Some(imap_config),
+    };
+    
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("{}: {}", tr("导出备份失败"), e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("{}: {}", tr("导出备份失败"), e))?;
+    
+    Ok(BackupExport { warning })
+}
+
+/// 导入备份命令
+/// 恢复备份中的偏好设置、子邮箱和IMAP配置；备份包含密码时在本机重新加密保存，
+/// 导入后仍需重新登录
+/// 
+/// # 参数
+/// * `path` - 备份文件路径
+#[tauri::command]
+async fn import_backup(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("{}: {}", tr("导入备份失败"), e))?;
+    let bundle: BackupBundle = serde_json::from_str(&content)
+        .map_err(|e| format!("{}: {}", tr("备份文件无效"), e))?;
+    if bundle.version > BACKUP_VERSION {
+        return Err(format!("{}: {}", tr("备份文件版本过新"), bundle.version));
+    }
+    
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("{}: {}", tr("无法访问存储"), e))?;
+    
+    if let Some(preferences) = &bundle.preferences {
+        store.set("preferences", serde_json::to_value(preferences).unwrap());
+    }
+    store.set("sub_emails", serde_json::to_value(&bundle.sub_emails).unwrap());
+    if let Some(config) = &bundle.imap_config {
+        store.set("imap_config", serde_json::to_value(config).unwrap());
+    }
+    
+    // 使用本机的机器ID重新加密密码
+    let server = bundle.imap_config.unwrap_or_else(|| load_imap_config(&app)).server;
+    for account in &bundle.accounts {
+        if let Some(password) = &account.password {
+            crypto::encrypt_and_save_password(password, &account.email, &server)
+                .map_err(|e| format!("{}: {}", tr("保存密码失败"), e))?;
+        }
+    }
+    
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("{}: {}", tr("导入备份失败"), e))?;
+    
+    Ok(())
+}
+
+/// 保存用户偏好设置到加密存储
+#[tauri::command]
+async fn save_preferences(
+    app: tauri::AppHandle,
+    preferences: UserPreferences,
+) -> Result<(), String> {
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    store.set("preferences", serde_json::to_value(&preferences).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+    
+    apply_runtime_preferences(&app, &preferences);
+    Ok(())
+}
+
+/// 从加密存储加载用户偏好设置
+#[tauri::command]
+async fn load_preferences(app: tauri::AppHandle) -> Result<Option<UserPreferences>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    match store.get("preferences") {
+        Some(value) => match serde_json::from_value::<UserPreferences>(value) {
+            Ok(preferences) => Ok(Some(preferences)),
+            // 字段类型不兼容时使用默认值，存储中的数据保持不变，直到用户再次保存
+            Err(e) => {
+                log::warn!("偏好设置数据无效，使用默认值: {}", e);
+                Ok(Some(UserPreferences::default()))
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+/// 读取单个偏好设置字段
+/// 
+/// # 参数
+/// * `key` - 偏好设置字段名（与前端一致的驼峰命名，如 `autoLogin`）
+#[tauri::command]
+async fn get_preference(
+    app: tauri::AppHandle,
+    key: String,
+) -> Result<Option<serde_json::Value>, String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    let preferences: UserPreferences = match store.get("preferences") {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to deserialize preferences: {}", e))?,
+        None => UserPreferences::default(),
+    };
+    
+    let value = serde_json::to_value(&preferences)
+        .map_err(|e| format!("Failed to serialize preferences: {}", e))?;
+    
+    Ok(value.get(&key).cloned())
+}
+
+/// 原子地更新单个偏好设置字段
+/// 在锁内将字段合并到已存储的偏好设置中，无需前端先读取整个结构再整体覆盖
+/// 
+/// # 参数
+/// * `key` - 偏好设置字段名（如 `autoLogin`）
+/// * `value` - 新的字段值
+#[tauri::command]
+async fn set_preference(
+    app: tauri::AppHandle,
+    key: String,
+    value: serde_json::Value,
+) -> Result<UserPreferences, String> {
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    
+    // 读取当前偏好设置，尚未保存过时以默认值为基础
+    let current: UserPreferences = match store.get("preferences") {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to deserialize preferences: {}", e))?,
+        None => UserPreferences::default(),
+    };
+    
+    let mut merged = serde_json::to_value(&current)
+        .map_err(|e| format!("Failed to serialize preferences: {}", e))?;
+    
+    match merged.get_mut(&key) {
+        Some(field) => *field = value,
+        None => return Err(format!("{}: {}", tr("未知的偏好设置项"), key)),
+    }
+    
+    // 反序列化一次以校验字段类型
+    let preferences: UserPreferences = serde_json::from_value(merged)
+        .map_err(|e| format!("{} {}: {}", tr("偏好设置项的值无效"), key, e))?;
+    
+    store.set("preferences", serde_json::to_value(&preferences).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+    
+    apply_runtime_preferences(&app, &preferences);
+    Ok(preferences)
+}
+
+/// 将偏好设置中运行时生效的项（刷新间隔、日志级别）同步到后台任务和日志记录器
+fn apply_runtime_preferences(app: &tauri::AppHandle, preferences: &UserPreferences) {
+    let interval_ms = preferences.auto_refresh_interval.max(0) as u64;
+    app.state::<refresh::RefreshScheduler>().set_interval(interval_ms);
+    logging::set_level(preferences.log_level);
+}
+
+/// 设置自动刷新间隔命令
+/// 同时更新偏好设置和正在运行的后台刷新任务，新间隔立即生效；为0时暂停自动刷新
+/// 
+/// # 参数
+/// * `secs` - 刷新间隔（秒），为负数或换算为毫秒后溢出时返回错误
+#[tauri::command]
+async fn set_refresh_interval(app: tauri::AppHandle, secs: i64) -> Result<UserPreferences, String> {
+    let interval_ms = secs
+        .checked_mul(1000)
+        .filter(|ms| *ms >= 0)
+        .ok_or_else(|| format!("{} autoRefreshInterval: {}", tr("偏好设置项的值无效"), secs))?;
+    
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut preferences = current_preferences(&app);
+    preferences.auto_refresh_interval = interval_ms;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("preferences", serde_json::to_value(&preferences).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+    
+    apply_runtime_preferences(&app, &preferences);
+    Ok(preferences)
+}
+
+/// 获取收件箱的新邮件，有新邮件时发送 `new-emails` 事件并执行过滤规则
+/// 与其他刷新互斥执行
+async fn refresh_inbox(app: &tauri::AppHandle) -> Result<NewMessagesResult, String> {
+    let scheduler = app.state::<refresh::RefreshScheduler>();
+    let _guard = scheduler.exclusive().await;
+    
+    let result = fetch_new_since(app.clone(), None).await?;
+    if !result.emails.is_empty() {
+        let _ = app.emit("new-emails", result.clone());
+        // 有新邮件时执行过滤规则
+        match apply_filters(app.clone(), None).await {
+            Ok(applied) if !applied.is_empty() => {
+                let _ = app.emit("filters-applied", applied);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("执行过滤规则失败: {}", e),
+        }
+    }
+    
+    Ok(result)
+}
+
+/// 设置日志级别命令
+/// 同时更新偏好设置和日志记录器，立即生效并在重启后保持；
+/// `debug` 级别会记录IMAP协议跟踪，排查问题后应调回较低的级别
+/// 
+/// # 参数
+/// * `level` - `error`、`warn`（默认）、`info` 或 `debug`
+#[tauri::command]
+async fn set_log_level(app: tauri::AppHandle, level: logging::LogLevel) -> Result<UserPreferences, String> {
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut preferences = current_preferences(&app);
+    preferences.log_level = level;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("preferences", serde_json::to_value(&preferences).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+    
+    apply_runtime_preferences(&app, &preferences);
+    Ok(preferences)
+}
+
+/// 保存关闭按钮的行为，用于询问对话框中的"不再询问"
+async fn save_close_action(app: &tauri::AppHandle, action: CloseAction) -> Result<(), String> {
+    let lock = app.state::<PreferencesLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut preferences = current_preferences(app);
+    preferences.close_action = action;
+    
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("preferences", serde_json::to_value(&preferences).unwrap());
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save preferences: {}", e))
+}
+
+/// 退出应用命令
+/// 
+/// # 参数
+/// * `remember` - 为true时将关闭按钮的行为保存为退出，之后不再询问
+#[tauri::command]
+async fn quit_app(app: tauri::AppHandle, remember: Option<bool>) -> Result<(), String> {
+    if remember.unwrap_or(false) {
+        save_close_action(&app, CloseAction::Quit).await?;
+    }
+    app.exit(0);
+    Ok(())
+}
+
+/// 隐藏主窗口到托盘命令
+/// 
+/// # 参数
+/// * `remember` - 为true时将关闭按钮的行为保存为隐藏到托盘，之后不再询问
+#[tauri::command]
+async fn hide_to_tray(app: tauri::AppHandle, remember: Option<bool>) -> Result<(), String> {
+    if remember.unwrap_or(false) {
+        save_close_action(&app, CloseAction::MinimizeToTray).await?;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        window.hide().map_err(|e| format!("Failed to hide window: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 处理主窗口的关闭按钮：按偏好设置 `closeAction` 退出、隐藏到托盘或通知界面询问
+fn handle_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    let tauri::WindowEvent::CloseRequested { api, .. } = event else {
+        return;
+    };
+    if window.label() != "main" {
+        return;
+    }
+    
+    match current_preferences(window.app_handle()).close_action {
+        CloseAction::Quit => {}
+        CloseAction::MinimizeToTray => {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+        CloseAction::Ask => {
+            api.prevent_close();
+            let _ = window.emit("close-requested", ());
+        }
+    }
+}
+
+/// 后台刷新：定时获取收件箱的新邮件，并更新未读数
+/// 未登录或获取失败时跳过本次刷新
+async fn background_refresh(app: tauri::AppHandle) {
+    if load_session_info(&app).is_err() {
+        return;
+    }
+    
+    if let Err(e) = refresh_inbox(&app).await {
+        log::warn!("后台刷新失败: {}", e);
+    }
+    
+    if let Err(e) = refresh_unread_counts(&app).await {
+        log::warn!("更新未读数失败: {}", e);
+    }
+}
+
+/// 未登录或服务器不支持IDLE时重新检查的间隔
+const IDLE_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// IDLE连接结束的原因（连接出错以外）
+enum IdleEnd {
+    /// 服务器不支持IDLE，只依赖定时刷新
+    Unsupported,
+    /// 已退出登录或切换了账户
+    SessionChanged,
+}
+
+/// IDLE推送循环
+/// 登录后在收件箱上保持IDLE，服务器通知变化时执行一次后台刷新。
+/// 连接断开后按带抖动的指数退避重连，重连成功后重新选择收件箱并刷新一次，补上断开期间到达的邮件
+/// 
+/// IDLE连接占用连接池的一个并发名额；并发上限为1时不使用IDLE，只依赖定时刷新
+async fn idle_loop(app: tauri::AppHandle) {
+    if app.state::<ImapPool>().max_connections() < 2 {
+        log::info!("并发连接数上限为1，不使用IDLE");
+        return;
+    }
+    let mut backoff = idle::Backoff::default();
+    // 不支持IDLE的账户，切换账户后重新检查
+    let mut unsupported: Option<String> = None;
+    
+    loop {
+        let (session, password) = match load_credentials(&app) {
+            Ok(credentials) if unsupported.as_deref() != Some(credentials.0.email.as_str()) => credentials,
+            _ => {
+                async_std::task::sleep(IDLE_RECHECK_INTERVAL).await;
+                continue;
+            }
+        };
+        
+        match idle_session(&app, &session.email, &password, &mut backoff).await {
+            Ok(IdleEnd::Unsupported) => {
+                log::info!("服务器不支持IDLE，使用定时刷新");
+                unsupported = Some(session.email);
+            }
+            Ok(IdleEnd::SessionChanged) => backoff.reset(),
             Err(e) => {
-                eprintln!("获取邮件时出错: {}", e);
+                let delay = backoff.next_delay();
+                log::warn!("IDLE连接断开，{}秒后重连: {}", delay.as_secs(), e);
+                async_std::task::sleep(delay).await;
             }
         }
     }
+}
+
+/// 建立IDLE连接并保持IDLE，直到连接出错、服务器不支持IDLE或会话变化
+/// 连接失败时连接状态为离线，已连接后断开时为重新连接
+async fn idle_session(
+    app: &tauri::AppHandle,
+    account: &str,
+    password: &str,
+    backoff: &mut idle::Backoff,
+) -> Result<IdleEnd, String> {
+    let config = load_imap_config(app);
+    let timeout = std::time::Duration::from_secs(config.operation_timeout_secs);
     
-    // 显式释放 messages
-    drop(messages);
+    // IDLE连接与池中的连接共用并发名额，连接关闭（函数返回）时释放
+    let pool = app.state::<ImapPool>();
+    let _permit = pool.reserve().await;
     
-    // 登出
-    imap_session
-        .logout()
-        .await
-        .map_err(|e| format!("登出失败: {}", e))?;
+    connection_status::begin_connect(app, account);
+    let connect = async {
+        let mut imap_session = imap_pool::connect(account, password, &config).await?;
+        let supported = idle::supports_idle(&mut imap_session).await?;
+        if supported {
+            // 只读打开，IDLE不需要修改邮件
+            let inbox = discover_inbox(app).await;
+            imap_session
+                .examine(utf7::encode(&inbox))
+                .await
+                .map_err(|e| format!("{} {}: {}", tr("无法打开文件夹"), inbox, e))?;
+        }
+        Ok::<_, String>((imap_session, supported))
+    };
+    let (mut imap_session, supported) = match async_std::future::timeout(timeout, connect).await {
+        Ok(Ok(connected)) => connected,
+        Ok(Err(e)) => {
+            connection_status::update(app, account, ConnectionState::Offline, Some(e.clone()));
+            return Err(e);
+        }
+        Err(_) => {
+            let error = AppError::OperationTimeout(config.operation_timeout_secs).to_string();
+            connection_status::update(app, account, ConnectionState::Offline, Some(error.clone()));
+            return Err(error);
+        }
+    };
+    if !supported {
+        let _ = imap_session.logout().await;
+        return Ok(IdleEnd::Unsupported);
+    }
     
-    Ok(emails)
+    connection_status::update(app, account, ConnectionState::Connected, None);
+    // 重连成功：断开期间到达的邮件需要补上
+    if backoff.failures() > 0 {
+        background_refresh(app.clone()).await;
+    }
+    backoff.reset();
+    
+    let idle_stop = app.state::<idle::IdleStop>();
+    loop {
+        if load_session_info(app).map(|session| session.email).as_deref() != Ok(account) {
+            let _ = imap_session.logout().await;
+            return Ok(IdleEnd::SessionChanged);
+        }
+        
+        // 结束IDLE（DONE）在无声断开的连接上可能不返回，整体设置超时
+        let wait = idle::wait(imap_session, idle::RENEW_INTERVAL, &idle_stop);
+        let (session, changed) = match async_std::future::timeout(idle::RENEW_INTERVAL + timeout, wait).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                connection_status::update(app, account, ConnectionState::Reconnecting, Some(e.clone()));
+                return Err(e);
+            }
+            Err(_) => {
+                let error = AppError::OperationTimeout(config.operation_timeout_secs).to_string();
+                connection_status::update(app, account, ConnectionState::Reconnecting, Some(error.clone()));
+                return Err(error);
+            }
+        };
+        imap_session = session;
+        
+        if changed {
+            background_refresh(app.clone()).await;
+        }
+    }
 }
 
-/// 发送邮件命令
-/// 简化版本：直接返回成功，不实际发送邮件
+/// 立即刷新命令
+/// 不等待下一次定时刷新，立即获取收件箱的新邮件（同样发送 `new-emails` 事件），
+/// 之后定时刷新从现在重新计时。正在进行的定时刷新会先完成，不会重复报告同一批邮件
 #[tauri::command]
-async fn send_email(
-    _app: tauri::AppHandle,
-    _to: String,
-    _subject: String,
-    _body: String,
-) -> Result<(), String> {
-    // 注意：这是简化实现，仅用于子邮箱生成
-    // 实际的邮件发送需要SMTP服务器支持
-    // 目前直接返回成功，子邮箱会被标记为已创建
-    Ok(())
+async fn force_refresh(app: tauri::AppHandle) -> Result<NewMessagesResult, String> {
+    let result = refresh_inbox(&app).await;
+    app.state::<refresh::RefreshScheduler>().restart();
+    result
 }
 
-/// 保存会话到加密存储
+/// 设置后端错误信息的语言
+/// 设置保存在存储中，下次启动时自动恢复
+/// 
+/// # 参数
+/// * `locale` - 语言标签，支持 `zh-CN` 和 `en`
 #[tauri::command]
-async fn save_session(
-    app: tauri::AppHandle,
-    session: AuthSession,
-) -> Result<(), String> {
+async fn set_locale(app: tauri::AppHandle, locale: String) -> Result<(), String> {
+    let parsed = i18n::Locale::parse(&locale)
+        .ok_or_else(|| format!("{}: {}", tr("不支持的语言"), locale))?;
+    
+    i18n::set_locale(parsed);
+    
     let store = app.store("store.json")
         .map_err(|e| format!("Failed to get store: {}", e))?;
     
-    store.set("session", serde_json::to_value(&session).unwrap());
-    store.save()
-        .map_err(|e| format!("Failed to save session: {}", e))?;
+    store.set("locale", serde_json::Value::String(parsed.tag().to_string()));
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save locale: {}", e))?;
     
     Ok(())
 }
 
-/// 保存密码到加密存储（用于后续IMAP操作）
-/// 使用三层加密保护密码安全
+/// 读取IMAP服务器配置
 #[tauri::command]
-async fn save_password(
+async fn get_imap_config(app: tauri::AppHandle) -> Result<ImapConfig, String> {
+    Ok(load_imap_config(&app))
+}
+
+/// 保存IMAP服务器配置
+/// 空闲超时在下次启动应用时生效
+#[tauri::command]
+async fn save_imap_config(
     app: tauri::AppHandle,
-    password: String,
+    config: ImapConfig,
 ) -> Result<(), String> {
-    // 从存储中获取当前会话以获取邮箱地址
     let store = app.store("store.json")
         .map_err(|e| format!("Failed to get store: {}", e))?;
     
-    let session_value = store.get("session")
-        .ok_or("未找到会话信息")?;
-    
-    let session: AuthSession = serde_json::from_value(session_value.clone())
-        .map_err(|e| format!("会话数据无效: {}", e))?;
-    
-    // 使用三层加密保存密码
-    crypto::encrypt_and_save_password(&password, &session.email)
-        .map_err(|e| format!("保存密码失败: {}", e))?;
-    
-    // 同时保存到store（用于向后兼容）
-    store.set("password", serde_json::to_value(&password).unwrap());
-    store.save()
-        .map_err(|e| format!("Failed to save password to store: {}", e))?;
+    store.set("imap_config", serde_json::to_value(&config).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save IMAP config: {}", e))?;
     
     Ok(())
 }
 
-/// 从加密存储加载会话
+/// IMAP服务器诊断命令
+/// 连接服务器并返回原始问候行和登录前的CAPABILITY响应，不进行认证，
+/// 用于排查登录失败的原因（如服务器要求STARTTLS或声明了LOGINDISABLED）
+/// 
+/// # 参数
+/// * `server` - IMAP服务器地址
+/// * `port` - IMAP服务器端口
+/// * `security` - `tls`（默认）或 `starttls`（明文连接，不升级）
 #[tauri::command]
-async fn load_session(app: tauri::AppHandle) -> Result<Option<AuthSession>, String> {
-    let store = app.store("store.json")
-        .map_err(|e| format!("Failed to get store: {}", e))?;
+async fn imap_probe(
+    app: tauri::AppHandle,
+    server: String,
+    port: u16,
+    security: Option<imap_pool::ImapSecurity>,
+) -> Result<imap_pool::ServerProbe, String> {
+    // 最低TLS版本和超时沿用已保存的配置
+    let config = ImapConfig {
+        server: server.trim().to_string(),
+        port,
+        ..load_imap_config(&app)
+    };
+    let timeout = std::time::Duration::from_secs(config.operation_timeout_secs);
     
-    match store.get("session") {
-        Some(value) => {
-            let session: AuthSession = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to deserialize session: {}", e))?;
-            Ok(Some(session))
-        }
-        None => Ok(None),
+    match async_std::future::timeout(timeout, imap_pool::probe(&config, security.unwrap_or_default())).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(AppError::OperationTimeout(config.operation_timeout_secs).into()),
     }
 }
 
-/// 从加密存储加载密码
-/// 使用三层解密恢复密码
+/// 读取SMTP服务器配置
 #[tauri::command]
-async fn load_password(app: tauri::AppHandle) -> Result<Option<String>, String> {
-    // 从存储中获取当前会话以获取邮箱地址
-    let store = app.store("store.json")
-        .map_err(|e| format!("Failed to get store: {}", e))?;
-    
-    let session_value = match store.get("session") {
-        Some(v) => v,
-        None => return Ok(None),
-    };
-    
-    let session: AuthSession = serde_json::from_value(session_value.clone())
-        .map_err(|e| format!("会话数据无效: {}", e))?;
-    
-    // 尝试从三层加密存储中读取密码
-    match crypto::load_and_decrypt_password(&session.email) {
-        Ok(password) => Ok(Some(password)),
-        Err(_) => {
-            // 如果三层加密读取失败，尝试从旧的store读取（向后兼容）
-            match store.get("password") {
-                Some(value) => {
-                    let password: String = serde_json::from_value(value.clone())
-                        .map_err(|e| format!("密码数据无效: {}", e))?;
-                    Ok(Some(password))
-                }
-                None => Ok(None),
-            }
-        }
-    }
+async fn get_smtp_config(app: tauri::AppHandle) -> Result<SmtpConfig, String> {
+    Ok(load_smtp_config(&app))
 }
 
-/// 保存子邮箱列表到加密存储
+/// 保存SMTP服务器配置，之后的发送立即使用新配置
+/// 
+/// # 参数
+/// * `config` - 服务器地址不能为空，端口不能为0
 #[tauri::command]
-async fn save_sub_emails(
+async fn set_smtp_config(
     app: tauri::AppHandle,
-    sub_emails: Vec<SubEmail>,
+    config: SmtpConfig,
 ) -> Result<(), String> {
+    let server = config.server.trim();
+    if server.is_empty() || server.contains(char::is_whitespace) {
+        return Err(format!("{}: {}", tr("SMTP服务器地址无效"), config.server));
+    }
+    if config.port == 0 {
+        return Err(format!("{}: {}", tr("SMTP端口无效"), config.port));
+    }
+    let config = SmtpConfig {
+        server: server.to_string(),
+        ..config
+    };
+    
     let store = app.store("store.json")
         .map_err(|e| format!("Failed to get store: {}", e))?;
     
-    store.set("sub_emails", serde_json::to_value(&sub_emails).unwrap());
-    store.save()
-        .map_err(|e| format!("Failed to save sub emails: {}", e))?;
+    store.set("smtp_config", serde_json::to_value(&config).unwrap());
+    persist::save(&app, &store, "store.json")
+        .map_err(|e| format!("Failed to save SMTP config: {}", e))?;
     
     Ok(())
 }
 
-/// 从加密存储加载子邮箱列表
+/// 调试命令：返回连接池中空闲连接数量，用于确认连接复用是否生效
 #[tauri::command]
-async fn load_sub_emails(app: tauri::AppHandle) -> Result<Vec<SubEmail>, String> {
-    let store = app.store("store.json")
-        .map_err(|e| format!("Failed to get store: {}", e))?;
-    
-    match store.get("sub_emails") {
-        Some(value) => {
-            let sub_emails: Vec<SubEmail> = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to deserialize sub emails: {}", e))?;
-            Ok(sub_emails)
-        }
-        None => Ok(vec![]),
-    }
+async fn debug_pool_size(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let pool = app.state::<ImapPool>();
+    Ok(serde_json::json!({
+        "size": pool.size().await,
+        "idleTimeoutSecs": pool.idle_time().as_secs(),
+        "maxConnections": pool.max_connections(),
+    }))
 }
 
-/// 保存用户偏好设置到加密存储
+/// 性能统计命令：连接池的复用率、FETCH平均耗时和邮件平均解析耗时
+/// 从应用启动开始累计
 #[tauri::command]
-async fn save_preferences(
-    app: tauri::AppHandle,
-    preferences: UserPreferences,
-) -> Result<(), String> {
-    let store = app.store("store.json")
-        .map_err(|e| format!("Failed to get store: {}", e))?;
-    
-    store.set("preferences", serde_json::to_value(&preferences).unwrap());
-    store.save()
-        .map_err(|e| format!("Failed to save preferences: {}", e))?;
-    
-    Ok(())
+async fn perf_stats() -> Result<perf::PerfStats, String> {
+    Ok(perf::snapshot())
 }
 
-/// 从加密存储加载用户偏好设置
+/// NOOP往返延迟统计（毫秒）
+#[derive(Debug, Clone, Serialize)]
+struct LatencyStats {
+    #[serde(rename = "minMs")]
+    min_ms: f64,
+    #[serde(rename = "avgMs")]
+    avg_ms: f64,
+    #[serde(rename = "maxMs")]
+    max_ms: f64,
+    samples: u32,
+}
+
+/// 延迟测量的NOOP次数
+const LATENCY_SAMPLES: u32 = 5;
+
+/// 测量IMAP往返延迟
+/// 使用连接池中的会话连续执行若干次NOOP，不包含建立连接的耗时，
+/// 用于区分服务器响应慢与本地解析慢
 #[tauri::command]
-async fn load_preferences(app: tauri::AppHandle) -> Result<Option<UserPreferences>, String> {
-    let store = app.store("store.json")
-        .map_err(|e| format!("Failed to get store: {}", e))?;
-    
-    match store.get("preferences") {
-        Some(value) => {
-            let preferences: UserPreferences = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to deserialize preferences: {}", e))?;
-            Ok(Some(preferences))
+async fn measure_latency(app: tauri::AppHandle) -> Result<LatencyStats, String> {
+    with_imap_session(&app, |imap_session| Box::pin(async move {
+        let mut durations = Vec::with_capacity(LATENCY_SAMPLES as usize);
+        for _ in 0..LATENCY_SAMPLES {
+            let started = std::time::Instant::now();
+            imap_session
+                .noop()
+                .await
+                .map_err(|e| format!("{}: {}", tr("网络连接异常"), e))?;
+            durations.push(started.elapsed().as_secs_f64() * 1000.0);
         }
-        None => Ok(None),
-    }
+        
+        Ok(LatencyStats {
+            min_ms: durations.iter().cloned().fold(f64::INFINITY, f64::min),
+            avg_ms: durations.iter().sum::<f64>() / durations.len() as f64,
+            max_ms: durations.iter().cloned().fold(0.0, f64::max),
+            samples: LATENCY_SAMPLES,
+        })
+    })).await
 }
 
 /// 记录错误到本地日志文件
@@ -585,12 +5972,221 @@ async fn log_error(app: tauri::AppHandle, entry: ErrorLogEntry) -> Result<(), St
     Ok(())
 }
 
+/// 导出诊断包命令
+/// 将最近的错误日志和后端日志、keyring状态、IMAP/SMTP配置、应用和系统版本以及连接池和性能统计
+/// 打包为zip文件，用于反馈问题
+/// 
+/// 配置中本身不含密码；本机保存的当前账户密码（包括内存中的密码和尚未迁移的旧版本明文密码）
+/// 在写入每个文件前都会被替换，日志中即使意外出现密码也不会进入诊断包
+/// 
+/// # 参数
+/// * `path` - zip文件路径
+/// * `hash_emails` - 是否把邮箱地址的用户名部分替换为哈希，默认不替换
+#[tauri::command]
+async fn export_diagnostics(app: tauri::AppHandle, path: String, hash_emails: Option<bool>) -> Result<(), String> {
+    let imap_config = load_imap_config(&app);
+    let store = app.store("store.json").ok();
+    let account = store.as_ref()
+        .and_then(|store| store.get("session"))
+        .and_then(|value| serde_json::from_value::<AuthSession>(value).ok())
+        .map(|session| session.email);
+    
+    let mut passwords: Vec<String> = account
+        .iter()
+        .flat_map(|email| crypto::stored_passwords(email, &imap_config.server))
+        .map(|(_, password)| password)
+        .collect();
+    passwords.extend(
+        app.state::<MemoryCredentials>().0.lock().unwrap()
+            .as_ref()
+            .map(|(_, password)| password.clone()),
+    );
+    // 旧版本明文保存的密码，迁移失败时仍留在store中
+    passwords.extend(
+        store.as_ref()
+            .and_then(|store| store.get("password"))
+            .and_then(|value| value.as_str().map(str::to_string)),
+    );
+    let mut bundle = diagnostics::Bundle::new(diagnostics::Redactor::new(passwords, hash_emails.unwrap_or(false)));
+    
+    bundle.add_json("info.json", &serde_json::json!({
+        "generatedAt": chrono::Utc::now().to_rfc3339(),
+        "appVersion": app.package_info().version.to_string(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "account": account,
+        "logLevel": current_preferences(&app).log_level,
+    }))?;
+    bundle.add_json("keyring.json", &serde_json::to_value(crypto::keyring_info()).unwrap())?;
+    bundle.add_json("config.json", &serde_json::json!({
+        "imap": imap_config,
+        "smtp": load_smtp_config(&app),
+    }))?;
+    let pool = app.state::<ImapPool>();
+    bundle.add_json("stats.json", &serde_json::json!({
+        "pool": {
+            "size": pool.size().await,
+            "idleTimeoutSecs": pool.idle_time().as_secs(),
+            "maxConnections": pool.max_connections(),
+        },
+        "perf": perf::snapshot(),
+        "connections": app.state::<ConnectionStatusTracker>().snapshot(),
+    }))?;
+    
+    // 日志文件名中带日期（error_<日期>.log、backend_<日期>.log），按日期取最近的
+    let log_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("logs");
+    let mut logs: Vec<(String, std::path::PathBuf)> = std::fs::read_dir(&log_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path()))
+                .filter(|(name, _)| name.ends_with(".log"))
+                .collect()
+        })
+        .unwrap_or_default();
+    logs.sort_by(|(a, _), (b, _)| {
+        let date = |name: &str| name.rsplit('_').next().unwrap_or_default().to_string();
+        date(b).cmp(&date(a)).then_with(|| a.cmp(b))
+    });
+    for (name, path) in logs.into_iter().take(diagnostics::MAX_LOG_FILES) {
+        match diagnostics::read_log_tail(&path) {
+            Ok(content) => bundle.add_text(&format!("logs/{}", name), &content)?,
+            Err(e) => log::warn!("读取日志文件 {} 失败: {}", name, e),
+        }
+    }
+    
+    bundle.write(std::path::Path::new(&path))
+}
+
+/// 退出时登出空闲IMAP连接的总等待时间
+const SHUTDOWN_LOGOUT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 清理连接池中空闲超时连接的间隔
+const POOL_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .setup(|app| {
+            // 先以默认级别安装日志记录器，读取偏好设置后再调整
+            logging::init(
+                app.path().app_data_dir().ok().map(|dir| dir.join("logs")),
+                logging::LogLevel::default(),
+            );
+            
+            // 在任何代码读取存储之前检查文件是否损坏
+            persist::recover(app.handle(), "store.json");
+            persist::recover(app.handle(), email_cache::CACHE_FILE);
+            // 关闭自动保存后创建存储，之后的 `app.store()` 都返回这里创建的实例；
+            // 默认的自动保存会在每次set后由插件直接覆盖写入，绕过 `persist::save` 的原子写入
+            for name in ["store.json", email_cache::CACHE_FILE] {
+                if let Err(e) = app.store_builder(name).disable_auto_save().build() {
+                    log::error!("加载 {} 失败: {}", name, e);
+                }
+            }
+            migrate_store(app.handle());
+            migrate_legacy_password(app.handle());
+            logging::set_level(current_preferences(app.handle()).log_level);
+            
+            // 按配置的空闲超时和并发上限创建IMAP连接池
+            let config = load_imap_config(app.handle());
+            app.manage(ImapPool::with_limits(
+                std::time::Duration::from_secs(config.idle_timeout_secs),
+                config.max_connections,
+            ));
+            app.manage(PreferencesLock::default());
+            app.manage(SpecialFoldersCache::default());
+            app.manage(SentKeys::default());
+            app.manage(MemoryCredentials::default());
+            app.manage(prefetch::PrefetchCache::default());
+            app.manage(outbox::OutboxLock::default());
+            app.manage(snooze::SnoozeLock::default());
+            app.manage(read_position::ReadPositionLock::default());
+            app.manage(contacts::ContactsLock::default());
+            app.manage(drafts::DraftsLock::default());
+            app.manage(idle::IdleStop::default());
+            app.manage(archive::ArchiveLock::default());
+            app.manage(unread::UnreadCountsCache::default());
+            app.manage(ConnectionStatusTracker::default());
+            
+            // 启动后台刷新任务，间隔取自偏好设置
+            let interval_ms = current_preferences(app.handle()).auto_refresh_interval.max(0) as u64;
+            let (scheduler, refresh_loop) = refresh::channel(interval_ms);
+            app.manage(scheduler);
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(refresh_loop.run(move || background_refresh(handle.clone())));
+            
+            // 定期清理已发送文件夹中过期的邮件（需在偏好设置中启用）
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    async_std::task::sleep(SENT_RETENTION_INTERVAL).await;
+                    if load_session_info(&handle).is_err() {
+                        continue;
+                    }
+                    if let Err(e) = purge_expired_sent(&handle).await {
+                        log::warn!("清理已发送邮件失败: {}", e);
+                    }
+                }
+            });
+            
+            // 后台发送发件箱中的邮件
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    drain_outbox(&handle).await;
+                    async_std::task::sleep(OUTBOX_POLL_INTERVAL).await;
+                }
+            });
+            
+            // 定期登出连接池中空闲超时的连接
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    async_std::task::sleep(POOL_CLEANUP_INTERVAL).await;
+                    let removed = handle.state::<ImapPool>().cleanup_expired().await;
+                    if removed > 0 {
+                        log::debug!("已登出空闲超时的IMAP连接: {} 个", removed);
+                    }
+                }
+            });
+            
+            // 收件箱IDLE推送，服务器不支持时只使用定时刷新
+            tauri::async_runtime::spawn(idle_loop(app.handle().clone()));
+            
+            // 唤醒到期的休眠邮件
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    wake_snoozed(&handle).await;
+                    async_std::task::sleep(SNOOZE_POLL_INTERVAL).await;
+                }
+            });
+            
+            // 启用自动登录时预热连接池，失败不影响启动
+            if current_preferences(app.handle()).auto_login {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = warm_pool(handle).await {
+                        log::warn!("预热连接池失败: {}", e);
+                    }
+                });
+            }
+            
+            // 恢复上次选择的错误信息语言
+            if let Some(locale) = app.store("store.json")
+                .ok()
+                .and_then(|store| store.get("locale"))
+                .and_then(|value| value.as_str().and_then(i18n::Locale::parse))
+            {
+                i18n::set_locale(locale);
+            }
+            
             // 创建系统托盘菜单
             let show_item = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
             let hide_item = MenuItem::with_id(app, "hide", "隐藏窗口", true, None::<&str>)?;
@@ -599,7 +6195,7 @@ pub fn run() {
             let menu = Menu::with_items(app, &[&show_item, &hide_item, &quit_item])?;
             
             // 创建系统托盘图标
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(unread::TRAY_ID)
                 .menu(&menu)
                 .icon(app.default_window_icon().unwrap().clone())
                 .on_menu_event(|app, event| {
@@ -640,21 +6236,527 @@ pub fn run() {
             
             Ok(())
         })
+        .on_window_event(handle_window_event)
         .invoke_handler(tauri::generate_handler![
             login,
             logout,
             fetch_emails,
+            fetch_email_envelopes,
+            sender_stats,
+            largest_messages,
+            fetch_unified,
+            load_cached_emails,
+            clear_cache,
+            fetch_new_since,
+            contact_suggestions,
+            expunge_folder,
+            apply_sent_retention,
+            folder_stats,
+            all_unread_counts,
+            list_flagged,
+            search_all_folders,
+            get_special_folders,
+            delete_email,
+            undelete_email,
+            trash_email,
+            set_flagged,
+            set_keyword,
+            get_flags,
+            sync_flags,
+            get_email,
+            prefetch_emails,
+            preview_email,
+            get_headers,
+            fetch_threads,
+            get_vacation_status,
+            fetch_by_ids,
+            get_structure,
+            download_attachment,
+            archive_email,
+            list_archived,
+            read_archived,
+            mark_junk,
+            mark_not_junk,
             send_email,
+            validate_address,
+            send_email_with_attachments,
+            resend_email,
+            sync_draft_to_server,
+            list_outbox,
+            test_smtp,
+            cancel_queued,
+            snooze_email,
+            list_snoozed,
+            unsnooze,
+            set_read_position,
+            get_read_position,
+            unsubscribe,
             save_session,
             save_password,
             load_session,
             load_password,
+            session_status,
+            warm_pool,
+            get_connection_status,
+            reset_connection,
+            diagnose_credential,
+            keyring_info,
+            check_credential_conflicts,
             save_sub_emails,
             load_sub_emails,
+            reconcile_sub_emails,
+            save_filters,
+            load_filters,
+            apply_filters,
+            export_backup,
+            import_backup,
             save_preferences,
             load_preferences,
-            log_error
+            set_refresh_interval,
+            set_log_level,
+            force_refresh,
+            get_preference,
+            set_preference,
+            set_locale,
+            quit_app,
+            hide_to_tray,
+            get_imap_config,
+            save_imap_config,
+            imap_probe,
+            get_smtp_config,
+            set_smtp_config,
+            debug_pool_size,
+            perf_stats,
+            measure_latency,
+            log_error,
+            export_diagnostics
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出前登出连接池中的空闲连接，避免服务器上残留会话占用连接数
+            if let tauri::RunEvent::Exit = event {
+                let pool = app.state::<ImapPool>();
+                let logged_out = tauri::async_runtime::block_on(pool.shutdown(SHUTDOWN_LOGOUT_TIMEOUT));
+                log::debug!("退出时已登出 {} 个IMAP连接", logged_out);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock_imap::{commands, literal, ScriptedStream};
+
+    /// 登录成功后执行 `script` 的会话，以及客户端写入内容的句柄
+    async fn scripted_session(script: &str) -> (async_imap::Session<ScriptedStream>, std::sync::Arc<std::sync::Mutex<Vec<u8>>>) {
+        let stream = ScriptedStream::new(format!("A0001 OK LOGIN completed\r\n{}", script));
+        let written = stream.written();
+        let session = imap_pool::login_over(stream, "user@2925.com", "secret").await.unwrap();
+        (session, written)
+    }
+
+    /// 序号 `seq` 的邮件的FETCH响应
+    fn fetch_response(seq: u32, uid: u32, flags: &str, internal_date: &str, message: &str) -> String {
+        format!(
+            "* {} FETCH (UID {} FLAGS ({}) INTERNALDATE \"{}\" BODY[] {})\r\n",
+            seq, uid, flags, internal_date, literal(message)
+        )
+    }
+
+    const SELECT_THREE: &str = "* 3 EXISTS\r\n* 0 RECENT\r\n* OK [UIDVALIDITY 7] UIDs valid\r\nA0002 OK [READ-WRITE] SELECT completed\r\n";
+    const CAPABILITY_NO_SORT: &str = "* CAPABILITY IMAP4rev1 UIDPLUS\r\nA0003 OK CAPABILITY completed\r\n";
+
+    #[async_std::test]
+    async fn login_sends_quoted_credentials() {
+        let (_session, written) = scripted_session("").await;
+        assert_eq!(commands(&written), vec!["LOGIN \"user@2925.com\" \"secret\""]);
+    }
+
+    #[async_std::test]
+    async fn login_rejected_is_auth_error() {
+        let stream = ScriptedStream::new("A0001 NO [AUTHENTICATIONFAILED] Invalid credentials\r\n");
+        let error = imap_pool::login_over(stream, "user@2925.com", "wrong").await.unwrap_err();
+        assert!(matches!(error, AppError::AuthRejected(_)), "{:?}", error);
+    }
+
+    #[async_std::test]
+    async fn login_connection_closed_is_network_error() {
+        let stream = ScriptedStream::new("");
+        let error = imap_pool::login_over(stream, "user@2925.com", "secret").await.unwrap_err();
+        assert!(matches!(error, AppError::Network(_)), "{:?}", error);
+    }
+
+    #[async_std::test]
+    async fn empty_inbox_returns_no_emails_without_fetching() {
+        let (mut session, written) = scripted_session("* 0 EXISTS\r\n* 0 RECENT\r\nA0002 OK [READ-WRITE] SELECT completed\r\n").await;
+        let emails = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        assert!(emails.is_empty());
+        assert_eq!(commands(&written)[1..], ["SELECT \"INBOX\""]);
+    }
+
+    #[async_std::test]
+    async fn fewer_than_fifty_messages_fetches_whole_range() {
+        let message = |n: u32| format!(
+            "From: sender{n}@example.com\r\nTo: user@2925.com\r\nSubject: Message {n}\r\nDate: Tue, 13 Oct 2026 0{n}:00:00 +0000\r\n\r\nbody {n}\r\n"
+        );
+        let script = format!(
+            "{}{}{}{}{}A0004 OK FETCH completed\r\n",
+            SELECT_THREE,
+            CAPABILITY_NO_SORT,
+            fetch_response(1, 101, "\\Seen", "13-Oct-2026 01:00:05 +0000", &message(1)),
+            fetch_response(2, 102, "", "13-Oct-2026 02:00:05 +0000", &message(2)),
+            fetch_response(3, 103, "\\Flagged", "13-Oct-2026 03:00:05 +0000", &message(3)),
+        );
+        let (mut session, written) = scripted_session(&script).await;
+        let emails = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        
+        assert_eq!(commands(&written)[3], "FETCH 1:3 (UID BODY.PEEK[] FLAGS INTERNALDATE)");
+        let ids: Vec<&str> = emails.iter().map(|email| email.id.as_str()).collect();
+        assert_eq!(ids, ["103", "102", "101"]);
+        assert_eq!(emails[0].subject, "Message 3");
+        assert!(emails[0].is_flagged && !emails[0].is_read);
+        assert!(emails[2].is_read);
+        assert_eq!(emails[2].body.trim(), "body 1");
+    }
+
+    #[async_std::test]
+    async fn large_folder_is_listed_with_one_fetch() {
+        let script = format!(
+            "* 120 EXISTS\r\n* 0 RECENT\r\nA0002 OK [READ-WRITE] SELECT completed\r\n{}A0004 OK FETCH completed\r\n",
+            CAPABILITY_NO_SORT,
+        );
+        let (mut session, written) = scripted_session(&script).await;
+        fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        
+        // 整个窗口在一条命令中获取，不按邮件逐封获取
+        assert_eq!(commands(&written)[1..], [
+            "SELECT \"INBOX\"",
+            "CAPABILITY",
+            "FETCH 71:120 (UID BODY.PEEK[] FLAGS INTERNALDATE)",
+        ]);
+    }
+
+    #[async_std::test]
+    async fn sorted_window_is_fetched_with_one_command() {
+        let message = |n: u32| format!("From: sender{n}@example.com\r\nSubject: Message {n}\r\n\r\nbody {n}\r\n");
+        let script = format!(
+            "{}* CAPABILITY IMAP4rev1 SORT\r\nA0003 OK CAPABILITY completed\r\n\
+             * SORT 103 101 102\r\nA0004 OK SORT completed\r\n{}{}{}A0005 OK FETCH completed\r\n",
+            SELECT_THREE,
+            fetch_response(1, 101, "", "13-Oct-2026 01:00:05 +0000", &message(1)),
+            fetch_response(2, 102, "", "13-Oct-2026 02:00:05 +0000", &message(2)),
+            fetch_response(3, 103, "", "13-Oct-2026 03:00:05 +0000", &message(3)),
+        );
+        let (mut session, written) = scripted_session(&script).await;
+        let emails = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        
+        let ids: Vec<&str> = emails.iter().map(|email| email.id.as_str()).collect();
+        assert_eq!(ids, ["103", "101", "102"]);
+        let commands = commands(&written);
+        assert_eq!(commands.iter().filter(|command| command.contains("FETCH")).count(), 1);
+        assert_eq!(commands.last().unwrap(), "UID FETCH 103,101,102 (UID BODY.PEEK[] FLAGS INTERNALDATE)");
+    }
+
+    #[async_std::test]
+    async fn stalled_smtp_server_times_out_as_unreachable() {
+        // 接受连接后不发送任何数据的服务器
+        let listener = async_std::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = async_std::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            async_std::task::sleep(std::time::Duration::from_secs(5)).await;
+            drop(stream);
+        });
+        
+        let config = SmtpConfig {
+            server: "127.0.0.1".to_string(),
+            port,
+            timeout_secs: 1,
+            ..SmtpConfig::default()
+        };
+        let recipients = ["someone@example.com".to_string()];
+        let started = std::time::Instant::now();
+        let error = smtp_send(&config, "user@2925.com", "secret", &recipients, "Subject: hi\r\n\r\nhi\r\n")
+            .await
+            .unwrap_err();
+        
+        assert!(started.elapsed() < std::time::Duration::from_secs(4));
+        // DATA之前超时，邮件肯定没有发出，可以用同一个幂等键重试
+        assert!(matches!(error, AppError::Connection(_)), "{:?}", error);
+        server.cancel().await;
+    }
+
+    #[test]
+    fn legacy_archive_entry_has_no_received_time() {
+        let entry: ArchivedEmail = serde_json::from_value(serde_json::json!({
+            "id": "a1", "from": "a@example.com", "subject": "s", "date": 1760400000000i64, "path": "/tmp/a1.eml.gz",
+        })).unwrap();
+        assert_eq!(entry.received_at, None);
+        
+        let value = serde_json::to_value(ArchivedEmail { received_at: Some(1760400005000), ..entry }).unwrap();
+        assert_eq!(value["receivedAt"], 1760400005000i64);
+        assert_eq!(value["date"], 1760400000000i64);
+    }
+
+    #[test]
+    fn message_id_must_be_uid() {
+        assert_eq!(parse_message_id(" 42 ").unwrap(), 42);
+        for invalid in ["", "1:*", "1,2", "../42", "-1"] {
+            let error = parse_message_id(invalid).unwrap_err();
+            assert!(error.contains(invalid), "{}", error);
+        }
+    }
+
+    #[async_std::test]
+    async fn non_ascii_search_term_is_sent_as_literal() {
+        let script = "* 3 EXISTS\r\nA0002 OK [READ-ONLY] EXAMINE completed\r\n\
+                      + Ready for literal data\r\n* SEARCH 5 7\r\nA0003 OK SEARCH completed\r\n\
+                      A0004 OK FETCH completed\r\n";
+        let (mut session, written) = scripted_session(script).await;
+        let criteria = text_search_criteria("已发送 \"报告\"").unwrap();
+        search_folder(&mut session, "INBOX", &criteria).await.unwrap();
+        
+        let term = "已发送 \"报告\"";
+        assert_eq!(commands(&written)[1..], [
+            "EXAMINE \"INBOX\"".to_string(),
+            format!("UID SEARCH CHARSET UTF-8 TEXT {{{}}}", term.len()),
+            term.to_string(),
+            "UID FETCH 7,5 (UID ENVELOPE FLAGS INTERNALDATE)".to_string(),
+        ]);
+    }
+
+    #[async_std::test]
+    async fn rejected_literal_search_is_error() {
+        let (mut session, _written) = scripted_session("A0002 NO [BADCHARSET (US-ASCII)] Unsupported charset\r\n").await;
+        let error = uid_search_utf8_text(&mut session, "报告").await.unwrap_err();
+        assert!(error.contains("Unsupported charset"), "{}", error);
+    }
+
+    #[test]
+    fn ascii_search_term_is_quoted() {
+        match text_search_criteria("say \"hi\"").unwrap() {
+            SearchCriteria::Ascii(criteria) => assert_eq!(criteria, "TEXT \"say \\\"hi\\\"\""),
+            other => panic!("{:?}", other),
+        }
+        assert!(text_search_criteria("报告\r\nA1 LOGOUT").is_err());
+    }
+
+    /// `charset` 编码、`transfer_encoding` 传输编码的纯文本邮件
+    fn encoded_message(text: &str, charset: &'static encoding_rs::Encoding, label: &str, transfer_encoding: &str) -> Vec<u8> {
+        use base64::Engine as _;
+        
+        let (bytes, _, _) = charset.encode(text);
+        let body = match transfer_encoding {
+            "base64" => base64::engine::general_purpose::STANDARD.encode(&bytes).into_bytes(),
+            _ => bytes.into_owned(),
+        };
+        let mut message = format!(
+            "From: sender@example.com\r\nSubject: test\r\nContent-Type: text/plain; charset={}\r\nContent-Transfer-Encoding: {}\r\n\r\n",
+            label, transfer_encoding
+        ).into_bytes();
+        message.extend_from_slice(&body);
+        message
+    }
+
+    #[test]
+    fn decodes_gb2312_base64_body() {
+        let message = encoded_message("你好，这是一封测试邮件。", encoding_rs::GBK, "gb2312", "base64");
+        let email = parse_email("1", &message, None).unwrap();
+        assert_eq!(email.body, "你好，这是一封测试邮件。");
+    }
+
+    #[test]
+    fn decodes_gbk_8bit_body() {
+        // 「镕」「瑢」在GBK中但不在GB2312中
+        let message = encoded_message("朱镕基 瑢", encoding_rs::GBK, "\"GBK\"", "8bit");
+        let email = parse_email("1", &message, None).unwrap();
+        assert_eq!(email.body.trim(), "朱镕基 瑢");
+    }
+
+    #[test]
+    fn gbk_html_part_is_decoded() {
+        use base64::Engine as _;
+        
+        let (html, _, _) = encoding_rs::GBK.encode("<p>会议通知</p>");
+        let message = format!(
+            "From: sender@example.com\r\nSubject: test\r\nContent-Type: multipart/alternative; boundary=b\r\n\r\n\
+             --b\r\nContent-Type: text/html; charset=GB2312\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n--b--\r\n",
+            base64::engine::general_purpose::STANDARD.encode(&html)
+        );
+        let email = parse_email("1", message.as_bytes(), None).unwrap();
+        assert_eq!(email.body_html.as_deref().map(str::trim), Some("<p>会议通知</p>"));
+    }
+
+    #[test]
+    fn unlabelled_utf8_body_is_decoded() {
+        let message = "From: sender@example.com\r\nSubject: test\r\nContent-Type: text/plain\r\n\r\n你好，世界 café\r\n";
+        let email = parse_email("1", message.as_bytes(), None).unwrap();
+        assert_eq!(email.body.trim(), "你好，世界 café");
+        
+        let message = "From: sender@example.com\r\nSubject: test\r\n\r\n没有Content-Type头部\r\n";
+        let email = parse_email("1", message.as_bytes(), None).unwrap();
+        assert_eq!(email.body.trim(), "没有Content-Type头部");
+    }
+
+    #[test]
+    fn older_preferences_get_defaults() {
+        // 早期版本只保存了这几个字段
+        let older = serde_json::json!({
+            "autoRefreshInterval": 10000,
+            "theme": "dark",
+            "windowSize": { "width": 800, "height": 600 },
+            "autoLogin": false,
+        });
+        let preferences: UserPreferences = serde_json::from_value(older).unwrap();
+        assert_eq!(preferences.auto_refresh_interval, 10000);
+        assert_eq!(preferences.theme, "dark");
+        assert!(!preferences.auto_login);
+        assert_eq!(preferences.cache_limit, email_cache::DEFAULT_CACHE_LIMIT);
+        assert!(preferences.compress_cache && preferences.mark_read_on_open);
+        
+        // 补全后写回再读取，值保持不变
+        let round_trip: UserPreferences = serde_json::from_value(serde_json::to_value(&preferences).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&round_trip).unwrap(), serde_json::to_value(&preferences).unwrap());
+    }
+
+    #[test]
+    fn newer_preferences_ignore_unknown_fields() {
+        let newer = serde_json::json!({ "theme": "glass", "futureOption": { "enabled": true } });
+        let preferences: UserPreferences = serde_json::from_value(newer).unwrap();
+        assert_eq!(preferences.auto_refresh_interval, UserPreferences::default().auto_refresh_interval);
+    }
+
+    #[test]
+    fn session_shape_drift_is_tolerated() {
+        let older: AuthSession = serde_json::from_value(serde_json::json!({
+            "email": "user@2925.com",
+            "token": "t",
+        })).unwrap();
+        assert_eq!(older.email, "user@2925.com");
+        assert_eq!(older.expires_at, 0);
+        
+        let newer: AuthSession = serde_json::from_value(serde_json::json!({
+            "email": "user@2925.com",
+            "token": "t",
+            "expiresAt": 1_760_000_000_000_i64,
+            "refreshToken": "r",
+        })).unwrap();
+        let round_trip: AuthSession = serde_json::from_value(serde_json::to_value(&newer).unwrap()).unwrap();
+        assert_eq!(round_trip.expires_at, 1_760_000_000_000);
+        assert_eq!(round_trip.token, "t");
+    }
+
+    #[async_std::test]
+    async fn missing_date_header_falls_back_to_internaldate() {
+        let script = format!(
+            "* 1 EXISTS\r\nA0002 OK [READ-WRITE] SELECT completed\r\n{}{}A0004 OK FETCH completed\r\n",
+            CAPABILITY_NO_SORT,
+            fetch_response(1, 9, "", "14-Oct-2026 08:30:00 +0800", "From: a@example.com\r\nSubject: No date\r\n\r\nhello\r\n"),
+        );
+        let (mut session, _) = scripted_session(&script).await;
+        let emails = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        
+        let expected = chrono::DateTime::parse_from_rfc3339("2026-10-14T08:30:00+08:00").unwrap().timestamp_millis();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].timestamp, expected);
+        assert_eq!(emails[0].received_at, Some(expected));
+    }
+
+    #[async_std::test]
+    async fn html_only_message_has_html_body() {
+        let html = "From: a@example.com\r\nSubject: Newsletter\r\nDate: Wed, 14 Oct 2026 09:00:00 +0000\r\n\
+            MIME-Version: 1.0\r\nContent-Type: text/html; charset=utf-8\r\n\r\n<p>Hello <b>world</b></p>\r\n";
+        let script = format!(
+            "* 1 EXISTS\r\nA0002 OK [READ-WRITE] SELECT completed\r\n{}{}A0004 OK FETCH completed\r\n",
+            CAPABILITY_NO_SORT,
+            fetch_response(1, 5, "", "14-Oct-2026 09:00:01 +0000", html),
+        );
+        let (mut session, _) = scripted_session(&script).await;
+        let emails = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await.unwrap();
+        
+        assert_eq!(emails.len(), 1);
+        assert!(emails[0].body_html.as_deref().unwrap().contains("<b>world</b>"));
+        assert!(emails[0].snippet.contains("Hello world"), "{}", emails[0].snippet);
+    }
+
+    #[async_std::test]
+    async fn select_failure_names_folder() {
+        let (mut session, _) = scripted_session("A0002 NO Mailbox does not exist\r\n").await;
+        let error = fetch_emails_from_imap(&mut session, "Missing", SortKey::default(), Order::NewestFirst).await.unwrap_err();
+        assert!(error.contains("Missing"), "{}", error);
+    }
+
+    #[async_std::test]
+    async fn connection_lost_during_fetch_is_reported() {
+        // 第二封邮件的字面量只收到一部分时连接中断
+        let mut script = format!(
+            "{}{}{}{}",
+            SELECT_THREE,
+            CAPABILITY_NO_SORT,
+            fetch_response(1, 101, "", "13-Oct-2026 01:00:05 +0000", "Subject: first\r\n\r\nbody\r\n"),
+            fetch_response(2, 102, "", "13-Oct-2026 02:00:05 +0000", "Subject: second\r\n\r\nbody\r\n"),
+        );
+        script.truncate(script.len() - 10);
+        let (mut session, _) = scripted_session(&script).await;
+        let result = fetch_emails_from_imap(&mut session, "INBOX", SortKey::default(), Order::NewestFirst).await;
+        assert!(result.is_err(), "{:?}", result.map(|emails| emails.len()));
+    }
+
+    #[async_std::test]
+    async fn connection_lost_during_chunked_scan_is_reported() {
+        // 第二封邮件的字面量只收到一部分时连接中断
+        let mut script = format!(
+            "* 1 FETCH (UID 101 RFC822.SIZE 10 BODY[HEADER.FIELDS (FROM)] {})\r\n\
+             * 2 FETCH (UID 102 RFC822.SIZE 20 BODY[HEADER.FIELDS (FROM)] {})\r\n",
+            literal("From: a@example.com\r\n\r\n"),
+            literal("From: b@example.com\r\n\r\n"),
+        );
+        script.truncate(script.len() - 10);
+        let (mut session, _) = scripted_session(&script).await;
+        
+        let mut visited = Vec::new();
+        let result = fetch_in_chunks(&mut session, 1..=2, "(UID RFC822.SIZE BODY.PEEK[HEADER.FIELDS (FROM)])", |message| {
+            visited.extend(message.uid);
+        }).await;
+        assert!(result.is_err(), "{:?}", visited);
+    }
+
+    #[async_std::test]
+    async fn merges_uid_and_flags_from_sibling_responses() {
+        let script = format!(
+            "* 1 FETCH (BODY[] {})\r\n* 1 FETCH (UID 7)\r\n* 1 FETCH (FLAGS (\\Seen $Work))\r\nA0002 OK FETCH completed\r\n",
+            literal("From: sender@example.com\r\nSubject: Split\r\n\r\nbody\r\n")
+        );
+        let (mut session, _) = scripted_session(&script).await;
+        let messages = session.uid_fetch("7", "(UID BODY.PEEK[] FLAGS)").await.unwrap();
+        let emails = collect_with(messages, build_email).await.unwrap();
+        
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].id, "7");
+        assert_eq!(emails[0].subject, "Split");
+        assert!(emails[0].is_read);
+        assert!(!emails[0].is_flagged);
+        assert_eq!(emails[0].keywords, ["$Work"]);
+    }
+
+    #[test]
+    fn malformed_addresses_are_rejected() {
+        for address in ["", "user", "@example.com", "user@", "user@example", "a b@example.com",
+            ".user@example.com", "us..er@example.com", "user@-example.com", "<user>@example.com",
+            "user@exa_mple.com"] {
+            assert_eq!(address_syntax_problem(address), Some("邮箱地址格式无效"), "{}", address);
+        }
+        assert_eq!(address_syntax_problem(&format!("{}@example.com", "a".repeat(65))), Some("邮箱地址格式无效"));
+        assert_eq!(address_syntax_problem("first.last+tag@mail.example.com"), None);
+    }
+
+    #[test]
+    fn local_part_rule_applies_only_to_2925() {
+        let rule = Some("2925邮箱用户名只能包含字母、数字、下划线、点和短横线");
+        assert_eq!(address_syntax_problem("user+tag@2925.com"), rule);
+        assert_eq!(address_syntax_problem("user+tag@2925.COM"), rule);
+        assert_eq!(address_syntax_problem("first.last_1-2@2925.com"), None);
+        assert_eq!(address_syntax_problem("user+tag@example.com"), None);
+    }
 }
@@ -0,0 +1,152 @@
+//! 后端日志模块
+//! 
+//! 通过 `log` 门面输出到stderr和应用数据目录下的 `logs/backend_<日期>.log`。
+//! 级别可在运行时调整：`debug` 级别同时记录async-imap的协议跟踪（LOGIN命令中的凭据会被隐藏），
+//! 便于排查问题后再调回较低的级别
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    #[default]
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Error,
+            1 => LogLevel::Warn,
+            2 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// 本应用的记录允许的最高级别
+    fn app_filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+        }
+    }
+
+    /// 传给 `log::set_max_level` 的全局级别，debug时需要放开trace以接收协议跟踪
+    fn global_filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Debug => LevelFilter::Trace,
+            level => level.app_filter(),
+        }
+    }
+}
+
+/// async-imap的协议跟踪只在trace级别输出
+const IMAP_TARGET: &str = "async_imap";
+
+struct BackendLogger {
+    level: AtomicU8,
+    log_dir: Option<PathBuf>,
+}
+
+static LOGGER: OnceLock<BackendLogger> = OnceLock::new();
+
+impl BackendLogger {
+    fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    fn write_file(&self, line: &str) {
+        let Some(dir) = &self.log_dir else {
+            return;
+        };
+        let date = chrono::Local::now().format("%Y-%m-%d");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("backend_{}.log", date)));
+        if let Ok(mut file) = file {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+impl Log for BackendLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = self.level();
+        if metadata.target().starts_with(IMAP_TARGET) && metadata.level() == Level::Trace {
+            return level == LogLevel::Debug;
+        }
+        metadata.level() <= level.app_filter()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        
+        let message = redact(&record.args().to_string());
+        let line = format!(
+            "[{}] [{}] [{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            message
+        );
+        eprint!("{}", line);
+        self.write_file(&line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// 隐藏协议跟踪中LOGIN命令的用户名和密码
+fn redact(message: &str) -> String {
+    let upper = message.to_ascii_uppercase();
+    match upper.find("LOGIN ") {
+        Some(index) => format!("{}LOGIN [已隐藏]", &message[..index]),
+        None => message.to_string(),
+    }
+}
+
+/// 安装日志记录器，只在启动时调用一次
+/// 
+/// # 参数
+/// * `log_dir` - 日志文件目录，为None时只输出到stderr
+/// * `level` - 初始级别
+pub fn init(log_dir: Option<PathBuf>, level: LogLevel) {
+    if let Some(dir) = &log_dir {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let logger = LOGGER.get_or_init(|| BackendLogger {
+        level: AtomicU8::new(level.as_u8()),
+        log_dir,
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(level.global_filter());
+    }
+}
+
+/// 调整日志级别，立即生效
+pub fn set_level(level: LogLevel) {
+    if let Some(logger) = LOGGER.get() {
+        logger.level.store(level.as_u8(), Ordering::Relaxed);
+        log::set_max_level(level.global_filter());
+    }
+}
@@ -0,0 +1,171 @@
+//! 邮件结构（BODYSTRUCTURE）解析模块
+//! 
+//! 将IMAP返回的BODYSTRUCTURE转换为带部分编号的树，
+//! 列出附件时无需下载完整邮件，下载时按编号只获取对应部分
+
+use async_imap::imap_proto::{BodyStructure, ContentEncoding};
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+
+use crate::i18n::tr;
+
+/// 邮件中的一个MIME部分
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagePart {
+    /// IMAP部分编号，如 `1`、`2.1`；多部分邮件的根节点为空字符串
+    #[serde(rename = "partId")]
+    pub part_id: String,
+    /// MIME类型，如 `application/pdf`
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub filename: Option<String>,
+    /// 传输编码后的大小（字节）
+    pub size: u32,
+    /// 传输编码，如 `base64`
+    pub encoding: String,
+    pub children: Vec<MessagePart>,
+}
+
+/// 将BODYSTRUCTURE转换为部分树
+/// 单部分邮件的正文编号为 `1`，多部分邮件的子部分从 `1` 开始依次编号
+pub fn parse_structure(structure: &BodyStructure) -> MessagePart {
+    let root_id = match structure {
+        BodyStructure::Multipart { .. } => String::new(),
+        _ => "1".to_string(),
+    };
+    build_part(structure, root_id)
+}
+
+fn build_part(structure: &BodyStructure, part_id: String) -> MessagePart {
+    match structure {
+        BodyStructure::Multipart { common, bodies, .. } => {
+            let children = bodies
+                .iter()
+                .enumerate()
+                .map(|(index, body)| build_part(body, child_id(&part_id, index + 1)))
+                .collect();
+            MessagePart {
+                mime_type: format!("{}/{}", common.ty.ty, common.ty.subtype).to_lowercase(),
+                filename: None,
+                size: 0,
+                encoding: String::new(),
+                children,
+                part_id,
+            }
+        }
+        BodyStructure::Basic { common, other, .. }
+        | BodyStructure::Text { common, other, .. }
+        | BodyStructure::Message { common, other, .. } => {
+            // 内嵌邮件（message/rfc822）的正文编号在其部分编号之下
+            let children = match structure {
+                BodyStructure::Message { body, .. } => {
+                    let body_id = match body.as_ref() {
+                        BodyStructure::Multipart { .. } => part_id.clone(),
+                        _ => child_id(&part_id, 1),
+                    };
+                    vec![build_part(body, body_id)]
+                }
+                _ => Vec::new(),
+            };
+            
+            // 文件名优先取Content-Disposition的filename，其次取Content-Type的name
+            let filename = common
+                .disposition
+                .as_ref()
+                .and_then(|disposition| find_param(&disposition.params, "filename"))
+                .or_else(|| find_param(&common.ty.params, "name"))
+                .map(decode_header_value);
+            
+            MessagePart {
+                mime_type: format!("{}/{}", common.ty.ty, common.ty.subtype).to_lowercase(),
+                filename,
+                size: other.octets,
+                encoding: encoding_name(&other.transfer_encoding),
+                children,
+                part_id,
+            }
+        }
+    }
+}
+
+fn child_id(parent: &str, index: usize) -> String {
+    if parent.is_empty() {
+        index.to_string()
+    } else {
+        format!("{}.{}", parent, index)
+    }
+}
+
+fn find_param<'a>(
+    params: &'a Option<Vec<(std::borrow::Cow<'_, str>, std::borrow::Cow<'a, str>)>>,
+    name: &str,
+) -> Option<&'a str> {
+    params
+        .as_ref()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_ref())
+}
+
+/// 解码RFC 2047编码的参数值（如 `=?UTF-8?B?...?=`）
+fn decode_header_value(value: &str) -> String {
+    let line = format!("X: {}", value);
+    match mailparse::parse_header(line.as_bytes()) {
+        Ok((header, _)) => header.get_value(),
+        Err(_) => value.to_string(),
+    }
+}
+
+fn encoding_name(encoding: &ContentEncoding) -> String {
+    match encoding {
+        ContentEncoding::SevenBit => "7bit".to_string(),
+        ContentEncoding::EightBit => "8bit".to_string(),
+        ContentEncoding::Binary => "binary".to_string(),
+        ContentEncoding::Base64 => "base64".to_string(),
+        ContentEncoding::QuotedPrintable => "quoted-printable".to_string(),
+        ContentEncoding::Other(other) => other.to_lowercase(),
+    }
+}
+
+/// 在部分树中查找指定编号的部分（跳过没有内容的multipart节点）
+pub fn find_part<'a>(part: &'a MessagePart, part_id: &str) -> Option<&'a MessagePart> {
+    if part.part_id == part_id && !part.encoding.is_empty() {
+        return Some(part);
+    }
+    part.children.iter().find_map(|child| find_part(child, part_id))
+}
+
+/// 解析部分编号，如 `2.1` -> `[2, 1]`
+pub fn parse_part_id(part_id: &str) -> Result<Vec<u32>, String> {
+    part_id
+        .split('.')
+        .map(|segment| segment.parse::<u32>().ok().filter(|n| *n > 0))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("{}: {}", tr("无效的部分编号"), part_id))
+}
+
+/// 按传输编码解码部分内容
+/// 
+/// # 参数
+/// * `data` - `BODY[part]` 返回的原始数据
+/// * `encoding` - 部分的传输编码
+pub fn decode_part(data: &[u8], encoding: &str) -> Result<Vec<u8>, String> {
+    match encoding {
+        "base64" => {
+            // base64内容按行折断，解码前去掉空白字符
+            let compact: Vec<u8> = data
+                .iter()
+                .copied()
+                .filter(|byte| !byte.is_ascii_whitespace())
+                .collect();
+            general_purpose::STANDARD
+                .decode(compact)
+                .map_err(|e| format!("{}: {}", tr("附件解码失败"), e))
+        }
+        "quoted-printable" => {
+            quoted_printable::decode(data, quoted_printable::ParseMode::Robust)
+                .map_err(|e| format!("{}: {}", tr("附件解码失败"), e))
+        }
+        _ => Ok(data.to_vec()),
+    }
+}
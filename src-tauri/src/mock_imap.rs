@@ -0,0 +1,81 @@
+//! 测试用的IMAP传输
+//! 
+//! 服务器的响应预先写好，按顺序读出；客户端写入的命令记录下来，供测试检查发送了哪些命令。
+//! async-imap的命令标签从 `A0001` 开始依次递增，脚本中的标记响应需使用对应的标签
+
+use std::io::Cursor;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_std::io::{Read, Write};
+
+/// 按脚本返回服务器响应的传输
+#[derive(Debug)]
+pub struct ScriptedStream {
+    input: Cursor<Vec<u8>>,
+    written: Arc<Mutex<Vec<u8>>>,
+    /// 每次读取最多返回的字节数
+    read_size: usize,
+}
+
+impl ScriptedStream {
+    /// `script` 为服务器依次返回的全部响应；读完后再读取时返回EOF
+    pub fn new(script: impl Into<Vec<u8>>) -> Self {
+        Self {
+            input: Cursor::new(script.into()),
+            written: Arc::default(),
+            read_size: usize::MAX,
+        }
+    }
+
+    /// 每次读取最多返回 `size` 字节，模拟响应分多个TCP分段到达
+    pub fn with_read_size(mut self, size: usize) -> Self {
+        self.read_size = size.max(1);
+        self
+    }
+
+    /// 客户端已写入内容的句柄，流被会话取得所有权后仍可读取
+    pub fn written(&self) -> Arc<Mutex<Vec<u8>>> {
+        self.written.clone()
+    }
+}
+
+/// 客户端发送的命令行（去掉标签），字面量内容也按行列出
+pub fn commands(written: &Mutex<Vec<u8>>) -> Vec<String> {
+    String::from_utf8_lossy(&written.lock().unwrap())
+        .split("\r\n")
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(' ') {
+            Some((tag, rest)) if tag.starts_with('A') && tag[1..].chars().all(|c| c.is_ascii_digit()) => rest.to_string(),
+            _ => line.to_string(),
+        })
+        .collect()
+}
+
+/// 以字面量形式返回的FETCH数据项：`{长度}\r\n内容`
+pub fn literal(content: &str) -> String {
+    format!("{{{}}}\r\n{}", content.len(), content)
+}
+
+impl Read for ScriptedStream {
+    fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        let len = buf.len().min(self.read_size);
+        Poll::Ready(std::io::Read::read(&mut self.input, &mut buf[..len]))
+    }
+}
+
+impl Write for ScriptedStream {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        self.written.lock().unwrap().extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
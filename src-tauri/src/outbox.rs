@@ -0,0 +1,176 @@
+//! 发件箱模块
+//! 
+//! 无法连接SMTP服务器时，待发送的邮件保存在存储的 `outbox` 中，
+//! 后台任务在网络恢复后按退避间隔重试发送
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+use crate::persist;
+
+/// 单封邮件的最大重试次数，超过后放弃并从发件箱移除
+pub const MAX_ATTEMPTS: u32 = 10;
+
+/// 首次重试的等待时间（秒），之后每次翻倍
+const BASE_BACKOFF_SECS: i64 = 30;
+/// 重试等待时间上限（秒）
+const MAX_BACKOFF_SECS: i64 = 30 * 60;
+
+/// 发送请求，即 `send_email` / `send_email_with_attachments` 的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutgoingRequest {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    #[serde(rename = "fromAlias", default)]
+    pub from_alias: Option<String>,
+    /// 附件在重试时重新读取
+    #[serde(rename = "attachmentPaths", default)]
+    pub attachment_paths: Vec<String>,
+    /// 入队时总会设置，重试使用相同的Message-ID
+    #[serde(rename = "idempotencyKey", default)]
+    pub idempotency_key: Option<String>,
+    /// 是否附加偏好设置中的签名，签名在每次发送时附加，不保存在正文中
+    #[serde(rename = "includeSignature", default = "default_include_signature")]
+    pub include_signature: bool,
+}
+
+fn default_include_signature() -> bool {
+    true
+}
+
+/// 发件箱中的邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMail {
+    pub id: String,
+    /// 发送账户
+    pub account: String,
+    pub request: OutgoingRequest,
+    /// 入队时间（毫秒）
+    #[serde(rename = "queuedAt")]
+    pub queued_at: i64,
+    /// 已尝试发送的次数
+    pub attempts: u32,
+    /// 下次尝试的时间（毫秒）
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: i64,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+}
+
+/// 发件箱读-改-写锁，避免发送命令与后台任务同时修改
+#[derive(Default)]
+pub struct OutboxLock(async_std::sync::Mutex<()>);
+
+fn load(app: &tauri::AppHandle) -> Vec<QueuedMail> {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("outbox"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, outbox: &[QueuedMail]) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("outbox", serde_json::to_value(outbox).unwrap());
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save outbox: {}", e))
+}
+
+/// 发件箱中的全部邮件，按入队时间排列
+pub fn list(app: &tauri::AppHandle) -> Vec<QueuedMail> {
+    load(app)
+}
+
+/// 将邮件加入发件箱
+/// 
+/// # 参数
+/// * `account` - 发送账户
+/// * `request` - 发送请求，需已设置幂等键
+/// * `error` - 本次发送失败的原因
+pub async fn enqueue(
+    app: &tauri::AppHandle,
+    account: &str,
+    request: OutgoingRequest,
+    error: String,
+) -> Result<QueuedMail, String> {
+    let lock = app.state::<OutboxLock>();
+    let _guard = lock.0.lock().await;
+    
+    let now = chrono::Utc::now().timestamp_millis();
+    let queued = QueuedMail {
+        id: uuid::Uuid::new_v4().to_string(),
+        account: account.to_string(),
+        request,
+        queued_at: now,
+        attempts: 1,
+        next_attempt_at: now + backoff_secs(1) * 1000,
+        last_error: Some(error),
+    };
+    
+    let mut outbox = load(app);
+    outbox.push(queued.clone());
+    save(app, &outbox)?;
+    
+    Ok(queued)
+}
+
+/// 从发件箱移除邮件，返回是否存在
+pub async fn remove(app: &tauri::AppHandle, id: &str) -> Result<bool, String> {
+    let lock = app.state::<OutboxLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut outbox = load(app);
+    let before = outbox.len();
+    outbox.retain(|mail| mail.id != id);
+    if outbox.len() == before {
+        return Ok(false);
+    }
+    save(app, &outbox)?;
+    Ok(true)
+}
+
+/// 记录一次失败的重试并安排下次重试
+/// 返回更新后的邮件；达到最大次数时从发件箱移除并返回None
+pub async fn record_failure(app: &tauri::AppHandle, id: &str, error: String) -> Result<Option<QueuedMail>, String> {
+    let lock = app.state::<OutboxLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut outbox = load(app);
+    let Some(position) = outbox.iter().position(|mail| mail.id == id) else {
+        return Ok(None);
+    };
+    
+    let mail = &mut outbox[position];
+    mail.attempts += 1;
+    mail.last_error = Some(error);
+    mail.next_attempt_at = chrono::Utc::now().timestamp_millis() + backoff_secs(mail.attempts) * 1000;
+    
+    let updated = (mail.attempts < MAX_ATTEMPTS).then(|| mail.clone());
+    if updated.is_none() {
+        outbox.remove(position);
+    }
+    save(app, &outbox)?;
+    
+    Ok(updated)
+}
+
+/// 已到重试时间的邮件
+/// 
+/// # 参数
+/// * `account` - 只返回该账户的邮件，其他账户的邮件在其登录后再发送
+pub fn due(app: &tauri::AppHandle, account: &str) -> Vec<QueuedMail> {
+    let now = chrono::Utc::now().timestamp_millis();
+    load(app)
+        .into_iter()
+        .filter(|mail| mail.account == account && mail.next_attempt_at <= now)
+        .collect()
+}
+
+/// 第 `attempts` 次失败后的等待时间（秒）
+fn backoff_secs(attempts: u32) -> i64 {
+    let exponent = attempts.saturating_sub(1).min(16);
+    (BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS)
+}
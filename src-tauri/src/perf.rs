@@ -0,0 +1,85 @@
+//! 性能统计模块
+//! 
+//! 统计连接池的复用次数、每次FETCH的耗时和邮件解析耗时，用于确认连接池确实有效并发现性能退化。
+//! 计数器是进程级的原子变量（与 `i18n` 的当前语言相同），记录时不加锁，不会明显影响被测的耗时；
+//! 应用重启后清零
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+
+static REUSED_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+static NEW_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+static FETCH_COUNT: AtomicU64 = AtomicU64::new(0);
+static FETCH_MICROS: AtomicU64 = AtomicU64::new(0);
+static PARSE_COUNT: AtomicU64 = AtomicU64::new(0);
+static PARSE_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// 性能统计快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfStats {
+    /// 从连接池复用的连接数
+    #[serde(rename = "reusedConnections")]
+    pub reused_connections: u64,
+    /// 新建的连接数（池中没有可用连接，或连接已过期、失效）
+    #[serde(rename = "newConnections")]
+    pub new_connections: u64,
+    /// 复用的连接占全部借出连接的比例，还没有借出过连接时为None
+    #[serde(rename = "reuseRatio")]
+    pub reuse_ratio: Option<f64>,
+    /// 读取完的FETCH响应流数
+    #[serde(rename = "fetchCount")]
+    pub fetch_count: u64,
+    /// 每次FETCH从开始读取到读取完毕的平均耗时（毫秒），包括其中的邮件解析
+    #[serde(rename = "averageFetchMs")]
+    pub average_fetch_ms: Option<f64>,
+    /// 解析的邮件数
+    #[serde(rename = "parseCount")]
+    pub parse_count: u64,
+    /// 每封邮件的平均解析耗时（毫秒）
+    #[serde(rename = "averageParseMs")]
+    pub average_parse_ms: Option<f64>,
+}
+
+/// 记录借出一个连接，`reused` 表示连接来自连接池
+pub fn record_connection(reused: bool) {
+    let counter = if reused { &REUSED_CONNECTIONS } else { &NEW_CONNECTIONS };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 记录一次FETCH的耗时
+pub fn record_fetch(elapsed: Duration) {
+    FETCH_COUNT.fetch_add(1, Ordering::Relaxed);
+    FETCH_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// 记录一封邮件的解析耗时
+pub fn record_parse(elapsed: Duration) {
+    PARSE_COUNT.fetch_add(1, Ordering::Relaxed);
+    PARSE_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// 总耗时（微秒）除以次数，得到平均毫秒数
+fn average_ms(total_micros: u64, count: u64) -> Option<f64> {
+    (count > 0).then(|| total_micros as f64 / count as f64 / 1000.0)
+}
+
+/// 当前的统计数据
+pub fn snapshot() -> PerfStats {
+    let reused_connections = REUSED_CONNECTIONS.load(Ordering::Relaxed);
+    let new_connections = NEW_CONNECTIONS.load(Ordering::Relaxed);
+    let total_connections = reused_connections + new_connections;
+    let fetch_count = FETCH_COUNT.load(Ordering::Relaxed);
+    let parse_count = PARSE_COUNT.load(Ordering::Relaxed);
+    
+    PerfStats {
+        reused_connections,
+        new_connections,
+        reuse_ratio: (total_connections > 0).then(|| reused_connections as f64 / total_connections as f64),
+        fetch_count,
+        average_fetch_ms: average_ms(FETCH_MICROS.load(Ordering::Relaxed), fetch_count),
+        parse_count,
+        average_parse_ms: average_ms(PARSE_MICROS.load(Ordering::Relaxed), parse_count),
+    }
+}
@@ -0,0 +1,194 @@
+//! 存储文件持久化模块
+//! 
+//! 存储插件的 `save()` 直接覆盖写入，进程在写入中途被终止会留下截断的文件，
+//! 而插件加载失败时会静默使用空数据，之后的保存会把会话、偏好设置和子邮箱全部覆盖掉。
+//! 这里先写入临时文件再重命名替换，并同时保留一份 `.bak`；启动时主文件无法解析则从备份恢复。
+//! 各命令持有的是各自数据的锁，可能同时保存同一个存储文件，保存过程由全局锁串行化
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri_plugin_store::Store;
+
+/// 串行化所有存储文件的保存，读取存储内容到写入完成期间持有
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 临时文件序号，与进程ID一起保证每次写入使用不同的临时文件
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 写入同目录下的临时文件并同步到磁盘后重命名替换目标文件
+/// 临时文件名各不相同，并发写入不会写进同一个文件；失败时删除临时文件
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let temp = with_suffix(path, &format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = std::fs::File::create_new(&temp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    }).and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// 检查文件内容是否为有效的存储数据（JSON对象）
+fn is_valid(bytes: &[u8]) -> bool {
+    serde_json::from_slice::<HashMap<String, serde_json::Value>>(bytes).is_ok()
+}
+
+/// 原子地保存存储，替代 `store.save()`
+/// 
+/// # 参数
+/// * `app` - 应用句柄，用于解析存储文件路径
+/// * `store` - 要保存的存储
+/// * `name` - 存储文件名，如 `store.json`
+pub fn save(app: &tauri::AppHandle, store: &Store<tauri::Wry>, name: &str) -> Result<(), String> {
+    let path = tauri_plugin_store::resolve_store_path(app, name).map_err(|e| e.to_string())?;
+    // 在锁内读取内容，后完成的保存总是写入较新的数据
+    let _guard = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let entries: HashMap<String, serde_json::Value> = store.entries().into_iter().collect();
+    let bytes = serde_json::to_vec_pretty(&entries).map_err(|e| e.to_string())?;
+    
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    write_atomic(&path, &bytes).map_err(|e| e.to_string())?;
+    
+    // 备份保存最近一次完整写入的内容，主文件损坏时用于恢复
+    write_atomic(&with_suffix(&path, ".bak"), &bytes).map_err(|e| e.to_string())
+}
+
+/// 启动时检查存储文件，主文件无法解析时用备份替换
+/// 必须在首次访问该存储之前调用，插件只在首次访问时读取文件
+/// 
+/// # 参数
+/// * `app` - 应用句柄
+/// * `name` - 存储文件名
+pub fn recover(app: &tauri::AppHandle, name: &str) {
+    if let Ok(path) = tauri_plugin_store::resolve_store_path(app, name) {
+        recover_path(&path, name);
+    }
+}
+
+fn recover_path(path: &Path, name: &str) {
+    let Ok(bytes) = std::fs::read(path) else {
+        // 文件不存在（首次启动）时无需处理
+        return;
+    };
+    if is_valid(&bytes) {
+        return;
+    }
+    
+    let backup = with_suffix(path, ".bak");
+    match std::fs::read(&backup) {
+        Ok(backup_bytes) if is_valid(&backup_bytes) => {
+            // 保留损坏的文件便于排查
+            let _ = std::fs::rename(path, with_suffix(path, ".corrupt"));
+            match write_atomic(path, &backup_bytes) {
+                Ok(()) => log::warn!("{} 已损坏，已从备份恢复", name),
+                Err(e) => log::error!("{} 已损坏，从备份恢复失败: {}", name, e),
+            }
+        }
+        _ => log::error!("{} 已损坏且没有可用的备份", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试专用的空目录
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("persist-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 目录中的文件名（排序后）
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_atomic_replaces_file_without_leftovers() {
+        let dir = temp_dir();
+        let path = dir.join("store.json");
+        std::fs::write(&path, b"{\"old\":1}").unwrap();
+        
+        write_atomic(&path, b"{\"new\":2}").unwrap();
+        
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"new\":2}");
+        assert_eq!(file_names(&dir), ["store.json"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writes_never_mix() {
+        let dir = temp_dir();
+        let path = dir.join("store.json");
+        let contents: Vec<Vec<u8>> = (0..8)
+            .map(|n| serde_json::to_vec(&serde_json::json!({ "writer": n, "data": "x".repeat(64 * 1024) })).unwrap())
+            .collect();
+        
+        std::thread::scope(|scope| {
+            for content in &contents {
+                let path = &path;
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        write_atomic(path, content).unwrap();
+                    }
+                });
+            }
+        });
+        
+        // 最终内容是某一次写入的完整内容，没有残留的临时文件
+        let written = std::fs::read(&path).unwrap();
+        assert!(contents.contains(&written));
+        assert_eq!(file_names(&dir), ["store.json"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupted_store_is_restored_from_backup() {
+        let dir = temp_dir();
+        let path = dir.join("store.json");
+        std::fs::write(&path, b"{\"session\":").unwrap();
+        std::fs::write(with_suffix(&path, ".bak"), b"{\"session\":{\"email\":\"user@2925.com\"}}").unwrap();
+        
+        recover_path(&path, "store.json");
+        
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"session\":{\"email\":\"user@2925.com\"}}");
+        assert_eq!(std::fs::read(with_suffix(&path, ".corrupt")).unwrap(), b"{\"session\":");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn valid_store_is_left_alone() {
+        let dir = temp_dir();
+        let path = dir.join("store.json");
+        std::fs::write(&path, b"{\"a\":1}").unwrap();
+        std::fs::write(with_suffix(&path, ".bak"), b"{\"b\":2}").unwrap();
+        
+        recover_path(&path, "store.json");
+        
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"a\":1}");
+        assert_eq!(file_names(&dir), ["store.json", "store.json.bak"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
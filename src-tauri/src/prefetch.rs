@@ -0,0 +1,58 @@
+//! 邮件预取缓存模块
+//! 
+//! 界面打开邮件时预取相邻的几封，`get_email` 命中缓存时无需再次往返服务器。
+//! 缓存按条目数和存活时间限制，每个条目只使用一次：取出后标记可能已在服务器上变化
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::email::Email;
+
+/// 单次预取的最大邮件数
+pub const MAX_PREFETCH_COUNT: usize = 10;
+/// 单次预取的邮件总大小上限（字节，按RFC822.SIZE计算）
+pub const MAX_PREFETCH_BYTES: u64 = 10 * 1024 * 1024;
+/// 缓存的最大条目数，超出时淘汰最早预取的条目
+const MAX_ENTRIES: usize = 50;
+/// 条目的存活时间
+const ENTRY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// 预取的邮件，作为应用状态管理
+#[derive(Default)]
+pub struct PrefetchCache(async_std::sync::Mutex<HashMap<String, (Email, Instant)>>);
+
+/// 缓存键，按账户、文件夹和UID区分
+pub fn key(account: &str, folder: &str, uid: u32) -> String {
+    format!("{}/{}/{}", account, folder, uid)
+}
+
+impl PrefetchCache {
+    /// 保存预取的邮件
+    pub async fn insert(&self, entries: Vec<(String, Email)>) {
+        let mut cache = self.0.lock().await;
+        cache.retain(|_, (_, at)| at.elapsed() < ENTRY_TTL);
+        
+        let now = Instant::now();
+        for (key, email) in entries {
+            cache.insert(key, (email, now));
+        }
+        
+        while cache.len() > MAX_ENTRIES {
+            let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, (_, at))| *at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            cache.remove(&oldest);
+        }
+    }
+
+    /// 取出未过期的预取邮件
+    pub async fn take(&self, key: &str) -> Option<Email> {
+        let mut cache = self.0.lock().await;
+        let (email, at) = cache.remove(key)?;
+        (at.elapsed() < ENTRY_TTL).then_some(email)
+    }
+}
@@ -0,0 +1,76 @@
+//! 阅读位置模块
+//! 
+//! 每个文件夹最后查看的邮件UID保存在存储的 `read_positions` 中，重新打开文件夹时由界面恢复到该位置。
+//! 同时记录设置时文件夹的UIDVALIDITY，UIDVALIDITY变化后UID不再指向同一封邮件，位置随之清除
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+use crate::persist;
+
+/// 文件夹的阅读位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadPosition {
+    /// 最后查看的邮件UID
+    pub uid: u32,
+    /// 设置时文件夹的UIDVALIDITY
+    #[serde(rename = "uidValidity")]
+    pub uid_validity: u32,
+    /// 设置时间（毫秒）
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+}
+
+/// 阅读位置读-改-写锁，避免界面频繁更新时互相覆盖
+#[derive(Default)]
+pub struct ReadPositionLock(async_std::sync::Mutex<()>);
+
+fn position_key(account: &str, folder: &str) -> String {
+    format!("{}/{}", account, folder)
+}
+
+fn load(app: &tauri::AppHandle) -> serde_json::Map<String, serde_json::Value> {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("read_positions"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, positions: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("read_positions", serde_json::Value::Object(positions));
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save read positions: {}", e))
+}
+
+/// 文件夹的阅读位置，没有记录时返回None
+pub fn get(app: &tauri::AppHandle, account: &str, folder: &str) -> Option<ReadPosition> {
+    load(app)
+        .remove(&position_key(account, folder))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// 记录文件夹的阅读位置，覆盖之前的记录
+pub async fn set(app: &tauri::AppHandle, account: &str, folder: &str, position: ReadPosition) -> Result<(), String> {
+    let lock = app.state::<ReadPositionLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut positions = load(app);
+    positions.insert(position_key(account, folder), serde_json::to_value(position).unwrap());
+    save(app, positions)
+}
+
+/// 清除文件夹的阅读位置
+pub async fn clear(app: &tauri::AppHandle, account: &str, folder: &str) -> Result<(), String> {
+    let lock = app.state::<ReadPositionLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut positions = load(app);
+    if positions.remove(&position_key(account, folder)).is_none() {
+        return Ok(());
+    }
+    save(app, positions)
+}
@@ -0,0 +1,154 @@
+//! 后台自动刷新模块
+//! 
+//! 刷新循环按 `auto_refresh_interval` 定时执行；修改间隔时通过通道唤醒循环立即重新计时，
+//! 间隔为0时暂停，但任务本身保持运行，之后设置新的间隔即可恢复。
+//! 手动刷新与定时刷新通过 `RefreshScheduler::exclusive` 互斥，避免同一批新邮件被报告两次
+
+use async_std::channel::{bounded, Receiver, Sender};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 刷新间隔的控制端，作为应用状态管理
+pub struct RefreshScheduler {
+    interval_ms: Arc<AtomicU64>,
+    notify: Sender<()>,
+    running: async_std::sync::Mutex<()>,
+}
+
+/// 刷新循环端，在后台任务中运行
+pub struct RefreshLoop {
+    interval_ms: Arc<AtomicU64>,
+    wake: Receiver<()>,
+}
+
+/// 创建一对共享刷新间隔的控制端和循环端
+/// 
+/// # 参数
+/// * `interval_ms` - 初始刷新间隔（毫秒），0表示暂停
+pub fn channel(interval_ms: u64) -> (RefreshScheduler, RefreshLoop) {
+    let interval_ms = Arc::new(AtomicU64::new(interval_ms));
+    // 容量为1即可：循环只关心"间隔已变化"，多次修改合并为一次唤醒
+    let (notify, wake) = bounded(1);
+    (
+        RefreshScheduler {
+            interval_ms: interval_ms.clone(),
+            notify,
+            running: async_std::sync::Mutex::new(()),
+        },
+        RefreshLoop { interval_ms, wake },
+    )
+}
+
+impl RefreshScheduler {
+    /// 修改刷新间隔并唤醒循环重新计时
+    pub fn set_interval(&self, interval_ms: u64) {
+        self.interval_ms.store(interval_ms, Ordering::Relaxed);
+        let _ = self.notify.try_send(());
+    }
+
+    /// 唤醒循环重新计时，手动刷新后下一次定时刷新从现在开始计算
+    pub fn restart(&self) {
+        let _ = self.notify.try_send(());
+    }
+
+    /// 获取刷新互斥锁，持有期间其他刷新等待
+    pub async fn exclusive(&self) -> async_std::sync::MutexGuard<'_, ()> {
+        self.running.lock().await
+    }
+}
+
+impl RefreshLoop {
+    /// 运行刷新循环，每个间隔执行一次 `tick`
+    /// 控制端被释放后循环结束
+    pub async fn run<F, Fut>(self, mut tick: F)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let interval_ms = self.interval_ms.load(Ordering::Relaxed);
+            if interval_ms == 0 {
+                // 已暂停，等待新的间隔
+                if self.wake.recv().await.is_err() {
+                    return;
+                }
+                continue;
+            }
+            
+            match async_std::future::timeout(Duration::from_millis(interval_ms), self.wake.recv()).await {
+                // 到达间隔，执行刷新
+                Err(_) => tick().await,
+                // 间隔已变化，按新间隔重新计时
+                Ok(Ok(())) => continue,
+                Ok(Err(_)) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Instant;
+
+    /// 在后台运行刷新循环，返回已执行的刷新次数
+    fn spawn_counting(refresh_loop: RefreshLoop) -> (Arc<AtomicUsize>, async_std::task::JoinHandle<()>) {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let counter = ticks.clone();
+        let handle = async_std::task::spawn(refresh_loop.run(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {}
+        }));
+        (ticks, handle)
+    }
+
+    #[async_std::test]
+    async fn shorter_interval_takes_effect_immediately() {
+        let (scheduler, refresh_loop) = channel(60_000);
+        let (ticks, _handle) = spawn_counting(refresh_loop);
+        
+        let changed = Instant::now();
+        scheduler.set_interval(100);
+        while ticks.load(Ordering::SeqCst) == 0 {
+            assert!(changed.elapsed() < Duration::from_secs(2), "新间隔没有生效");
+            async_std::task::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[async_std::test]
+    async fn rescheduling_moves_pending_refresh() {
+        let (scheduler, refresh_loop) = channel(400);
+        let (ticks, _handle) = spawn_counting(refresh_loop);
+        
+        // 原定在400ms时的刷新推迟到重新计时后的400ms，即600ms时，不会在400ms时额外执行一次
+        async_std::task::sleep(Duration::from_millis(200)).await;
+        scheduler.set_interval(400);
+        async_std::task::sleep(Duration::from_millis(300)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 0);
+        
+        async_std::task::sleep(Duration::from_millis(250)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+    }
+
+    #[async_std::test]
+    async fn zero_interval_pauses_until_resumed() {
+        let (scheduler, refresh_loop) = channel(50);
+        let (ticks, handle) = spawn_counting(refresh_loop);
+        
+        scheduler.set_interval(0);
+        let paused_at = ticks.load(Ordering::SeqCst);
+        async_std::task::sleep(Duration::from_millis(200)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), paused_at);
+        
+        scheduler.set_interval(50);
+        async_std::task::sleep(Duration::from_millis(200)).await;
+        assert!(ticks.load(Ordering::SeqCst) > paused_at);
+        
+        // 控制端释放后循环结束
+        drop(scheduler);
+        async_std::future::timeout(Duration::from_secs(1), handle).await.unwrap();
+    }
+}
@@ -0,0 +1,577 @@
+//! SMTP发送模块
+//! 
+//! 基于async-std与native-tls实现的最小SMTP客户端（隐式TLS或STARTTLS），
+//! 以及构造纯文本邮件所需的MIME/RFC 2047编码辅助函数
+
+use async_native_tls::TlsStream;
+use async_std::io::prelude::*;
+use async_std::io::{BufRead, BufReader};
+use async_std::net::TcpStream;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::imap_pool::{self, MinTlsVersion};
+
+/// 默认SMTP服务器
+pub const DEFAULT_SMTP_SERVER: &str = "smtp.2925.com";
+/// 默认SMTP端口（隐式TLS）
+pub const DEFAULT_SMTP_PORT: u16 = 465;
+/// 默认附件总大小上限（MB）
+pub const DEFAULT_ATTACHMENT_LIMIT_MB: u64 = 25;
+/// 服务器未声明SIZE扩展时默认的邮件大小上限（MB），附件经base64编码后约增大三分之一
+pub const DEFAULT_MESSAGE_SIZE_LIMIT_MB: u64 = 50;
+
+/// SMTP连接的加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// 连接后立即TLS握手（通常为465端口）
+    #[default]
+    Tls,
+    /// 明文连接后通过STARTTLS升级（通常为587端口），服务器不支持时连接失败，不会以明文发送凭据
+    StartTls,
+}
+
+/// 已建立TLS并完成EHLO的SMTP连接
+pub struct SmtpConnection {
+    stream: BufReader<TlsStream<TcpStream>>,
+    /// EHLO响应中声明的扩展（不含状态码，如 `AUTH PLAIN LOGIN`、`SIZE 52428800`）
+    capabilities: Vec<String>,
+}
+
+impl SmtpConnection {
+    /// 连接SMTP服务器，建立TLS并发送EHLO
+    /// 
+    /// # 参数
+    /// * `server` - SMTP服务器地址
+    /// * `port` - SMTP服务器端口
+    /// * `security` - 加密方式
+    /// * `min_tls_version` - 允许的最低TLS版本，服务器仅支持更低的版本时握手失败
+    pub async fn connect(
+        server: &str,
+        port: u16,
+        security: SmtpSecurity,
+        min_tls_version: MinTlsVersion,
+    ) -> Result<Self, AppError> {
+        let mut tcp_stream = TcpStream::connect((server, port))
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        if security == SmtpSecurity::StartTls {
+            tcp_stream = start_tls(tcp_stream).await?;
+        }
+        
+        let tls_stream = imap_pool::tls_handshake(tcp_stream, server, min_tls_version).await?;
+        
+        let mut connection = Self {
+            stream: BufReader::new(tls_stream),
+            capabilities: Vec::new(),
+        };
+        
+        // 隐式TLS时读取服务器问候语；STARTTLS的问候语已在升级前读取
+        if security == SmtpSecurity::Tls {
+            connection.expect_reply(220).await?;
+        }
+        
+        // STARTTLS升级后需要重新EHLO，之前声明的扩展作废
+        let lines = connection.command("EHLO localhost", 250).await?;
+        // 第一行是服务器域名，其余为扩展
+        connection.capabilities = lines.into_iter().skip(1).collect();
+        
+        Ok(connection)
+    }
+
+    /// 服务器是否声明支持AUTH
+    pub fn supports_auth(&self) -> bool {
+        self.capabilities.iter().any(|line| {
+            let keyword = line.split_whitespace().next().unwrap_or_default();
+            keyword.eq_ignore_ascii_case("AUTH") || keyword.to_ascii_uppercase().starts_with("AUTH=")
+        })
+    }
+
+    /// SIZE扩展声明的最大邮件大小（字节），未声明或为0（不限制）时返回None
+    pub fn max_message_size(&self) -> Option<u64> {
+        self.declared_size().filter(|&size| size > 0)
+    }
+
+    /// SIZE扩展的参数，声明了SIZE但没有给出大小时为0；未声明SIZE时返回None
+    fn declared_size(&self) -> Option<u64> {
+        self.capabilities.iter().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            if !parts.next()?.eq_ignore_ascii_case("SIZE") {
+                return None;
+            }
+            Some(parts.next().and_then(|size| size.parse::<u64>().ok()).unwrap_or(0))
+        })
+    }
+
+    /// 使用AUTH LOGIN认证
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<(), AppError> {
+        self.command("AUTH LOGIN", 334).await?;
+        self.command(&general_purpose::STANDARD.encode(username), 334).await?;
+        
+        let line = general_purpose::STANDARD.encode(password);
+        self.write_line(&line).await?;
+        match self.read_reply().await? {
+            (235, _) => Ok(()),
+            (code, lines) if (500..600).contains(&code) => {
+                Err(AppError::AuthRejected(format!("{} {}", code, lines.join(" "))))
+            }
+            (code, lines) => Err(AppError::Smtp(format!("{} {}", code, lines.join(" ")))),
+        }
+    }
+
+    /// 发送信封（MAIL FROM、RCPT TO）并以DATA开始传输邮件内容，之后调用 `send_data`
+    /// 
+    /// 在此之前失败时服务器肯定没有收到邮件；DATA发出后服务器是否接收只能由 `send_data` 的结果确定
+    /// 
+    /// # 参数
+    /// * `from` - 信封发件人地址（仅地址，不含显示名称）
+    /// * `recipients` - 信封收件人地址
+    /// * `size` - 邮件内容的字节数
+    /// * `fallback_limit` - 服务器未声明SIZE时的大小上限（字节），为0时不限制
+    /// 
+    /// 邮件超过服务器声明的SIZE（或 `fallback_limit`）时在发送任何命令前返回 `MessageTooLarge`
+    pub async fn start_data(
+        &mut self,
+        from: &str,
+        recipients: &[String],
+        size: u64,
+        fallback_limit: u64,
+    ) -> Result<(), AppError> {
+        let limit = match self.declared_size() {
+            Some(size) => size,
+            None => fallback_limit,
+        };
+        if limit > 0 && size > limit {
+            return Err(AppError::MessageTooLarge { size, limit });
+        }
+        
+        self.command(&format!("MAIL FROM:<{}>", from), 250).await?;
+        for recipient in recipients {
+            self.command(&format!("RCPT TO:<{}>", recipient), 250).await?;
+        }
+        self.command("DATA", 354).await?;
+        Ok(())
+    }
+
+    /// 传输DATA之后的邮件内容并等待服务器确认接收
+    /// 
+    /// # 参数
+    /// * `message` - 完整的RFC 5322邮件内容
+    pub async fn send_data(&mut self, message: &str) -> Result<(), AppError> {
+        // 行首的"."需要转义为".."（dot-stuffing）
+        let mut data = String::with_capacity(message.len() + 64);
+        for line in message.split("\r\n") {
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data.push_str(".\r\n");
+        
+        self.stream
+            .get_mut()
+            .write_all(data.as_bytes())
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        self.expect_reply(250).await?;
+        
+        Ok(())
+    }
+
+    /// 发送QUIT并关闭连接，忽略服务器响应
+    pub async fn quit(mut self) {
+        let _ = self.command("QUIT", 221).await;
+    }
+
+    /// 发送一行命令并检查响应码，返回响应文本行
+    async fn command(&mut self, line: &str, expected: u16) -> Result<Vec<String>, AppError> {
+        self.write_line(line).await?;
+        self.expect_reply(expected).await
+    }
+
+    async fn write_line(&mut self, line: &str) -> Result<(), AppError> {
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))
+    }
+
+    async fn expect_reply(&mut self, expected: u16) -> Result<Vec<String>, AppError> {
+        expect_reply_from(&mut self.stream, expected).await
+    }
+
+    async fn read_reply(&mut self) -> Result<(u16, Vec<String>), AppError> {
+        read_reply_from(&mut self.stream).await
+    }
+}
+
+/// 在明文连接上读取问候语、发送EHLO和STARTTLS，返回可以开始TLS握手的连接
+async fn start_tls(tcp_stream: TcpStream) -> Result<TcpStream, AppError> {
+    let mut reader = BufReader::new(tcp_stream);
+    expect_reply_from(&mut reader, 220).await?;
+    
+    for (line, expected) in [("EHLO localhost", 250), ("STARTTLS", 220)] {
+        let stream = reader.get_mut();
+        stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        expect_reply_from(&mut reader, expected).await?;
+    }
+    
+    // 服务器在220之后等待TLS握手，缓冲区中不会有剩余数据
+    Ok(reader.into_inner())
+}
+
+async fn expect_reply_from<R: BufRead + Unpin>(reader: &mut R, expected: u16) -> Result<Vec<String>, AppError> {
+    let (code, lines) = read_reply_from(reader).await?;
+    if code != expected {
+        return Err(AppError::Smtp(format!("{} {}", code, lines.join(" "))));
+    }
+    Ok(lines)
+}
+
+/// 读取一个（可能为多行的）SMTP响应
+async fn read_reply_from<R: BufRead + Unpin>(reader: &mut R) -> Result<(u16, Vec<String>), AppError> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        if read == 0 {
+            return Err(AppError::Network(tr("连接意外中断").to_string()));
+        }
+        
+        let line = line.trim_end();
+        if line.len() < 3 {
+            return Err(AppError::Smtp(format!("{}: {}", tr("无效的SMTP响应"), line)));
+        }
+        let code: u16 = line[..3]
+            .parse()
+            .map_err(|_| AppError::Smtp(format!("{}: {}", tr("无效的SMTP响应"), line)))?;
+        lines.push(line.get(4..).unwrap_or("").to_string());
+        
+        // "250-" 表示后面还有续行，"250 " 为最后一行
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok((code, lines));
+        }
+    }
+}
+
+/// 检查单行头部字段的值，拒绝CR/LF以防止头部注入
+/// 
+/// # 参数
+/// * `field` - 头部字段名，用于错误信息
+/// * `value` - 待写入头部的值
+pub fn check_header_value(field: &str, value: &str) -> Result<(), AppError> {
+    if value.contains(['\r', '\n']) {
+        return Err(AppError::InvalidHeader(field.to_string()));
+    }
+    Ok(())
+}
+
+/// 校验邮箱地址格式（local@domain，仅允许地址本身，不含显示名称）
+pub fn is_valid_address(address: &str) -> bool {
+    if address.len() > 254 || address.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    
+    // 这些字符会破坏SMTP命令或头部中的地址语法
+    let forbidden = ['<', '>', '(', ')', '[', ']', ',', ';', ':', '"', '\\', '@'];
+    let local_ok = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && !local.contains(forbidden);
+    let domain_ok = domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+    
+    local_ok && domain_ok
+}
+
+/// 解析以逗号分隔的收件人列表，任一地址无效即返回错误
+pub fn parse_recipients(list: &str) -> Result<Vec<String>, AppError> {
+    check_header_value("To", list)?;
+    
+    let recipients: Vec<String> = list
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect();
+    if recipients.is_empty() {
+        return Err(AppError::InvalidRecipient(tr("收件人不能为空").to_string()));
+    }
+    if let Some(invalid) = recipients.iter().find(|address| !is_valid_address(address)) {
+        return Err(AppError::InvalidRecipient(invalid.clone()));
+    }
+    
+    Ok(recipients)
+}
+
+/// 按RFC 2047对含非ASCII字符的头部文本进行编码
+/// 纯ASCII文本原样返回；长文本拆分为多个encoded-word并折行，保证每个不超过75字符
+pub fn encode_header_text(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    
+    // 每段最多45字节原文，base64后为60字符，加上 "=?UTF-8?B?" 和 "?=" 不超过75字符
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for ch in text.chars() {
+        if chunk.len() + ch.len_utf8() > 45 {
+            words.push(encoded_word(&chunk));
+            chunk.clear();
+        }
+        chunk.push(ch);
+    }
+    if !chunk.is_empty() {
+        words.push(encoded_word(&chunk));
+    }
+    
+    words.join("\r\n ")
+}
+
+fn encoded_word(text: &str) -> String {
+    format!("=?UTF-8?B?{}?=", general_purpose::STANDARD.encode(text.as_bytes()))
+}
+
+/// 格式化带显示名称的地址，如 `"张三" <zhangsan@2925.com>`
+/// 
+/// # 参数
+/// * `display_name` - 显示名称，为空时只返回地址
+/// * `address` - 邮箱地址
+pub fn format_mailbox(display_name: Option<&str>, address: &str) -> String {
+    match display_name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) if name.is_ascii() => {
+            let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("\"{}\" <{}>", escaped, address)
+        }
+        Some(name) => format!("{} <{}>", encode_header_text(name), address),
+        None => format!("<{}>", address),
+    }
+}
+
+/// 邮件附件
+pub struct Attachment {
+    /// 附件文件名
+    pub filename: String,
+    /// MIME类型，如 `application/pdf`
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    /// 读取本地文件作为附件，根据扩展名推断MIME类型
+    /// 
+    /// # 参数
+    /// * `path` - 文件路径
+    pub fn from_path(path: &std::path::Path) -> Result<Self, String> {
+        let data = std::fs::read(path)
+            .map_err(|e| format!("{} {}: {}", tr("无法读取附件"), path.display(), e))?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        let content_type = mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .essence_str()
+            .to_string();
+        
+        Ok(Self {
+            filename,
+            content_type,
+            data,
+        })
+    }
+}
+
+/// 生成Message-ID（含尖括号）
+/// 提供 `seed`（如幂等键）时由其派生，同一次发送的重试使用相同的Message-ID，便于下游识别重复邮件
+/// 
+/// # 参数
+/// * `domain` - Message-ID的域名部分
+/// * `seed` - 派生种子，为None时随机生成
+pub fn generate_message_id(domain: &str, seed: Option<&str>) -> String {
+    let local = match seed {
+        Some(seed) => Sha256::digest(seed.as_bytes())
+            .iter()
+            .take(16)
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>(),
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    format!("<{}@{}>", local, domain)
+}
+
+/// 签名分隔线（RFC 3676 4.3），`--` 后有一个空格
+pub const SIGNATURE_SEPARATOR: &str = "-- ";
+
+/// 在纯文本正文末尾附加签名，签名前为分隔线
+/// 
+/// 签名为空时正文不变；签名自带的分隔线会被去掉，避免出现两条。
+/// 正文未引用的部分已包含相同的签名块时不再附加，以 `>` 开头的引用行（如回复中引用的原邮件）不计在内
+pub fn append_signature(body: &str, signature: &str) -> String {
+    let signature = signature.replace("\r\n", "\n");
+    let signature = signature
+        .strip_prefix(SIGNATURE_SEPARATOR)
+        .and_then(|rest| rest.strip_prefix('\n'))
+        .unwrap_or(&signature)
+        .trim_matches('\n');
+    if signature.trim().is_empty() {
+        return body.to_string();
+    }
+    
+    let block = format!("{}\n{}", SIGNATURE_SEPARATOR, signature);
+    let unquoted = body
+        .replace("\r\n", "\n")
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if unquoted.contains(&block) {
+        return body.to_string();
+    }
+    
+    let mut signed = body.trim_end_matches(['\r', '\n']).to_string();
+    if !signed.is_empty() {
+        signed.push_str("\n\n");
+    }
+    signed.push_str(&block);
+    signed
+}
+
+/// 待发送的邮件，无附件时为纯文本，有附件时为multipart/mixed
+pub struct OutgoingMessage {
+    /// Message-ID头部（含尖括号），由 `generate_message_id` 生成
+    pub message_id: String,
+    /// From头部（已格式化）
+    pub from: String,
+    /// Sender头部（已格式化），通过别名发送时为实际账户
+    pub sender: Option<String>,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<Attachment>,
+}
+
+impl OutgoingMessage {
+    /// 生成完整的RFC 5322邮件内容
+    pub fn to_rfc5322(&self) -> String {
+        let mut headers = vec![
+            format!("Date: {}", chrono::Local::now().to_rfc2822()),
+            format!("From: {}", self.from),
+        ];
+        if let Some(sender) = &self.sender {
+            headers.push(format!("Sender: {}", sender));
+        }
+        headers.push(format!("To: {}", self.to.join(", ")));
+        headers.push(format!("Subject: {}", encode_header_text(&self.subject)));
+        headers.push(format!("Message-ID: {}", self.message_id));
+        headers.push("MIME-Version: 1.0".to_string());
+        
+        if self.attachments.is_empty() {
+            headers.push("Content-Type: text/plain; charset=utf-8".to_string());
+            headers.push("Content-Transfer-Encoding: base64".to_string());
+            
+            let mut message = headers.join("\r\n");
+            message.push_str("\r\n\r\n");
+            message.push_str(&encode_base64_lines(self.body.as_bytes()));
+            return message;
+        }
+        
+        let boundary = format!("----=_Part_{}", uuid::Uuid::new_v4().simple());
+        headers.push(format!("Content-Type: multipart/mixed; boundary=\"{}\"", boundary));
+        
+        let mut message = headers.join("\r\n");
+        message.push_str("\r\n\r\n");
+        
+        // 正文部分
+        message.push_str(&format!("--{}\r\n", boundary));
+        message.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+        message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        message.push_str(&encode_base64_lines(self.body.as_bytes()));
+        message.push_str("\r\n");
+        
+        // 附件部分
+        for attachment in &self.attachments {
+            let filename = encode_header_text(&attachment.filename).replace('"', "");
+            message.push_str(&format!("--{}\r\n", boundary));
+            message.push_str(&format!(
+                "Content-Type: {}; name=\"{}\"\r\n",
+                attachment.content_type, filename
+            ));
+            message.push_str(&format!(
+                "Content-Disposition: attachment; filename=\"{}\"\r\n",
+                filename
+            ));
+            message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+            message.push_str(&encode_base64_lines(&attachment.data));
+            message.push_str("\r\n");
+        }
+        
+        message.push_str(&format!("--{}--", boundary));
+        message
+    }
+}
+
+/// 重新发送邮件时添加的Resent-*字段（RFC 5322 3.6.6）
+pub struct ResentHeaders {
+    /// Resent-Message-ID（含尖括号），由 `generate_message_id` 生成
+    pub message_id: String,
+    /// Resent-From（已格式化）
+    pub from: String,
+    /// Resent-To，即本次实际的收件人
+    pub to: Vec<String>,
+}
+
+impl ResentHeaders {
+    /// 在原始邮件最前面添加Resent-*字段块，原有头部和正文（包括附件）保持不变
+    /// 原邮件中的换行统一为CRLF
+    pub fn prepend_to(&self, raw: &str) -> String {
+        let mut lines = vec![
+            format!("Resent-Date: {}", chrono::Local::now().to_rfc2822()),
+            format!("Resent-From: {}", self.from),
+            format!("Resent-To: {}", self.to.join(", ")),
+            format!("Resent-Message-ID: {}", self.message_id),
+        ];
+        lines.extend(raw.lines().map(str::to_string));
+        lines.join("\r\n")
+    }
+}
+
+/// base64编码并按76字符折行
+pub fn encode_base64_lines(data: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(data);
+    encoded
+        .as_bytes()
+        .chunks(76)
+        .map(|line| std::str::from_utf8(line).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
@@ -0,0 +1,175 @@
+//! 邮件预览摘要模块
+//! 
+//! 从正文生成列表中显示的简短预览：去除HTML标签、引用的回复内容，并合并空白
+
+use base64::{engine::general_purpose, Engine as _};
+
+/// 摘要的最大字符数
+pub const SNIPPET_LENGTH: usize = 140;
+/// 悬停预览的最大字符数
+pub const PREVIEW_LENGTH: usize = 500;
+
+/// 粗略判断正文是否为HTML
+fn looks_like_html(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    lower.contains("<html") || lower.contains("<body") || lower.contains("<div")
+        || lower.contains("<p>") || lower.contains("<br") || lower.contains("</")
+}
+
+/// 将HTML转换为纯文本
+/// 去除标签以及 `<style>`、`<script>` 的内容，块级标签转换为换行，并解码常见实体
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('>') else {
+            rest = "";
+            break;
+        };
+        
+        let tag = after[..end].trim().to_ascii_lowercase();
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        rest = &after[end + 1..];
+        
+        match name {
+            // 样式和脚本的内容不是正文，跳到对应的结束标签之后
+            "style" | "script" if !tag.starts_with('/') => {
+                let closing = format!("</{}", name);
+                rest = match rest.to_ascii_lowercase().find(&closing) {
+                    Some(position) => {
+                        let tail = &rest[position..];
+                        tail.find('>').map(|close| &tail[close + 1..]).unwrap_or("")
+                    }
+                    None => "",
+                };
+            }
+            "br" | "p" | "div" | "tr" | "li" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" => {
+                text.push('\n');
+            }
+            _ => {}
+        }
+    }
+    text.push_str(rest);
+    
+    decode_entities(&text)
+}
+
+/// 解码常见的HTML实体
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 生成邮件预览摘要
+/// 
+/// # 参数
+/// * `body` - 邮件正文（纯文本或HTML）
+pub fn build_snippet(body: &str) -> String {
+    build_preview(body, SNIPPET_LENGTH)
+}
+
+/// 生成最多 `max_chars` 个字符的纯文本预览，超出时以省略号结尾
+pub fn build_preview(body: &str, max_chars: usize) -> String {
+    let text = if looks_like_html(body) {
+        html_to_text(body)
+    } else {
+        body.to_string()
+    };
+    
+    // 跳过以 ">" 开头的引用行；全部是引用时保留原文
+    let unquoted: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .collect();
+    let source = if unquoted.iter().all(|line| line.trim().is_empty()) {
+        text.lines().collect::<Vec<_>>().join(" ")
+    } else {
+        unquoted.join(" ")
+    };
+    
+    let collapsed = source.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+    
+    let mut snippet: String = collapsed.chars().take(max_chars).collect();
+    snippet.push('…');
+    snippet
+}
+
+/// 由邮件头部和截断的正文开头生成预览
+/// 
+/// 截断位置可能落在多部分邮件的结束分隔线之前、base64编码单元或多字节字符的中间：
+/// 取第一个文本部分，base64只解码完整的4字符单元，末尾不完整的字符丢弃
+/// 
+/// # 参数
+/// * `header` - 完整的邮件头部（`BODY[HEADER]`）
+/// * `partial_text` - 正文开头部分（`BODY[TEXT]<0.n>`）
+pub fn partial_preview(header: &[u8], partial_text: &[u8]) -> String {
+    let mut raw = Vec::with_capacity(header.len() + partial_text.len());
+    raw.extend_from_slice(header);
+    raw.extend_from_slice(partial_text);
+    
+    let Ok(parsed) = mailparse::parse_mail(&raw) else {
+        return build_preview(&String::from_utf8_lossy(partial_text), PREVIEW_LENGTH);
+    };
+    let Some(part) = first_text_part(&parsed) else {
+        return String::new();
+    };
+    
+    let bytes = match part.get_body_encoded() {
+        mailparse::body::Body::Base64(body) => {
+            let mut encoded: Vec<u8> = body
+                .get_raw()
+                .iter()
+                .copied()
+                .filter(|byte| !byte.is_ascii_whitespace())
+                .collect();
+            encoded.truncate(encoded.len() / 4 * 4);
+            general_purpose::STANDARD.decode(encoded).unwrap_or_default()
+        }
+        _ => part.get_body_raw().unwrap_or_default(),
+    };
+    
+    // 不是最后一段输入，解码器保留末尾不完整的字节序列而不是输出替换字符
+    let encoding = encoding_rs::Encoding::for_label(part.ctype.charset.trim().as_bytes())
+        .unwrap_or(encoding_rs::UTF_8);
+    let mut decoder = encoding.new_decoder();
+    let mut text = String::with_capacity(
+        decoder.max_utf8_buffer_length(bytes.len()).unwrap_or(bytes.len() * 3),
+    );
+    let _ = decoder.decode_to_string(&bytes, &mut text, false);
+    
+    build_preview(&text, PREVIEW_LENGTH)
+}
+
+/// 第一个text/plain部分，没有时为第一个其他文本部分（如text/html）
+fn first_text_part<'a, 'b>(mail: &'b mailparse::ParsedMail<'a>) -> Option<&'b mailparse::ParsedMail<'a>> {
+    let mut leaves = Vec::new();
+    collect_leaves(mail, &mut leaves);
+    leaves
+        .iter()
+        .find(|part| part.ctype.mimetype == "text/plain")
+        .or_else(|| leaves.iter().find(|part| part.ctype.mimetype.starts_with("text/")))
+        .copied()
+}
+
+fn collect_leaves<'a, 'b>(mail: &'b mailparse::ParsedMail<'a>, leaves: &mut Vec<&'b mailparse::ParsedMail<'a>>) {
+    if mail.subparts.is_empty() {
+        leaves.push(mail);
+    }
+    for part in &mail.subparts {
+        collect_leaves(part, leaves);
+    }
+}
@@ -0,0 +1,116 @@
+//! 邮件休眠模块
+//! 
+//! 休眠只是本地状态，保存在存储的 `snoozed` 中，服务器上的邮件不受影响。
+//! 获取的邮件列表中休眠未到期的邮件带 `snoozedUntil` 字段，由界面隐藏；
+//! 到期后由后台任务移除并通知界面
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+use crate::persist;
+
+/// 休眠的邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snooze {
+    /// 邮件所属账户
+    pub account: String,
+    pub folder: String,
+    /// 邮件UID
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// 唤醒时间（毫秒）
+    pub until: i64,
+    /// 设置休眠的时间（毫秒）
+    #[serde(rename = "snoozedAt")]
+    pub snoozed_at: i64,
+}
+
+impl Snooze {
+    fn is_same(&self, account: &str, folder: &str, message_id: &str) -> bool {
+        self.account == account && self.folder == folder && self.message_id == message_id
+    }
+}
+
+/// 休眠列表读-改-写锁，避免命令与后台任务同时修改
+#[derive(Default)]
+pub struct SnoozeLock(async_std::sync::Mutex<()>);
+
+fn load(app: &tauri::AppHandle) -> Vec<Snooze> {
+    app.store("store.json")
+        .ok()
+        .and_then(|store| store.get("snoozed"))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, snoozed: &[Snooze]) -> Result<(), String> {
+    let store = app.store("store.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    store.set("snoozed", serde_json::to_value(snoozed).unwrap());
+    persist::save(app, &store, "store.json")
+        .map_err(|e| format!("Failed to save snoozed emails: {}", e))
+}
+
+/// 账户的全部休眠邮件，按唤醒时间排列
+pub fn list(app: &tauri::AppHandle, account: &str) -> Vec<Snooze> {
+    let mut snoozed: Vec<Snooze> = load(app)
+        .into_iter()
+        .filter(|snooze| snooze.account == account)
+        .collect();
+    snoozed.sort_by_key(|snooze| snooze.until);
+    snoozed
+}
+
+/// 文件夹中休眠未到期的邮件，UID到唤醒时间的映射
+pub fn active_in(app: &tauri::AppHandle, account: &str, folder: &str) -> HashMap<String, i64> {
+    let now = chrono::Utc::now().timestamp_millis();
+    load(app)
+        .into_iter()
+        .filter(|snooze| snooze.account == account && snooze.folder == folder && snooze.until > now)
+        .map(|snooze| (snooze.message_id, snooze.until))
+        .collect()
+}
+
+/// 休眠邮件，同一封邮件再次休眠时更新唤醒时间
+pub async fn add(app: &tauri::AppHandle, snooze: Snooze) -> Result<(), String> {
+    let lock = app.state::<SnoozeLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut snoozed = load(app);
+    snoozed.retain(|existing| !existing.is_same(&snooze.account, &snooze.folder, &snooze.message_id));
+    snoozed.push(snooze);
+    save(app, &snoozed)
+}
+
+/// 取消休眠，返回邮件是否处于休眠中
+pub async fn remove(app: &tauri::AppHandle, account: &str, folder: &str, message_id: &str) -> Result<bool, String> {
+    let lock = app.state::<SnoozeLock>();
+    let _guard = lock.0.lock().await;
+    
+    let mut snoozed = load(app);
+    let before = snoozed.len();
+    snoozed.retain(|existing| !existing.is_same(account, folder, message_id));
+    if snoozed.len() == before {
+        return Ok(false);
+    }
+    save(app, &snoozed)?;
+    Ok(true)
+}
+
+/// 移除并返回已到唤醒时间的邮件（所有账户）
+pub async fn take_expired(app: &tauri::AppHandle) -> Result<Vec<Snooze>, String> {
+    let lock = app.state::<SnoozeLock>();
+    let _guard = lock.0.lock().await;
+    
+    let now = chrono::Utc::now().timestamp_millis();
+    let (expired, remaining): (Vec<Snooze>, Vec<Snooze>) = load(app)
+        .into_iter()
+        .partition(|snooze| snooze.until <= now);
+    if !expired.is_empty() {
+        save(app, &remaining)?;
+    }
+    Ok(expired)
+}
@@ -0,0 +1,196 @@
+//! 邮件会话模块
+//! 
+//! 服务器支持THREAD扩展（RFC 5256）时由服务器计算会话树；
+//! 否则根据Message-ID、In-Reply-To和References头部在本地构建，两种方式返回相同的树结构
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+/// 服务器返回的会话树的最大嵌套深度，超过时停止解析
+const MAX_DEPTH: usize = 256;
+
+/// 会话树节点
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadNode {
+    /// 邮件UID；服务器返回的会话中，不在文件夹内的共同父邮件为None
+    pub id: Option<String>,
+    /// 回复该邮件的邮件
+    pub children: Vec<ThreadNode>,
+}
+
+/// 本地构建会话所需的头部
+pub struct ReferenceHeaders {
+    pub uid: u32,
+    pub message_id: Option<String>,
+    /// References中的Message-ID，按从远到近排列，In-Reply-To附加在末尾
+    pub references: Vec<String>,
+}
+
+impl ReferenceHeaders {
+    /// 从 `Message-ID`、`In-Reply-To` 和 `References` 头部提取
+    pub fn from_headers(uid: u32, headers: &[mailparse::MailHeader]) -> Self {
+        use mailparse::MailHeaderMap;
+        
+        let ids = |name: &str| {
+            headers
+                .get_first_value(name)
+                .map(|value| message_ids(&value))
+                .unwrap_or_default()
+        };
+        let mut references = ids("References");
+        for id in ids("In-Reply-To") {
+            if !references.contains(&id) {
+                references.push(id);
+            }
+        }
+        
+        Self {
+            uid,
+            message_id: ids("Message-ID").into_iter().next(),
+            references,
+        }
+    }
+}
+
+/// 提取头部值中所有尖括号括起的Message-ID
+fn message_ids(value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let id = rest[start + 1..start + end].trim();
+        if !id.is_empty() {
+            ids.push(id.to_string());
+        }
+        rest = &rest[start + end + 1..];
+    }
+    ids
+}
+
+/// 解析 `* THREAD` 响应行
+/// 
+/// `(3 6 (4 23)(44 7 96))` 中连续的UID依次为上一封的回复，括号中的子列表都是最后一个UID的回复；
+/// 以子列表开头的会话（如 `((3)(5))`）的根节点为缺失的父邮件
+pub fn parse_thread_response(line: &str) -> Vec<ThreadNode> {
+    let bytes = line.as_bytes();
+    let mut pos = line.find('(').unwrap_or(bytes.len());
+    let mut threads = Vec::new();
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b' ' => pos += 1,
+            b'(' => match parse_thread_list(bytes, &mut pos, 0) {
+                Some(thread) => threads.push(thread),
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    threads
+}
+
+fn parse_thread_list(bytes: &[u8], pos: &mut usize, depth: usize) -> Option<ThreadNode> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    // 跳过左括号
+    *pos += 1;
+    
+    let mut chain = Vec::new();
+    let mut children = Vec::new();
+    loop {
+        match bytes.get(*pos)? {
+            b' ' => *pos += 1,
+            b')' => {
+                *pos += 1;
+                break;
+            }
+            b'(' => children.push(parse_thread_list(bytes, pos, depth + 1)?),
+            b'0'..=b'9' => {
+                let start = *pos;
+                while bytes.get(*pos).is_some_and(u8::is_ascii_digit) {
+                    *pos += 1;
+                }
+                chain.push(String::from_utf8_lossy(&bytes[start..*pos]).into_owned());
+            }
+            _ => return None,
+        }
+    }
+    
+    let Some(last) = chain.pop() else {
+        return Some(ThreadNode { id: None, children });
+    };
+    let mut node = ThreadNode { id: Some(last), children };
+    while let Some(id) = chain.pop() {
+        node = ThreadNode {
+            id: Some(id),
+            children: vec![node],
+        };
+    }
+    Some(node)
+}
+
+/// 根据引用关系在本地构建会话树
+/// 
+/// 每封邮件的父邮件是References（及In-Reply-To）中最近的、在文件夹内的邮件；
+/// 引用的邮件都不在文件夹内时为会话的根。会话和回复都按UID（即到达顺序）排列
+pub fn build_threads(mut messages: Vec<ReferenceHeaders>) -> Vec<ThreadNode> {
+    messages.sort_by_key(|message| message.uid);
+    
+    let mut by_message_id: HashMap<&str, u32> = HashMap::new();
+    for message in &messages {
+        if let Some(id) = &message.message_id {
+            by_message_id.entry(id.as_str()).or_insert(message.uid);
+        }
+    }
+    
+    let mut parents: HashMap<u32, u32> = HashMap::new();
+    for message in &messages {
+        let parent = message
+            .references
+            .iter()
+            .rev()
+            .filter_map(|id| by_message_id.get(id.as_str()).copied())
+            .find(|&candidate| candidate != message.uid && !is_ancestor(&parents, message.uid, candidate));
+        if let Some(parent) = parent {
+            parents.insert(message.uid, parent);
+        }
+    }
+    
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut roots = Vec::new();
+    for message in &messages {
+        match parents.get(&message.uid) {
+            Some(parent) => children.entry(*parent).or_default().push(message.uid),
+            None => roots.push(message.uid),
+        }
+    }
+    
+    roots.into_iter().map(|uid| build_node(uid, &children)).collect()
+}
+
+/// `uid` 是否为 `node` 的祖先（或就是 `node`），用于避免引用关系形成环
+fn is_ancestor(parents: &HashMap<u32, u32>, uid: u32, node: u32) -> bool {
+    let mut current = node;
+    loop {
+        if current == uid {
+            return true;
+        }
+        match parents.get(&current) {
+            Some(&parent) => current = parent,
+            None => return false,
+        }
+    }
+}
+
+fn build_node(uid: u32, children: &HashMap<u32, Vec<u32>>) -> ThreadNode {
+    ThreadNode {
+        id: Some(uid.to_string()),
+        children: children
+            .get(&uid)
+            .map(|replies| replies.iter().map(|&reply| build_node(reply, children)).collect())
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,94 @@
+//! 未读数模块
+//! 
+//! 缓存每个账户各文件夹的未读数，由后台刷新定时更新，
+//! 总未读数显示在托盘提示（macOS上也显示在托盘标题）和Dock角标上
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::i18n::tr;
+
+/// 托盘图标的ID，更新提示和标题时按ID查找
+pub const TRAY_ID: &str = "main";
+
+/// 托盘提示的默认文字
+const TRAY_TOOLTIP: &str = "2925邮箱管理系统";
+
+/// 各文件夹的未读数及总数
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadCounts {
+    /// 文件夹名称到未读数的映射
+    pub folders: HashMap<String, u32>,
+    pub total: u32,
+    /// 统计时间（毫秒）
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+}
+
+impl UnreadCounts {
+    pub fn new(folders: HashMap<String, u32>) -> Self {
+        let total = folders.values().sum();
+        Self {
+            folders,
+            total,
+            updated_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// 按账户缓存的未读数，作为应用状态管理
+#[derive(Default)]
+pub struct UnreadCountsCache(async_std::sync::Mutex<HashMap<String, UnreadCounts>>);
+
+impl UnreadCountsCache {
+    pub async fn get(&self, account: &str) -> Option<UnreadCounts> {
+        self.0.lock().await.get(account).cloned()
+    }
+
+    pub async fn set(&self, account: &str, counts: UnreadCounts) {
+        self.0.lock().await.insert(account.to_string(), counts);
+    }
+}
+
+/// 有未读邮件时的托盘提示
+fn unread_tooltip(total: u32) -> String {
+    format!("{} - {} {}", tr(TRAY_TOOLTIP), total, tr("封未读"))
+}
+
+/// 在托盘和Dock角标上显示总未读数，为0时清除
+/// 平台不支持的部分（如Windows的托盘标题）会被忽略
+pub fn show_total(app: &tauri::AppHandle, total: u32) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let (tooltip, title) = if total > 0 {
+            (unread_tooltip(total), Some(total.to_string()))
+        } else {
+            (tr(TRAY_TOOLTIP).to_string(), None)
+        };
+        if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+            log::debug!("更新托盘提示失败: {}", e);
+        }
+        if let Err(e) = tray.set_title(title) {
+            log::debug!("更新托盘标题失败: {}", e);
+        }
+    }
+    
+    if let Some(window) = app.get_webview_window("main") {
+        let count = (total > 0).then_some(total as i64);
+        if let Err(e) = window.set_badge_count(count) {
+            log::debug!("更新角标失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tooltip_shows_unread_count() {
+        // 测试进程不切换语言，使用默认的简体中文
+        assert_eq!(unread_tooltip(3), "2925邮箱管理系统 - 3 封未读");
+    }
+}
@@ -0,0 +1,197 @@
+//! 邮件列表退订模块
+//! 
+//! 解析 `List-Unsubscribe`（RFC 2369）和 `List-Unsubscribe-Post`（RFC 8058）头部。
+//! 只接受http(s)和mailto链接；声明了一键退订时通过HTTPS POST退订，否则向mailto地址发送退订邮件
+
+use std::time::Duration;
+
+use async_std::io::prelude::BufReadExt;
+use async_std::io::{BufReader, WriteExt};
+use async_std::net::TcpStream;
+use mailparse::MailHeaderMap;
+use serde::{Deserialize, Serialize};
+use tauri::Url;
+
+use crate::i18n::tr;
+
+/// 一键退订请求的超时时间
+const ONE_CLICK_TIMEOUT: Duration = Duration::from_secs(30);
+/// 一键退订POST的请求体（RFC 8058 3.1）
+const ONE_CLICK_BODY: &str = "List-Unsubscribe=One-Click";
+
+/// 邮件声明的退订方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unsubscribe {
+    /// 退订邮件地址（完整的mailto链接）
+    pub mailto: Option<String>,
+    /// 退订网页链接
+    pub http: Option<String>,
+    /// 声明了 `List-Unsubscribe-Post: List-Unsubscribe=One-Click` 且网页链接为HTTPS
+    #[serde(rename = "oneClick")]
+    pub one_click: bool,
+}
+
+/// mailto链接中的收件人、主题和正文
+pub struct MailtoTarget {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// 从邮件头部解析退订方式，没有可用的链接时返回None
+/// 每种方式只取第一个有效链接
+pub fn from_headers(headers: &[mailparse::MailHeader]) -> Option<Unsubscribe> {
+    let list = headers.get_first_value("List-Unsubscribe")?;
+    
+    let mut mailto = None;
+    let mut http = None;
+    // 链接以尖括号括起，逗号分隔；尖括号外的内容（如注释）忽略
+    let mut rest = list.as_str();
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let link = rest[start + 1..start + end].trim();
+        rest = &rest[start + end + 1..];
+        
+        let Ok(url) = Url::parse(link) else {
+            continue;
+        };
+        match url.scheme() {
+            "http" | "https" if http.is_none() && url.host_str().is_some_and(|host| !host.is_empty()) => {
+                http = Some(url.to_string());
+            }
+            "mailto" if mailto.is_none() && parse_mailto(link).is_some() => {
+                mailto = Some(link.to_string());
+            }
+            _ => {}
+        }
+    }
+    if mailto.is_none() && http.is_none() {
+        return None;
+    }
+    
+    let one_click = headers
+        .get_first_value("List-Unsubscribe-Post")
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case(ONE_CLICK_BODY))
+        && http.as_deref().is_some_and(|link| link.starts_with("https://"));
+    
+    Some(Unsubscribe { mailto, http, one_click })
+}
+
+/// 解析mailto链接，收件人地址无效时返回None
+/// 没有指定主题时使用 `unsubscribe`
+pub fn parse_mailto(link: &str) -> Option<MailtoTarget> {
+    let url = Url::parse(link).ok()?;
+    if url.scheme() != "mailto" {
+        return None;
+    }
+    
+    let to = percent_decode(url.path())?;
+    let to = to.trim();
+    if !crate::smtp::is_valid_address(to) {
+        return None;
+    }
+    
+    let mut subject = None;
+    let mut body = String::new();
+    for (key, value) in url.query_pairs() {
+        if key.eq_ignore_ascii_case("subject") {
+            subject = Some(value.into_owned());
+        } else if key.eq_ignore_ascii_case("body") {
+            body = value.into_owned();
+        }
+    }
+    
+    Some(MailtoTarget {
+        to: to.to_string(),
+        subject: subject
+            .filter(|subject| !subject.trim().is_empty())
+            .unwrap_or_else(|| "unsubscribe".to_string()),
+        body,
+    })
+}
+
+/// 解码 `%XX` 转义，结果不是有效的UTF-8时返回None
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// 向HTTPS链接发送一键退订POST（RFC 8058），不跟随重定向
+/// 服务器返回2xx或3xx时视为已退订
+pub async fn post_one_click(link: &str) -> Result<(), String> {
+    let url = Url::parse(link).map_err(|e| format!("{}: {}", tr("退订链接无效"), e))?;
+    let host = match url.host_str() {
+        Some(host) if url.scheme() == "https" && !host.is_empty() => host.to_string(),
+        _ => return Err(format!("{}: {}", tr("退订链接无效"), link)),
+    };
+    let port = url.port_or_known_default().unwrap_or(443);
+    
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    let host_header = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.clone(),
+    };
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        target,
+        host_header,
+        ONE_CLICK_BODY.len(),
+        ONE_CLICK_BODY,
+    );
+    
+    let exchange = async {
+        let tcp_stream = TcpStream::connect((host.as_str(), port))
+            .await
+            .map_err(|e| format!("{}: {}", tr("退订失败"), e))?;
+        let mut tls_stream = async_native_tls::TlsConnector::new()
+            .connect(host.as_str(), tcp_stream)
+            .await
+            .map_err(|e| format!("{}: {}", tr("退订失败"), e))?;
+        tls_stream.write_all(request.as_bytes())
+            .await
+            .map_err(|e| format!("{}: {}", tr("退订失败"), e))?;
+        tls_stream.flush()
+            .await
+            .map_err(|e| format!("{}: {}", tr("退订失败"), e))?;
+        
+        // 只需要状态行，例如 `HTTP/1.1 200 OK`
+        let mut status_line = String::new();
+        BufReader::new(tls_stream)
+            .read_line(&mut status_line)
+            .await
+            .map_err(|e| format!("{}: {}", tr("退订失败"), e))?;
+        Ok::<String, String>(status_line)
+    };
+    let status_line = async_std::future::timeout(ONE_CLICK_TIMEOUT, exchange)
+        .await
+        .map_err(|_| format!("{} ({}s)", tr("退订请求超时"), ONE_CLICK_TIMEOUT.as_secs()))??;
+    
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("{}: {}", tr("退订失败"), status_line.trim()))?;
+    if !(200..400).contains(&status) {
+        return Err(format!("{}: HTTP {}", tr("退订失败"), status));
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! IMAP修改版UTF-7编码模块（RFC 3501 5.1.3）
+//! 
+//! 服务器以修改版UTF-7传输非ASCII文件夹名称（如 `已发送` 为 `&XfJT0ZAB-`），
+//! 返回给界面前解码，向服务器发送文件夹名称前编码
+
+use base64::{engine::general_purpose, Engine as _};
+
+/// 修改版base64使用 `,` 代替 `/`
+fn to_standard_base64(encoded: &str) -> String {
+    encoded.replace(',', "/")
+}
+
+/// 将修改版UTF-7文件夹名称解码为Unicode
+/// 无法解码的片段保持原样
+pub fn decode(name: &str) -> String {
+    let mut decoded = String::with_capacity(name.len());
+    let mut rest = name;
+    
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('-') else {
+            // 没有结束符，按原样保留
+            decoded.push_str(&rest[start..]);
+            return decoded;
+        };
+        
+        let segment = &after[..end];
+        if segment.is_empty() {
+            // `&-` 表示字符 `&`
+            decoded.push('&');
+        } else {
+            match decode_segment(segment) {
+                Some(text) => decoded.push_str(&text),
+                None => decoded.push_str(&rest[start..start + end + 2]),
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    decoded.push_str(rest);
+    
+    decoded
+}
+
+fn decode_segment(segment: &str) -> Option<String> {
+    let bytes = general_purpose::STANDARD_NO_PAD
+        .decode(to_standard_base64(segment))
+        .ok()?;
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+/// 将Unicode文件夹名称编码为修改版UTF-7
+pub fn encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    let mut pending: Vec<u16> = Vec::new();
+    
+    let flush = |pending: &mut Vec<u16>, encoded: &mut String| {
+        if pending.is_empty() {
+            return;
+        }
+        let bytes: Vec<u8> = pending.iter().flat_map(|unit| unit.to_be_bytes()).collect();
+        encoded.push('&');
+        encoded.push_str(&general_purpose::STANDARD_NO_PAD.encode(bytes).replace('/', ","));
+        encoded.push('-');
+        pending.clear();
+    };
+    
+    for c in name.chars() {
+        if (' '..='~').contains(&c) {
+            flush(&mut pending, &mut encoded);
+            if c == '&' {
+                encoded.push_str("&-");
+            } else {
+                encoded.push(c);
+            }
+        } else {
+            let mut buffer = [0u16; 2];
+            pending.extend_from_slice(c.encode_utf16(&mut buffer));
+        }
+    }
+    flush(&mut pending, &mut encoded);
+    
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_and_decodes_chinese_folder() {
+        assert_eq!(encode("已发送"), "&XfJT0ZAB-");
+        assert_eq!(decode("&XfJT0ZAB-"), "已发送");
+        assert_eq!(decode("INBOX/&XfJT0ZAB-/2026"), "INBOX/已发送/2026");
+    }
+
+    #[test]
+    fn ampersand_is_escaped() {
+        assert_eq!(encode("A&B"), "A&-B");
+        assert_eq!(decode("&-"), "&");
+        assert_eq!(decode("A&-B&-"), "A&B&");
+    }
+
+    #[test]
+    fn round_trips_mixed_names() {
+        for name in ["收件箱", "Work & 工作", "📬 Archive", "~peter/mail/台北/日本語", "plain"] {
+            assert_eq!(decode(&encode(name)), name);
+        }
+        // 修改版base64中 `/` 写作 `,`
+        assert!(encode("台北").contains(','), "{}", encode("台北"));
+    }
+
+    #[test]
+    fn malformed_input_is_kept() {
+        // 缺少结束符
+        assert_eq!(decode("Sent&XfJT0ZAB"), "Sent&XfJT0ZAB");
+        // 不是base64
+        assert_eq!(decode("a&!!-b"), "a&!!-b");
+        // 解码后字节数为奇数
+        assert_eq!(decode("&AGEA-"), "&AGEA-");
+        // 单独的低位代理项
+        assert_eq!(decode("&3AA-"), "&3AA-");
+    }
+}
@@ -0,0 +1,73 @@
+//! 自动回复状态模块
+//! 
+//! 通过IMAP METADATA扩展（RFC 5464）读取服务器级的自动回复条目，只读不写。
+//! 自动回复规则本身由Sieve管理；这里约定正文保存在 `/private/vacation`，
+//! 启用状态保存在 `/private/vacation/enabled`。服务器不支持METADATA时返回不支持
+
+use serde::Serialize;
+
+use crate::i18n::tr;
+use crate::imap_pool::ImapSession;
+
+/// 自动回复正文的条目
+const TEXT_ENTRY: &str = "/private/vacation";
+/// 自动回复启用状态的条目
+const ENABLED_ENTRY: &str = "/private/vacation/enabled";
+
+/// 服务器上的自动回复状态
+#[derive(Debug, Clone, Serialize)]
+pub struct VacationStatus {
+    /// 服务器是否支持读取（声明了METADATA或METADATA-SERVER）
+    pub supported: bool,
+    /// 自动回复是否启用；不支持时为false
+    pub enabled: bool,
+    /// 自动回复正文，没有设置时为None
+    pub text: Option<String>,
+}
+
+impl VacationStatus {
+    fn unsupported() -> Self {
+        Self {
+            supported: false,
+            enabled: false,
+            text: None,
+        }
+    }
+}
+
+/// 读取自动回复状态
+/// 
+/// 没有启用状态条目时，设置了正文即视为启用
+pub async fn fetch(imap_session: &mut ImapSession) -> Result<VacationStatus, String> {
+    let capabilities = imap_session
+        .capabilities()
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取服务器能力失败"), e))?;
+    if !capabilities.has_str("METADATA") && !capabilities.has_str("METADATA-SERVER") {
+        return Ok(VacationStatus::unsupported());
+    }
+    
+    // 邮箱名为空字符串表示服务器级条目
+    let entries = imap_session
+        .get_metadata("", "", &format!("({} {})", TEXT_ENTRY, ENABLED_ENTRY))
+        .await
+        .map_err(|e| format!("{}: {}", tr("获取自动回复状态失败"), e))?;
+    
+    let value = |name: &str| {
+        entries
+            .iter()
+            .find(|entry| entry.entry.eq_ignore_ascii_case(name))
+            .and_then(|entry| entry.value.clone())
+    };
+    let text = value(TEXT_ENTRY).filter(|text| !text.trim().is_empty());
+    let enabled = match value(ENABLED_ENTRY) {
+        Some(flag) => matches!(flag.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"),
+        None => text.is_some(),
+    };
+    
+    Ok(VacationStatus {
+        supported: true,
+        enabled,
+        text,
+    })
+}
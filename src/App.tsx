@@ -15,6 +15,7 @@ import EmailDetail from './components/Email/EmailDetail';
 import SubEmailManager from './components/SubEmail/SubEmailManager';
 import LoadingSpinner from './components/Common/LoadingSpinner';
 import AnimatedButton from './components/Common/AnimatedButton';
+import CloseConfirmDialog from './components/App/CloseConfirmDialog';
 
 /**
  * 应用容器样式
@@ -90,6 +91,37 @@ const UserEmail = styled.span`
   font-weight: 500;
 `;
 
+/**
+ * 提示横幅样式
+ */
+const WarningBanner = styled.div`
+  display: flex;
+  justify-content: space-between;
+  align-items: center;
+  gap: 12px;
+  margin: 12px 12px 0;
+  padding: 8px 12px;
+  background: rgba(245, 158, 11, 0.12);
+  border: 1px solid rgba(245, 158, 11, 0.35);
+  border-radius: 8px;
+  color: #b45309;
+  font-size: 13px;
+  flex-shrink: 0;
+`;
+
+/**
+ * 提示横幅关闭按钮样式
+ */
+const DismissButton = styled.button`
+  background: none;
+  border: none;
+  color: inherit;
+  font-size: 16px;
+  line-height: 1;
+  cursor: pointer;
+  padding: 0 4px;
+`;
+
 /**
  * 内容区域样式
  * 三栏布局：子邮箱管理 | 邮件列表 | 邮件详情
@@ -213,7 +245,7 @@ Clock.displayName = 'Clock';
  * 处理认证状态和路由逻辑
  */
 const MainApp: React.FC = () => {
-  const { isAuthenticated, loading, session, logout } = useAuth();
+  const { isAuthenticated, loading, session, logout, warning, dismissWarning } = useAuth();
   const [selectedEmailId, setSelectedEmailId] = useState<string | null>(null);
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
 
@@ -289,6 +321,14 @@ const MainApp: React.FC = () => {
           </UserInfo>
         </TopBar>
 
+        {/* 非致命提示，如登录凭据未能保存 */}
+        {warning && (
+          <WarningBanner role="status">
+            <span>⚠️ {warning}</span>
+            <DismissButton onClick={dismissWarning} aria-label="关闭提示">×</DismissButton>
+          </WarningBanner>
+        )}
+
         {/* 主内容区域 - 三栏布局 */}
         <ContentArea>
           {/* 左侧：子邮箱管理面板 */}
@@ -307,7 +347,7 @@ const MainApp: React.FC = () => {
 
           {/* 右侧：邮件详情 */}
           <RightPanel>
-            <EmailDetail email={selectedEmail} />
+            <EmailDetail email={selectedEmail} onEmailSelect={handleEmailSelect} />
           </RightPanel>
         </ContentArea>
 
@@ -332,6 +372,8 @@ const App: React.FC = () => {
       <EmailProvider>
         <SubEmailProvider>
           <MainApp />
+          {/* 关闭按钮行为为ask时询问退出还是隐藏到托盘，登录前后都需要 */}
+          <CloseConfirmDialog />
         </SubEmailProvider>
       </EmailProvider>
     </AuthProvider>
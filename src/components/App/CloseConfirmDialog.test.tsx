@@ -0,0 +1,82 @@
+/**
+ * CloseConfirmDialog组件单元测试
+ * 测试收到关闭请求后的询问和选择
+ */
+
+import React from 'react';
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { render, screen, fireEvent, waitFor, act } from '@testing-library/react';
+import '@testing-library/jest-dom';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import CloseConfirmDialog from './CloseConfirmDialog';
+
+// Mock Tauri commands and events
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(),
+}));
+
+/**
+ * 渲染对话框并返回触发 `close-requested` 事件的函数
+ */
+const renderDialog = async () => {
+  let handler: (() => void) | undefined;
+  vi.mocked(listen).mockImplementation(async (event, callback) => {
+    expect(event).toBe('close-requested');
+    handler = callback as () => void;
+    return () => {};
+  });
+
+  render(<CloseConfirmDialog />);
+  await waitFor(() => expect(handler).toBeDefined());
+  return () => act(() => handler!());
+};
+
+describe('CloseConfirmDialog组件', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('收到关闭请求前不应该显示', async () => {
+    await renderDialog();
+    expect(screen.queryByRole('dialog')).not.toBeInTheDocument();
+  });
+
+  it('选择退出时应该调用quit_app', async () => {
+    const requestClose = await renderDialog();
+    requestClose();
+
+    expect(screen.getByRole('dialog')).toBeInTheDocument();
+    fireEvent.click(screen.getByText('退出'));
+
+    await waitFor(() => {
+      expect(invoke).toHaveBeenCalledWith('quit_app', { remember: false });
+    });
+  });
+
+  it('记住选择并隐藏到托盘时应该调用hide_to_tray', async () => {
+    const requestClose = await renderDialog();
+    requestClose();
+
+    fireEvent.click(screen.getByLabelText('记住我的选择，不再询问'));
+    fireEvent.click(screen.getByText('最小化到托盘'));
+
+    await waitFor(() => {
+      expect(invoke).toHaveBeenCalledWith('hide_to_tray', { remember: true });
+    });
+    expect(screen.queryByRole('dialog')).not.toBeInTheDocument();
+  });
+
+  it('取消时应该关闭对话框且不调用命令', async () => {
+    const requestClose = await renderDialog();
+    requestClose();
+
+    fireEvent.click(screen.getByText('取消'));
+
+    expect(screen.queryByRole('dialog')).not.toBeInTheDocument();
+    expect(invoke).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,175 @@
+/**
+ * 关闭确认对话框组件
+ * 偏好设置 `closeAction` 为ask时，后端在点击关闭按钮后发送 `close-requested` 事件，
+ * 由此对话框询问退出还是隐藏到托盘
+ */
+
+import React, { useEffect, useState } from 'react';
+import styled from 'styled-components';
+import { listen } from '@tauri-apps/api/event';
+import { tauriCommands } from '../../services/tauriCommands';
+import AnimatedButton from '../Common/AnimatedButton';
+
+/**
+ * 遮罩层样式
+ */
+const Overlay = styled.div`
+  position: fixed;
+  top: 0;
+  left: 0;
+  right: 0;
+  bottom: 0;
+  background: rgba(0, 0, 0, 0.4);
+  backdrop-filter: blur(8px);
+  -webkit-backdrop-filter: blur(8px);
+  display: flex;
+  align-items: center;
+  justify-content: center;
+  z-index: 1100;
+`;
+
+/**
+ * 对话框容器样式
+ */
+const Dialog = styled.div`
+  background: rgba(255, 255, 255, 0.85);
+  backdrop-filter: blur(20px) saturate(180%);
+  -webkit-backdrop-filter: blur(20px) saturate(180%);
+  border-radius: 20px;
+  border: 1px solid rgba(255, 255, 255, 0.3);
+  box-shadow:
+    0 8px 32px 0 rgba(139, 92, 246, 0.2),
+    0 0 0 1px rgba(139, 92, 246, 0.1),
+    inset 0 1px 0 0 rgba(255, 255, 255, 0.5);
+  padding: 24px;
+  width: 90%;
+  max-width: 400px;
+`;
+
+/**
+ * 标题样式
+ */
+const Title = styled.h2`
+  color: #6366f1;
+  font-size: 18px;
+  font-weight: 700;
+  margin: 0 0 16px 0;
+`;
+
+/**
+ * "记住我的选择"标签样式
+ */
+const RememberLabel = styled.label`
+  display: flex;
+  align-items: center;
+  gap: 8px;
+  color: #374151;
+  font-size: 13px;
+  cursor: pointer;
+  user-select: none;
+  margin-bottom: 20px;
+`;
+
+/**
+ * 按钮容器样式
+ */
+const ButtonRow = styled.div`
+  display: flex;
+  justify-content: flex-end;
+  gap: 8px;
+`;
+
+/**
+ * 关闭确认对话框组件
+ * 挂载时开始监听 `close-requested` 事件，收到事件后显示
+ */
+export const CloseConfirmDialog: React.FC = () => {
+  const [isOpen, setIsOpen] = useState(false);
+  const [remember, setRemember] = useState(false);
+
+  /**
+   * 监听后端的关闭请求
+   */
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+
+    listen('close-requested', () => {
+      setRemember(false);
+      setIsOpen(true);
+    })
+      .then((stop) => {
+        if (disposed) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        console.error('监听关闭请求失败:', error);
+      });
+
+    return () => {
+      disposed = true;
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  /**
+   * 退出应用
+   */
+  const handleQuit = async () => {
+    try {
+      await tauriCommands.window.quitApp(remember);
+    } catch (error) {
+      console.error('退出应用失败:', error);
+    }
+  };
+
+  /**
+   * 隐藏到托盘
+   */
+  const handleHide = async () => {
+    setIsOpen(false);
+    try {
+      await tauriCommands.window.hideToTray(remember);
+    } catch (error) {
+      console.error('隐藏窗口失败:', error);
+    }
+  };
+
+  if (!isOpen) {
+    return null;
+  }
+
+  return (
+    <Overlay>
+      <Dialog role="dialog" aria-modal="true" aria-labelledby="close-confirm-title">
+        <Title id="close-confirm-title">关闭窗口</Title>
+        <RememberLabel>
+          <input
+            type="checkbox"
+            checked={remember}
+            onChange={(e) => setRemember(e.target.checked)}
+          />
+          记住我的选择，不再询问
+        </RememberLabel>
+        <ButtonRow>
+          <AnimatedButton variant="secondary" size="small" onClick={() => setIsOpen(false)}>
+            取消
+          </AnimatedButton>
+          <AnimatedButton variant="secondary" size="small" onClick={handleHide}>
+            最小化到托盘
+          </AnimatedButton>
+          <AnimatedButton variant="danger" size="small" onClick={handleQuit}>
+            退出
+          </AnimatedButton>
+        </ButtonRow>
+      </Dialog>
+    </Overlay>
+  );
+};
+
+export default CloseConfirmDialog;
@@ -3,3 +3,4 @@
  */
 
 export { AppInitializer } from './AppInitializer';
+export { CloseConfirmDialog } from './CloseConfirmDialog';
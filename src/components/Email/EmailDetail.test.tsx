@@ -0,0 +1,97 @@
+/**
+ * EmailDetail组件单元测试
+ * 测试退信的投递失败卡片和原邮件链接
+ */
+
+import React from 'react';
+import { describe, it, expect, vi } from 'vitest';
+import { render, screen, fireEvent } from '@testing-library/react';
+import '@testing-library/jest-dom';
+import EmailDetail from './EmailDetail';
+import { EmailContext } from '../../contexts/EmailContext';
+import type { Email } from '../../types/email.types';
+
+const original: Email = {
+  id: '41',
+  from: 'user@2925.com',
+  to: 'nobody@example.com',
+  subject: '测试',
+  body: '你好',
+  timestamp: 1760400000000,
+  isRead: true,
+  isSubEmailForwarded: false,
+  messageId: '<original-1@2925.com>',
+};
+
+const bounce: Email = {
+  id: '42',
+  from: 'MAILER-DAEMON@mx.2925.com',
+  to: 'user@2925.com',
+  subject: 'Undelivered Mail Returned to Sender',
+  body: '',
+  timestamp: 1760400060000,
+  isRead: true,
+  isSubEmailForwarded: false,
+  deliveryStatus: {
+    reportingMta: 'mx.2925.com',
+    recipients: [
+      { recipient: 'nobody@example.com', action: 'failed', status: '5.1.1', diagnostic: '550 5.1.1 User unknown' },
+      { recipient: 'later@example.com', action: 'delayed', status: '4.4.1', diagnostic: null },
+    ],
+    originalMessageId: '<original-1@2925.com>',
+  },
+};
+
+/**
+ * 辅助函数：在提供邮件列表的EmailContext中渲染EmailDetail
+ */
+const renderDetail = (email: Email, list: Email[], onEmailSelect?: (emailId: string) => void) => {
+  const context = {
+    list,
+    loading: false,
+    lastFetchTime: 0,
+    error: null,
+    fetchEmails: vi.fn(),
+    markAsRead: vi.fn(),
+    setEmails: vi.fn(),
+    refreshWithMainEmail: vi.fn(),
+  } as unknown as React.ContextType<typeof EmailContext>;
+
+  return render(
+    <EmailContext.Provider value={context}>
+      <EmailDetail email={email} onEmailSelect={onEmailSelect} />
+    </EmailContext.Provider>
+  );
+};
+
+describe('EmailDetail组件', () => {
+  it('退信应该显示投递失败的收件人和原因', () => {
+    renderDetail(bounce, [original, bounce]);
+
+    const card = screen.getByRole('alert');
+    expect(card).toHaveTextContent('投递到 nobody@example.com 失败：550 5.1.1 User unknown');
+    // 只显示失败的收件人
+    expect(card).not.toHaveTextContent('later@example.com');
+  });
+
+  it('点击原邮件链接应该选中原邮件', () => {
+    const onEmailSelect = vi.fn();
+    renderDetail(bounce, [original, bounce], onEmailSelect);
+
+    fireEvent.click(screen.getByText('查看原邮件: 测试'));
+    expect(onEmailSelect).toHaveBeenCalledWith('41');
+  });
+
+  it('原邮件不在列表中时应该只显示Message-ID', () => {
+    renderDetail(bounce, [bounce], vi.fn());
+
+    expect(screen.queryByRole('button')).not.toBeInTheDocument();
+    expect(screen.getByRole('alert')).toHaveTextContent('原邮件: <original-1@2925.com>');
+  });
+
+  it('普通邮件不应该显示投递失败卡片', () => {
+    renderDetail(original, [original]);
+
+    expect(screen.queryByRole('alert')).not.toBeInTheDocument();
+  });
+});
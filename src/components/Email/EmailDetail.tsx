@@ -1,6 +1,6 @@
 /**
  * EmailDetail组件
- * 显示邮件的完整内容，支持标记已读
+ * 显示邮件的完整内容，支持标记已读；退信显示投递失败卡片，可跳转到原邮件
  */
 
 import React, { useContext, useEffect } from 'react';
@@ -135,6 +135,41 @@ const ForwardedBadge = styled(motion.div)`
   word-break: break-all;
 `;
 
+/**
+ * 投递失败卡片样式
+ */
+const DeliveryFailureCard = styled.div`
+  display: flex;
+  flex-direction: column;
+  gap: 6px;
+  padding: 10px 12px;
+  background: rgba(239, 68, 68, 0.1);
+  border: 1px solid rgba(239, 68, 68, 0.3);
+  border-radius: 8px;
+  color: #b91c1c;
+  font-size: 12px;
+  line-height: 1.4;
+  word-break: break-word;
+`;
+
+/**
+ * 原邮件链接样式
+ */
+const OriginalLink = styled.button`
+  align-self: flex-start;
+  padding: 0;
+  border: none;
+  background: none;
+  color: #6366f1;
+  font-size: 12px;
+  text-decoration: underline;
+  cursor: pointer;
+
+  &:hover {
+    color: #4f46e5;
+  }
+`;
+
 /**
  * 邮件正文容器样式
  */
@@ -278,6 +313,11 @@ interface EmailDetailProps {
    */
   email: Email | null;
   
+  /**
+   * 选中其他邮件的回调，退信卡片中点击原邮件时调用
+   */
+  onEmailSelect?: (emailId: string) => void;
+  
   /**
    * 自定义类名
    */
@@ -324,6 +364,7 @@ const processEmailBody = (body: string): string => {
  * 自动标记邮件为已读，支持玻璃风格设计
  * 
  * @param email - 邮件数据对象（null表示未选中邮件）
+ * @param onEmailSelect - 选中其他邮件的回调
  * @param className - 自定义CSS类名
  * 
  * @example
@@ -331,6 +372,7 @@ const processEmailBody = (body: string): string => {
  */
 const EmailDetail: React.FC<EmailDetailProps> = ({
   email,
+  onEmailSelect,
   className,
 }) => {
   // 获取邮件上下文
@@ -340,7 +382,7 @@ const EmailDetail: React.FC<EmailDetailProps> = ({
     throw new Error('EmailDetail必须在EmailProvider内使用');
   }
 
-  const { markAsRead } = emailContext;
+  const { markAsRead, list } = emailContext;
 
   /**
    * 当邮件打开时自动标记为已读
@@ -370,6 +412,15 @@ const EmailDetail: React.FC<EmailDetailProps> = ({
     );
   }
 
+  /**
+   * 退信中投递失败的收件人，以及列表中按Message-ID找到的原邮件
+   */
+  const failedRecipients = email.deliveryStatus?.recipients.filter(r => r.action === 'failed') ?? [];
+  const originalMessageId = email.deliveryStatus?.originalMessageId;
+  const originalEmail = originalMessageId
+    ? list.find(e => e.messageId === originalMessageId)
+    : undefined;
+
   /**
    * 渲染邮件详情
    */
@@ -414,6 +465,25 @@ const EmailDetail: React.FC<EmailDetailProps> = ({
               🔄 此邮件转发自子邮箱: {email.originalSubEmail}
             </ForwardedBadge>
           )}
+
+          {/* 退信的投递失败卡片 */}
+          {failedRecipients.length > 0 && (
+            <DeliveryFailureCard role="alert">
+              {failedRecipients.map(recipient => (
+                <div key={recipient.recipient}>
+                  ⚠️ 投递到 {recipient.recipient} 失败
+                  {recipient.diagnostic || recipient.status ? `：${recipient.diagnostic || recipient.status}` : ''}
+                </div>
+              ))}
+              {originalEmail && onEmailSelect ? (
+                <OriginalLink type="button" onClick={() => onEmailSelect(originalEmail.id)}>
+                  查看原邮件: {originalEmail.subject || '(无主题)'}
+                </OriginalLink>
+              ) : originalMessageId ? (
+                <div>原邮件: {originalMessageId}</div>
+              ) : null}
+            </DeliveryFailureCard>
+          )}
         </EmailHeader>
 
         {/* 邮件正文 */}
@@ -0,0 +1,90 @@
+/**
+ * AuthContext单元测试
+ * 测试登录凭据未能保存时的提示
+ */
+
+import React from 'react';
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { render, screen, fireEvent, waitFor } from '@testing-library/react';
+import '@testing-library/jest-dom';
+import { invoke } from '@tauri-apps/api/core';
+import { AuthProvider } from './AuthContext';
+import { useAuth } from '../hooks/useAuth';
+
+// Mock Tauri commands
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+/**
+ * 辅助组件：显示认证状态和提示，并提供登录按钮
+ */
+const AuthProbe: React.FC = () => {
+  const { isAuthenticated, warning, dismissWarning, login } = useAuth();
+  return (
+    <div>
+      <span>{isAuthenticated ? '已登录' : '未登录'}</span>
+      {warning && <p role="status">{warning}</p>}
+      <button onClick={() => login({ email: 'user@2925.com', password: 'secret' }).catch(() => {})}>登录</button>
+      <button onClick={dismissWarning}>关闭</button>
+    </div>
+  );
+};
+
+/**
+ * 模拟后端命令，`persisted` 为登录结果中的值
+ */
+const mockCommands = (persisted: boolean) => {
+  vi.mocked(invoke).mockImplementation(async (command: string) => {
+    switch (command) {
+      case 'login':
+        return { email: 'user@2925.com', token: 't', expiresAt: Date.now() + 60_000, persisted };
+      case 'load_session':
+      case 'load_preferences':
+        return null;
+      default:
+        return undefined;
+    }
+  });
+};
+
+describe('AuthContext', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('凭据未能保存时应该显示提示', async () => {
+    mockCommands(false);
+    render(
+      <AuthProvider>
+        <AuthProbe />
+      </AuthProvider>
+    );
+
+    fireEvent.click(await screen.findByRole('button', { name: '登录' }));
+
+    await waitFor(() => {
+      expect(screen.getByText('已登录')).toBeInTheDocument();
+    });
+    expect(screen.getByRole('status')).toHaveTextContent('无法保存登录凭据，下次启动需要重新登录');
+
+    fireEvent.click(screen.getByRole('button', { name: '关闭' }));
+    expect(screen.queryByRole('status')).not.toBeInTheDocument();
+  });
+
+  it('凭据已保存时不应该显示提示', async () => {
+    mockCommands(true);
+    render(
+      <AuthProvider>
+        <AuthProbe />
+      </AuthProvider>
+    );
+
+    fireEvent.click(await screen.findByRole('button', { name: '登录' }));
+
+    await waitFor(() => {
+      expect(screen.getByText('已登录')).toBeInTheDocument();
+    });
+    expect(screen.queryByRole('status')).not.toBeInTheDocument();
+  });
+});
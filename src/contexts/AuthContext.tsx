@@ -31,6 +31,16 @@ interface AuthContextValue extends AuthState {
    * @returns 会话是否有效
    */
   validateSession: () => Promise<boolean>;
+  
+  /**
+   * 需要提示用户的非致命问题，如登录凭据未能保存
+   */
+  warning: string | null;
+  
+  /**
+   * 关闭当前提示
+   */
+  dismissWarning: () => void;
 }
 
 /**
@@ -58,6 +68,15 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
     loading: true,
     error: null,
   });
+  // 非致命提示，与错误分开保存，登录成功后仍显示
+  const [warning, setWarning] = useState<string | null>(null);
+
+  /**
+   * 关闭当前提示
+   */
+  const dismissWarning = useCallback(() => {
+    setWarning(null);
+  }, []);
 
   /**
    * 验证会话是否过期
@@ -115,14 +134,14 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
         error: null,
       }));
 
-      // 调用Tauri登录命令
-      const session = await tauriCommands.auth.login(credentials);
-
-      // 保存会话到本地存储
-      await tauriCommands.storage.saveSession(session);
-      
-      // 保存密码到本地存储（用于后续IMAP操作）
-      await tauriCommands.storage.savePassword(credentials.password);
+      // 调用Tauri登录命令，登录成功后后端已保存会话和密码
+      const { persisted, ...session } = await tauriCommands.auth.login(credentials);
+      if (persisted === false) {
+        console.warn('无法保存登录凭据，下次启动需要重新登录');
+        setWarning('无法保存登录凭据，下次启动需要重新登录');
+      } else {
+        setWarning(null);
+      }
       
       // 保存自动登录偏好
       if (autoLogin) {
@@ -176,6 +195,7 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
     try {
       // 调用Tauri登出命令
       await tauriCommands.auth.logout();
+      setWarning(null);
 
       // 清除认证状态
       setAuthState({
@@ -273,6 +293,8 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
     login,
     logout,
     validateSession,
+    warning,
+    dismissWarning,
   };
 
   return (
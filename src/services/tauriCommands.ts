@@ -4,7 +4,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { LoginCredentials, AuthSession } from '../types/auth.types';
+import type { LoginCredentials, AuthSession, LoginResult } from '../types/auth.types';
 import type { Email } from '../types/email.types';
 import type { SubEmail } from '../types/subEmail.types';
 import type { UserPreferences } from '../types/preferences.types';
@@ -32,12 +32,12 @@ export const authCommands = {
   /**
    * 登录命令
    * @param credentials 登录凭据
-   * @returns 认证会话信息
+   * @returns 认证会话信息，`persisted` 为false时凭据未能保存
    * @throws {TauriCommandError} 当登录失败时抛出
    */
-  async login(credentials: LoginCredentials): Promise<AuthSession> {
+  async login(credentials: LoginCredentials): Promise<LoginResult> {
     try {
-      const session = await invoke<AuthSession>('login', {
+      const session = await invoke<LoginResult>('login', {
         email: credentials.email,
         password: credentials.password,
       });
@@ -281,6 +281,45 @@ export const logCommands = {
   },
 };
 
+/**
+ * 窗口相关的Tauri命令
+ */
+export const windowCommands = {
+  /**
+   * 退出应用
+   * @param remember 为true时之后点击关闭按钮直接退出，不再询问
+   * @throws {TauriCommandError} 当退出失败时抛出
+   */
+  async quitApp(remember: boolean): Promise<void> {
+    try {
+      await invoke<void>('quit_app', { remember });
+    } catch (error) {
+      throw new TauriCommandError(
+        '退出应用失败',
+        'quit_app',
+        error
+      );
+    }
+  },
+
+  /**
+   * 隐藏主窗口到托盘
+   * @param remember 为true时之后点击关闭按钮直接隐藏到托盘，不再询问
+   * @throws {TauriCommandError} 当隐藏失败时抛出
+   */
+  async hideToTray(remember: boolean): Promise<void> {
+    try {
+      await invoke<void>('hide_to_tray', { remember });
+    } catch (error) {
+      throw new TauriCommandError(
+        '隐藏窗口失败',
+        'hide_to_tray',
+        error
+      );
+    }
+  },
+};
+
 /**
  * 统一导出所有Tauri命令
  */
@@ -289,6 +328,7 @@ export const tauriCommands = {
   email: emailCommands,
   storage: storageCommands,
   log: logCommands,
+  window: windowCommands,
 };
 
 export default tauriCommands;
@@ -22,6 +22,16 @@ export interface AuthSession {
   expiresAt: number;
 }
 
+/**
+ * 登录命令结果
+ * 登录成功后后端已保存会话和密码；`persisted` 为false时（如系统密钥环不可用）
+ * 本次运行期间仍可使用，下次启动需要重新登录
+ */
+export interface LoginResult extends AuthSession {
+  /** 会话和密码是否已保存 */
+  persisted: boolean;
+}
+
 /**
  * 认证服务接口
  * 定义认证相关的所有操作
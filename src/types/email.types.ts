@@ -1,3 +1,48 @@
+/**
+ * 日历邀请中的事件
+ */
+export interface CalendarEvent {
+  /** iTIP方法，如 REQUEST、CANCEL */
+  method: string | null;
+  /** 会议已取消 */
+  cancelled: boolean;
+  summary: string | null;
+  /** ISO 8601开始时间，全天事件只有日期 */
+  start: string | null;
+  end: string | null;
+  /** 开始时间的时区（TZID） */
+  timezone: string | null;
+  allDay: boolean;
+  organizer: string | null;
+  location: string | null;
+  uid: string | null;
+}
+
+/**
+ * 退信中单个收件人的投递结果
+ */
+export interface RecipientStatus {
+  /** 最终收件人地址 */
+  recipient: string;
+  /** failed、delayed、delivered、relayed 或 expanded */
+  action: string;
+  /** 状态码，如 5.1.1 */
+  status: string | null;
+  /** 远程服务器给出的原因 */
+  diagnostic: string | null;
+}
+
+/**
+ * 退信（投递状态通知）中的投递状态
+ */
+export interface DeliveryStatus {
+  /** 生成退信的MTA */
+  reportingMta: string | null;
+  recipients: RecipientStatus[];
+  /** 原邮件的Message-ID，用于关联到原邮件 */
+  originalMessageId: string | null;
+}
+
 /**
  * 邮件接口
  * 表示单个邮件的完整信息
@@ -13,14 +58,169 @@ export interface Email {
   subject: string;
   /** 邮件正文内容 */
   body: string;
-  /** 邮件时间戳（毫秒） */
+  /** HTML正文，只有单封邮件详情中内嵌图片已替换为data URI */
+  bodyHtml?: string | null;
+  /** 会议邀请中的事件 */
+  calendarEvent?: CalendarEvent | null;
+  /** 退信中的投递状态 */
+  deliveryStatus?: DeliveryStatus | null;
+  /** 列表预览摘要 */
+  snippet?: string;
+  /** 邮件时间戳（毫秒），优先取Date头部 */
   timestamp: number;
+  /** 服务器接收时间（毫秒），取自INTERNALDATE */
+  receivedAt?: number | null;
   /** 是否已读 */
   isRead: boolean;
+  /** 是否已标记删除（尚未压缩文件夹） */
+  isDeleted?: boolean;
+  /** 是否已加星标 */
+  isFlagged?: boolean;
   /** 是否为子邮箱转发的邮件 */
   isSubEmailForwarded: boolean;
   /** 原始子邮箱地址（仅当isSubEmailForwarded为true时有值） */
-  originalSubEmail?: string;
+  originalSubEmail?: string | null;
+  /** 投递到的子邮箱后缀（取自Delivered-To / X-Original-To / To 头部） */
+  deliveredToSuffix?: string | null;
+  /** 邮件无法解析，仅包含从原始头部扫描出的基本信息 */
+  parseError?: boolean;
+  /** 抄送地址，没有抄送时为null */
+  cc?: string | null;
+  /** Message-ID，用于从退信关联到原邮件 */
+  messageId?: string | null;
+  /** 仅包含信封信息，正文需另外获取 */
+  envelopeOnly?: boolean;
+  /** 合并列表中 `id` 所属的文件夹 */
+  folder?: string;
+  /** 按Message-ID去重后，邮件出现的所有文件夹 */
+  folders?: string[];
+  /** 邮件大小（字节），仅 `largest_messages` 返回 */
+  size?: number;
+  /** 自定义关键字（标签），不含系统标记 */
+  keywords?: string[];
+  /** 由发件人地址计算的头像颜色（`#rrggbb`），同一发件人始终相同 */
+  senderColor?: string | null;
+  /** 发件人地址的Gravatar哈希（MD5） */
+  gravatarHash?: string | null;
+  /** 休眠未到期时的唤醒时间戳（毫秒），界面应隐藏该邮件 */
+  snoozedUntil?: number;
+  /** 邮件列表的退订方式（来自 `List-Unsubscribe` 头部），信封列表中为null */
+  unsubscribe?: EmailUnsubscribe | null;
+}
+
+/**
+ * 邮件列表退订方式
+ * `oneClick` 为true时可直接调用 `unsubscribe` 命令一键退订
+ */
+export interface EmailUnsubscribe {
+  /** 退订邮件的mailto链接 */
+  mailto: string | null;
+  /** 退订网页链接（http或https） */
+  http: string | null;
+  /** 支持RFC 8058一键退订 */
+  oneClick: boolean;
+}
+
+/**
+ * 邮件头部字段
+ * 由 `get_headers` 命令按原顺序返回，同名字段可能出现多次
+ */
+export interface HeaderField {
+  /** 字段名称 */
+  name: string;
+  /** 展开折行并按RFC 2047解码后的值 */
+  value: string;
+  /** 展开折行后的原始值 */
+  raw: string;
+}
+
+/**
+ * 会话树节点
+ */
+export interface ThreadNode {
+  /** 邮件ID（UID），服务器返回的会话中缺失的共同父邮件为null */
+  id: string | null;
+  /** 回复该邮件的邮件 */
+  children: ThreadNode[];
+}
+
+/**
+ * 文件夹的会话列表
+ * 由 `fetch_threads` 命令返回
+ */
+export interface ThreadList {
+  threads: ThreadNode[];
+  /** 由服务器THREAD扩展计算，false时为本地根据引用头部构建 */
+  serverSide: boolean;
+}
+
+/**
+ * 未读数统计
+ * 由 `all_unread_counts` 命令返回
+ */
+export interface UnreadCounts {
+  /** 文件夹名称到未读数的映射 */
+  folders: Record<string, number>;
+  /** 所有文件夹的未读总数 */
+  total: number;
+  /** 统计时间戳（毫秒） */
+  updatedAt: number;
+}
+
+/**
+ * 联系人建议
+ * 由 `contact_suggestions` 命令返回，按往来次数和最近往来时间排序
+ */
+export interface Contact {
+  /** 邮箱地址（小写） */
+  address: string;
+  /** 最近一次出现的显示名称 */
+  name: string | null;
+  /** 出现在多少封邮件中 */
+  count: number;
+  /** 最近一次出现的邮件时间（毫秒） */
+  lastSeen: number;
+}
+
+/**
+ * 文件夹的阅读位置
+ * 由 `get_read_position` 命令返回
+ */
+export interface ReadPosition {
+  /** 最后查看的邮件UID */
+  uid: number;
+  /** 记录时文件夹的UIDVALIDITY */
+  uidValidity: number;
+  /** 记录时间（毫秒） */
+  updatedAt: number;
+}
+
+/**
+ * 服务器上的自动回复状态
+ * 由 `get_vacation_status` 命令返回
+ */
+export interface VacationStatus {
+  /** 服务器是否支持METADATA扩展，false时其余字段无意义 */
+  supported: boolean;
+  /** 自动回复是否启用 */
+  enabled: boolean;
+  /** 自动回复正文，未设置时为null */
+  text: string | null;
+}
+
+/**
+ * 要同步到服务器草稿箱的草稿
+ * 作为 `sync_draft_to_server` 命令的参数
+ */
+export interface EmailDraft {
+  /** 草稿ID，同一份草稿每次同步使用相同的ID */
+  id: string;
+  /** 收件人，多个地址以逗号分隔，可以为空 */
+  to: string;
+  subject: string;
+  body: string;
+  /** 附件的本地路径 */
+  attachmentPaths?: string[];
 }
 
 /**
@@ -7,12 +7,22 @@
 export type {
   LoginCredentials,
   AuthSession,
+  LoginResult,
   AuthService,
 } from './auth.types';
 
 // 邮件相关类型
 export type {
   Email,
+  EmailUnsubscribe,
+  HeaderField,
+  ThreadNode,
+  ThreadList,
+  UnreadCounts,
+  Contact,
+  ReadPosition,
+  VacationStatus,
+  EmailDraft,
   EmailService,
 } from './email.types';
 
@@ -20,6 +30,7 @@ export type {
 export type {
   SubEmail,
   SubEmailStatus,
+  SubEmailReconcile,
   SubEmailService,
 } from './subEmail.types';
 
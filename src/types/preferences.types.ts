@@ -24,6 +24,24 @@ export interface UserPreferences {
   windowSize: WindowSize;
   /** 是否启用自动登录 */
   autoLogin: boolean;
+  /** 发件人显示名称 */
+  displayName?: string | null;
+  /** 附加在发送邮件末尾的纯文本签名（自动添加 `-- ` 分隔线），不设置时不附加 */
+  signature?: string | null;
+  /** 附件总大小上限（MB） */
+  attachmentLimitMb?: number;
+  /** 每个文件夹离线缓存的邮件数上限，0表示不缓存 */
+  cacheLimit?: number;
+  /** 离线缓存是否压缩保存，默认true */
+  compressCache?: boolean;
+  /** 已发送文件夹中邮件的保留天数，超过的邮件自动删除；不设置时不删除 */
+  sentRetentionDays?: number;
+  /** 打开邮件时标记为已读，默认true */
+  markReadOnOpen?: boolean;
+  /** 后端日志级别，默认warn */
+  logLevel?: 'error' | 'warn' | 'info' | 'debug';
+  /** 点击窗口关闭按钮时的行为，默认quit；ask时后端发送 `close-requested` 事件 */
+  closeAction?: 'quit' | 'minimizeToTray' | 'ask';
 }
 
 /**
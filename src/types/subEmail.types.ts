@@ -1,7 +1,8 @@
 /**
  * 子邮箱状态类型
+ * `orphaned` 表示核对时发现从未收到过邮件
  */
-export type SubEmailStatus = 'creating' | 'active' | 'failed';
+export type SubEmailStatus = 'creating' | 'active' | 'failed' | 'orphaned';
 
 /**
  * 子邮箱接口
@@ -18,6 +19,18 @@ export interface SubEmail {
   status: SubEmailStatus;
 }
 
+/**
+ * 子邮箱核对结果（`reconcile_sub_emails`）
+ */
+export interface SubEmailReconcile {
+  /** 本次标记为 `orphaned` 的地址 */
+  orphaned: string[];
+  /** 恢复为 `active` 的地址 */
+  restored: string[];
+  /** 更新后的完整子邮箱列表 */
+  subEmails: SubEmail[];
+}
+
 /**
  * 子邮箱服务接口
  * 定义子邮箱相关的所有操作